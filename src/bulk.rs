@@ -0,0 +1,730 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_program::instruction::Instruction;
+use solana_sdk::{
+    hash::Hash,
+    signature::Signature,
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::{
+    errors::{MetabossError, Result},
+    program_errors::map_client_error,
+    rpc::Rpc,
+    transaction::{add_compute_budget, ComputeBudget, MAX_CONFIRMATION_WAIT},
+};
+
+/// How often a transaction journaled as sent is polled while waiting for it to settle.
+const SENT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Status of a single item as recorded in the journal.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ItemStatus {
+    Pending,
+    /// Journaled before the transaction is sent. The block height lets a resumed run wait
+    /// for the blockhash to expire before deciding the transaction didn't land.
+    Sent {
+        signature: String,
+        last_valid_block_height: u64,
+    },
+    Confirmed {
+        signature: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    #[serde(flatten)]
+    pub status: ItemStatus,
+}
+
+/// Append-only JSON lines log of item statuses. The last entry for an id wins.
+pub struct Journal {
+    file: Mutex<File>,
+}
+
+impl Journal {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Latest recorded status of every item in the journal at `path`. A missing file is
+    /// treated as an empty journal.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<HashMap<String, ItemStatus>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut statuses = HashMap::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A torn final line from an interrupted write is ignored.
+            if let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) {
+                statuses.insert(entry.id, entry.status);
+            }
+        }
+
+        Ok(statuses)
+    }
+
+    pub fn record(&self, id: &str, status: &ItemStatus) -> Result<()> {
+        let entry = JournalEntry {
+            id: id.to_string(),
+            status: status.clone(),
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BulkConfig {
+    pub journal_path: PathBuf,
+    /// Number of items processed at the same time.
+    pub concurrency: usize,
+    /// Maximum number of items started per second. `None` disables the limit.
+    pub requests_per_second: Option<u32>,
+    /// How long a fetched blockhash is reused before a new one is requested.
+    pub blockhash_ttl: Duration,
+    /// Compute budget applied to every transaction. `None` sends the instructions as is.
+    pub compute_budget: Option<ComputeBudget>,
+}
+
+impl BulkConfig {
+    pub fn new<P: Into<PathBuf>>(journal_path: P) -> Self {
+        Self {
+            journal_path: journal_path.into(),
+            concurrency: 8,
+            requests_per_second: Some(10),
+            blockhash_ttl: Duration::from_secs(30),
+            compute_budget: None,
+        }
+    }
+}
+
+/// Run an operation over many items, e.g. mints to verify or update.
+///
+/// `build_ixs` produces the instructions for one item id, typically by calling one of
+/// the `*_ix` functions. Every status change is written to the journal, so re-running
+/// with the same journal skips items that already confirmed. Items journaled as sent
+/// are looked up in the transaction history, and only sent again once their blockhash
+/// has expired without them landing.
+///
+/// Returns the final status of every item, in input order. A failure to write the
+/// journal stops the run and is returned as an error, since sending without a journal
+/// entry could send an item twice on resume.
+pub fn execute_bulk<R, F>(
+    client: &R,
+    signers: &[&(dyn Signer + Sync)],
+    ids: &[String],
+    config: &BulkConfig,
+    build_ixs: F,
+) -> Result<Vec<JournalEntry>>
+where
+    R: Rpc + Sync + ?Sized,
+    F: Fn(&R, &str) -> Result<Vec<Instruction>> + Sync,
+{
+    if signers.is_empty() {
+        return Err(MetabossError::validation(
+            "At least one signer is required to pay for the transactions",
+        ));
+    }
+
+    let previous = Journal::load(&config.journal_path)?;
+    let journal = Journal::open(&config.journal_path)?;
+
+    for id in ids {
+        if matches!(
+            previous.get(id),
+            None | Some(ItemStatus::Pending | ItemStatus::Failed { .. })
+        ) {
+            journal.record(id, &ItemStatus::Pending)?;
+        }
+    }
+
+    let limiter = config
+        .requests_per_second
+        .map(|rps| RateLimiter::new(Duration::from_secs(1) / rps.max(1)));
+    let blockhashes = BlockhashCache::new(config.blockhash_ttl);
+
+    let results: Mutex<Vec<Option<ItemStatus>>> = Mutex::new(vec![None; ids.len()]);
+    let queue = Mutex::new(ids.iter().enumerate());
+    let journal_error = Mutex::new(None);
+
+    let ctx = Context {
+        client,
+        signers,
+        journal: &journal,
+        limiter: limiter.as_ref(),
+        blockhashes: &blockhashes,
+        compute_budget: config.compute_budget.as_ref(),
+    };
+
+    thread::scope(|scope| {
+        for _ in 0..config.concurrency.max(1) {
+            scope.spawn(|| loop {
+                if journal_error.lock().unwrap().is_some() {
+                    break;
+                }
+                let next = queue.lock().unwrap().next();
+                let Some((index, id)) = next else {
+                    break;
+                };
+
+                match ctx.process(id, previous.get(id), &build_ixs) {
+                    Ok(status) => results.lock().unwrap()[index] = Some(status),
+                    Err(e) => {
+                        journal_error.lock().unwrap().get_or_insert(e);
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = journal_error.into_inner().unwrap() {
+        return Err(e);
+    }
+
+    let entries = ids
+        .iter()
+        .zip(results.into_inner().unwrap())
+        .map(|(id, status)| JournalEntry {
+            id: id.clone(),
+            status: status.unwrap_or(ItemStatus::Pending),
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+struct Context<'a, R: ?Sized> {
+    client: &'a R,
+    signers: &'a [&'a (dyn Signer + Sync)],
+    journal: &'a Journal,
+    limiter: Option<&'a RateLimiter>,
+    blockhashes: &'a BlockhashCache,
+    compute_budget: Option<&'a ComputeBudget>,
+}
+
+impl<R: Rpc + ?Sized> Context<'_, R> {
+    /// Settle one item. Only journal failures are returned as errors; failures of the
+    /// item itself are recorded as [`ItemStatus::Failed`], unless the transaction may
+    /// still land, in which case it stays journaled as sent.
+    fn process<F>(
+        &self,
+        id: &str,
+        previous: Option<&ItemStatus>,
+        build_ixs: &F,
+    ) -> Result<ItemStatus>
+    where
+        F: Fn(&R, &str) -> Result<Vec<Instruction>>,
+    {
+        match previous {
+            Some(status @ ItemStatus::Confirmed { .. }) => return Ok(status.clone()),
+            Some(
+                status @ ItemStatus::Sent {
+                    signature,
+                    last_valid_block_height,
+                },
+            ) => match self.check_sent(signature, *last_valid_block_height) {
+                Ok(Some(status)) => {
+                    self.journal.record(id, &status)?;
+                    return Ok(status);
+                }
+                Ok(None) => {}
+                // Whether it landed is unknown, so it stays journaled as sent and is
+                // checked again on the next run.
+                Err(_) => return Ok(status.clone()),
+            },
+            _ => {}
+        }
+
+        let (tx, ixs, last_valid_block_height) = match self.build_tx(id, build_ixs) {
+            Ok(built) => built,
+            Err(e) => return self.fail(id, e),
+        };
+
+        let sent = ItemStatus::Sent {
+            signature: tx.signatures[0].to_string(),
+            last_valid_block_height,
+        };
+        self.journal.record(id, &sent)?;
+
+        let signature = match self.client.send_and_confirm_transaction(&tx) {
+            Ok(signature) => signature,
+            Err(e) if is_rejected(&e) => return self.fail(id, map_client_error(e, &ixs)),
+            // A timeout or RPC failure doesn't say whether the transaction landed, so the
+            // next run settles it with `check_sent` instead of sending it again.
+            Err(_) => return Ok(sent),
+        };
+
+        let status = ItemStatus::Confirmed {
+            signature: signature.to_string(),
+        };
+        self.journal.record(id, &status)?;
+
+        Ok(status)
+    }
+
+    /// Wait for a transaction journaled as sent to settle. Returns `None` once its
+    /// blockhash has expired without it landing successfully, after which it is safe to
+    /// send the item again.
+    fn check_sent(
+        &self,
+        signature: &str,
+        last_valid_block_height: u64,
+    ) -> Result<Option<ItemStatus>> {
+        let sig = Signature::from_str(signature).map_err(|e| {
+            MetabossError::validation(format!("Invalid signature {signature} in journal: {e}"))
+        })?;
+        let commitment = self.client.commitment();
+
+        let started = Instant::now();
+        loop {
+            // The height is read first so that a transaction landing right before expiry
+            // still shows up in the status.
+            let height = self.client.get_block_height_with_commitment(commitment)?;
+            let status = self
+                .client
+                .get_signature_statuses_with_history(&[sig])?
+                .value
+                .into_iter()
+                .next()
+                .flatten();
+
+            match status {
+                Some(status) if status.satisfies_commitment(commitment) => {
+                    return Ok(match status.err {
+                        None => Some(ItemStatus::Confirmed {
+                            signature: signature.to_string(),
+                        }),
+                        // A failed transaction changed nothing, so the item is sent again.
+                        Some(_) => None,
+                    });
+                }
+                None if height > last_valid_block_height => return Ok(None),
+                _ => {}
+            }
+
+            if started.elapsed() >= MAX_CONFIRMATION_WAIT {
+                return Err(MetabossError::UnknownStatus {
+                    signature: sig,
+                    reason: "timed out waiting for the transaction to settle".to_string(),
+                });
+            }
+            thread::sleep(SENT_POLL_INTERVAL);
+        }
+    }
+
+    fn build_tx<F>(
+        &self,
+        id: &str,
+        build_ixs: &F,
+    ) -> Result<(VersionedTransaction, Vec<Instruction>, u64)>
+    where
+        F: Fn(&R, &str) -> Result<Vec<Instruction>>,
+    {
+        if let Some(limiter) = self.limiter {
+            limiter.wait();
+        }
+
+        let signers: Vec<&dyn Signer> = self.signers.iter().map(|s| *s as &dyn Signer).collect();
+
+        let mut ixs = build_ixs(self.client, id)?;
+        if let Some(budget) = self.compute_budget {
+            ixs = add_compute_budget(self.client, &signers, &ixs, budget)?;
+        }
+
+        let (blockhash, last_valid_block_height) = self.blockhashes.get(self.client)?;
+        let tx = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&signers[0].pubkey()),
+            &signers,
+            blockhash,
+        )
+        .into();
+
+        Ok((tx, ixs, last_valid_block_height))
+    }
+
+    fn fail(&self, id: &str, error: MetabossError) -> Result<ItemStatus> {
+        let status = ItemStatus::Failed {
+            error: error.to_string(),
+        };
+        self.journal.record(id, &status)?;
+
+        Ok(status)
+    }
+}
+
+/// Whether a send failed for certain: the transaction was rejected by preflight, or it
+/// landed and failed. Either way, it changed nothing and the item can be sent again.
+fn is_rejected(err: &ClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::TransactionError(_)
+            | ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(_),
+                ..
+            })
+    )
+}
+
+/// Spaces out calls so that at most one starts per `interval`.
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn wait(&self) {
+        let now = Instant::now();
+        let start = {
+            let mut next = self.next.lock().unwrap();
+            let start = (*next).max(now);
+            *next = start + self.interval;
+            start
+        };
+
+        if start > now {
+            thread::sleep(start - now);
+        }
+    }
+}
+
+/// Shares one blockhash, with its last valid block height, across sends until it is
+/// older than `ttl`.
+struct BlockhashCache {
+    ttl: Duration,
+    cached: Mutex<Option<(Hash, u64, Instant)>>,
+}
+
+impl BlockhashCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cached: Mutex::new(None),
+        }
+    }
+
+    fn get(&self, client: &(impl Rpc + ?Sized)) -> Result<(Hash, u64)> {
+        let mut cached = self.cached.lock().unwrap();
+
+        if let Some((hash, last_valid_block_height, fetched)) = *cached {
+            if fetched.elapsed() < self.ttl {
+                return Ok((hash, last_valid_block_height));
+            }
+        }
+
+        let (hash, last_valid_block_height) =
+            client.get_latest_blockhash_with_commitment(client.commitment())?;
+        *cached = Some((hash, last_valid_block_height, Instant::now()));
+
+        Ok((hash, last_valid_block_height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+    };
+    use solana_sdk::pubkey::Pubkey;
+
+    fn temp_journal(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "metaboss_lib_{}_{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_journal_round_trip_last_entry_wins() {
+        let path = temp_journal("round_trip");
+        let journal = Journal::open(&path).unwrap();
+
+        journal.record("a", &ItemStatus::Pending).unwrap();
+        journal
+            .record(
+                "a",
+                &ItemStatus::Sent {
+                    signature: "sig".to_string(),
+                    last_valid_block_height: 100,
+                },
+            )
+            .unwrap();
+        journal
+            .record(
+                "b",
+                &ItemStatus::Failed {
+                    error: "boom".to_string(),
+                },
+            )
+            .unwrap();
+        journal
+            .record(
+                "a",
+                &ItemStatus::Confirmed {
+                    signature: "sig".to_string(),
+                },
+            )
+            .unwrap();
+
+        let statuses = Journal::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(
+            statuses["a"],
+            ItemStatus::Confirmed {
+                signature: "sig".to_string()
+            }
+        );
+        assert_eq!(
+            statuses["b"],
+            ItemStatus::Failed {
+                error: "boom".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_journal_entry_format() {
+        let entry = JournalEntry {
+            id: "mint".to_string(),
+            status: ItemStatus::Sent {
+                signature: "sig".to_string(),
+                last_valid_block_height: 100,
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"id":"mint","status":"sent","signature":"sig","last_valid_block_height":100}"#
+        );
+    }
+
+    #[test]
+    fn test_journal_load_missing_file() {
+        let path = temp_journal("missing");
+        assert!(Journal::load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_execute_bulk_skips_confirmed_items() {
+        let path = temp_journal("resume");
+        let journal = Journal::open(&path).unwrap();
+        journal
+            .record(
+                "done",
+                &ItemStatus::Confirmed {
+                    signature: "sig".to_string(),
+                },
+            )
+            .unwrap();
+
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let payer = solana_sdk::signature::Keypair::new();
+        let mut config = BulkConfig::new(&path);
+        config.requests_per_second = None;
+
+        let built = Mutex::new(Vec::new());
+        let entries = execute_bulk(
+            &client,
+            &[&payer],
+            &["done".to_string(), "fails".to_string()],
+            &config,
+            |_, id| {
+                built.lock().unwrap().push(id.to_string());
+                Err(MetabossError::Validation(format!(
+                    "no instructions for {id}"
+                )))
+            },
+        )
+        .unwrap();
+
+        let statuses = Journal::load(&path).unwrap();
+        let lines = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(built.into_inner().unwrap(), vec!["fails".to_string()]);
+        // Queued items are journaled as pending before they are built.
+        assert!(lines.contains(r#"{"id":"fails","status":"pending"}"#));
+        assert!(!lines.contains(r#"{"id":"done","status":"pending"}"#));
+        assert_eq!(
+            entries[0].status,
+            ItemStatus::Confirmed {
+                signature: "sig".to_string()
+            }
+        );
+        assert!(matches!(entries[1].status, ItemStatus::Failed { .. }));
+        assert!(matches!(statuses["fails"], ItemStatus::Failed { .. }));
+    }
+
+    fn resume_sent_item(client: &RpcClient, name: &str) -> (Vec<String>, ItemStatus) {
+        let path = temp_journal(name);
+        let signature = Signature::new_unique().to_string();
+        Journal::open(&path)
+            .unwrap()
+            .record(
+                "sent",
+                &ItemStatus::Sent {
+                    signature,
+                    last_valid_block_height: 0,
+                },
+            )
+            .unwrap();
+
+        let payer = solana_sdk::signature::Keypair::new();
+        let mut config = BulkConfig::new(&path);
+        config.requests_per_second = None;
+
+        let built = Mutex::new(Vec::new());
+        let entries = execute_bulk(
+            client,
+            &[&payer],
+            &["sent".to_string()],
+            &config,
+            |_, id| {
+                built.lock().unwrap().push(id.to_string());
+                Err(MetabossError::validation("not sent in tests"))
+            },
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        (built.into_inner().unwrap(), entries[0].status.clone())
+    }
+
+    #[test]
+    fn test_execute_bulk_settles_landed_sent_item() {
+        // The mock reports every signature as finalized.
+        let client = RpcClient::new_mock("succeeds".to_string());
+
+        let (built, status) = resume_sent_item(&client, "sent_landed");
+
+        assert!(built.is_empty());
+        assert!(matches!(status, ItemStatus::Confirmed { .. }));
+    }
+
+    #[test]
+    fn test_execute_bulk_resends_expired_sent_item() {
+        // The mock knows no signatures and its block height is past 0.
+        let client = RpcClient::new_mock("sig_not_found".to_string());
+
+        let (built, status) = resume_sent_item(&client, "sent_expired");
+
+        assert_eq!(built, vec!["sent".to_string()]);
+        assert!(matches!(status, ItemStatus::Failed { .. }));
+    }
+
+    #[test]
+    fn test_execute_bulk_keeps_sent_item_when_send_is_ambiguous() {
+        let path = temp_journal("send_ambiguous");
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetLatestBlockhash,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": Hash::new_unique().to_string(),
+                    "lastValidBlockHeight": 100,
+                },
+            }),
+        );
+        // Every other request gets an unparseable response, so the send fails without
+        // saying whether the transaction landed.
+        let client = RpcClient::new_mock_with_mocks("fails".to_string(), mocks);
+        let payer = solana_sdk::signature::Keypair::new();
+        let mut config = BulkConfig::new(&path);
+        config.requests_per_second = None;
+
+        let entries = execute_bulk(
+            &client,
+            &[&payer],
+            &["item".to_string()],
+            &config,
+            |_, _| {
+                Ok(vec![Instruction::new_with_bytes(
+                    Pubkey::new_unique(),
+                    &[],
+                    vec![],
+                )])
+            },
+        )
+        .unwrap();
+
+        let statuses = Journal::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            entries[0].status,
+            ItemStatus::Sent {
+                last_valid_block_height: 100,
+                ..
+            }
+        ));
+        assert_eq!(statuses["item"], entries[0].status);
+    }
+
+    #[test]
+    fn test_rate_limiter_spaces_calls() {
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+        let start = Instant::now();
+
+        for _ in 0..4 {
+            limiter.wait();
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(60));
+    }
+}
@@ -1,28 +1,52 @@
-use anyhow::Result;
-use mpl_token_metadata::{instructions::BurnV1Builder, types::TokenStandard};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{
-    signature::{Keypair, Signature},
-    signer::Signer,
-};
+use mpl_token_metadata::{accounts::Metadata, instructions::BurnV1Builder, types::TokenStandard};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{signature::Signature, signer::Signer};
 
 use crate::{
     data::Asset,
     decode::ToPubkey,
     derive::{derive_metadata_pda, derive_token_record_pda},
-    transaction::send_and_confirm_tx,
+    errors::Result,
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::{send_and_confirm_tx_with_compute_budget, ComputeBudget},
 };
 
 pub enum BurnAssetArgs<'a, P1, P2: ToPubkey> {
     V1 {
-        authority: &'a Keypair,
+        authority: &'a dyn Signer,
         mint: P1,
         token: P2,
         amount: u64,
+        compute_budget: ComputeBudget,
     },
 }
 
-pub fn burn_asset<P1, P2>(client: &RpcClient, args: BurnAssetArgs<P1, P2>) -> Result<Signature>
+impl<'a, P1: ToPubkey, P2: ToPubkey> BurnAssetArgs<'a, P1, P2> {
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<BurnAssetArgs<'a, Pubkey, Pubkey>> {
+        let BurnAssetArgs::V1 {
+            authority,
+            mint,
+            token,
+            amount,
+            compute_budget,
+        } = self;
+
+        Ok(BurnAssetArgs::V1 {
+            authority,
+            mint: mint.to_pubkey()?,
+            token: token.to_pubkey()?,
+            amount,
+            compute_budget,
+        })
+    }
+}
+
+pub fn burn_asset<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<P1, P2>,
+) -> Result<Signature>
 where
     P1: ToPubkey,
     P2: ToPubkey,
@@ -32,25 +56,98 @@ where
     }
 }
 
-fn burn_asset_v1<P1, P2>(client: &RpcClient, args: BurnAssetArgs<P1, P2>) -> Result<Signature>
+pub fn burn_asset_ix<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<P1, P2>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    match args {
+        BurnAssetArgs::V1 { .. } => burn_asset_v1_ix(client, args),
+    }
+}
+
+pub fn burn_asset_simulate<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<P1, P2>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    match args {
+        BurnAssetArgs::V1 { .. } => burn_asset_v1_simulate(client, args),
+    }
+}
+
+fn burn_asset_v1<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<P1, P2>,
+) -> Result<Signature>
 where
     P1: ToPubkey,
     P2: ToPubkey,
 {
+    let BurnAssetArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
+
+    let burn_ix = burn_asset_v1_ix(client, args)?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[burn_ix], &compute_budget)
+}
+
+fn burn_asset_v1_simulate<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<P1, P2>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let BurnAssetArgs::V1 { authority, .. } = args;
+
+    let ix = burn_asset_v1_ix(client, args)?;
+
+    simulate_tx(client, &[authority], &[ix])
+}
+
+fn burn_asset_v1_ix<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<P1, P2>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let args = args.resolve()?;
+    let BurnAssetArgs::V1 { mint, .. } = args;
+
+    let md = Asset::new(mint).get_metadata(client)?;
+
+    Ok(build_burn_asset_v1_ix(args, md))
+}
+
+/// Build the burn instruction from resolved arguments and the asset's metadata.
+pub(crate) fn build_burn_asset_v1_ix(
+    args: BurnAssetArgs<Pubkey, Pubkey>,
+    md: Metadata,
+) -> Instruction {
     let BurnAssetArgs::V1 {
         authority,
         mint,
         token,
         amount,
+        ..
     } = args;
 
-    let mint = mint.to_pubkey()?;
     let mut asset = Asset::new(mint);
 
-    let md = asset.get_metadata(client)?;
-
-    let token = token.to_pubkey()?;
-
     let mut burn_builder = BurnV1Builder::new();
     burn_builder
         .authority(authority.pubkey())
@@ -96,7 +193,5 @@ where
     };
     burn_builder.collection_metadata(collection_metadata);
 
-    let burn_ix = burn_builder.instruction();
-
-    send_and_confirm_tx(client, &[authority], &[burn_ix])
+    burn_builder.instruction()
 }
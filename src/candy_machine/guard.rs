@@ -0,0 +1,324 @@
+//! Candy Guard accounts and their guard sets.
+//!
+//! After the account header the program stores the default guard set, then a `u32`
+//! group count and, for each group, a 6-byte label followed by its guard set. A guard set
+//! is a `u64` of feature flags followed by every enabled guard in flag order, each in a
+//! fixed-size slot.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+
+use super::check_anchor_account;
+use crate::{
+    constants::CANDY_GUARD_PROGRAM_ID,
+    decode::{errors::DecodeError, ToPubkey},
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+};
+
+/// Length of a group label, padded with null bytes.
+const MAX_LABEL_SIZE: usize = 6;
+
+/// Number of guards the crate knows; a guard set with a higher flag set fails to decode.
+const GUARD_COUNT: u32 = 21;
+
+/// A guard with the size of the slot it's stored in.
+trait Guard: BorshDeserialize {
+    const SIZE: usize;
+}
+
+macro_rules! guards {
+    ($($name:ident [$size:expr] { $($field:ident: $ty:ty),* $(,)? })*) => {
+        $(
+            #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+            pub struct $name {
+                $(pub $field: $ty,)*
+            }
+
+            impl Guard for $name {
+                const SIZE: usize = $size;
+            }
+        )*
+    };
+}
+
+guards! {
+    BotTax [9] { lamports: u64, last_instruction: bool }
+    SolPayment [40] { lamports: u64, destination: Pubkey }
+    TokenPayment [72] { amount: u64, mint: Pubkey, destination_ata: Pubkey }
+    StartDate [8] { date: i64 }
+    ThirdPartySigner [32] { signer_key: Pubkey }
+    TokenGate [40] { amount: u64, mint: Pubkey }
+    Gatekeeper [33] { gatekeeper_network: Pubkey, expire_on_use: bool }
+    EndDate [8] { date: i64 }
+    AllowList [32] { merkle_root: [u8; 32] }
+    MintLimit [3] { id: u8, limit: u16 }
+    NftPayment [64] { required_collection: Pubkey, destination: Pubkey }
+    RedeemedAmount [8] { maximum: u64 }
+    AddressGate [32] { address: Pubkey }
+    NftGate [32] { required_collection: Pubkey }
+    NftBurn [32] { required_collection: Pubkey }
+    TokenBurn [40] { amount: u64, mint: Pubkey }
+    FreezeSolPayment [40] { lamports: u64, destination: Pubkey }
+    FreezeTokenPayment [72] { amount: u64, mint: Pubkey, destination_ata: Pubkey }
+    ProgramGate [4 + 5 * 32] { additional: Vec<Pubkey> }
+    Allocation [5] { id: u8, limit: u32 }
+    Token2022Payment [72] { amount: u64, mint: Pubkey, destination_ata: Pubkey }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuardSet {
+    pub bot_tax: Option<BotTax>,
+    pub sol_payment: Option<SolPayment>,
+    pub token_payment: Option<TokenPayment>,
+    pub start_date: Option<StartDate>,
+    pub third_party_signer: Option<ThirdPartySigner>,
+    pub token_gate: Option<TokenGate>,
+    pub gatekeeper: Option<Gatekeeper>,
+    pub end_date: Option<EndDate>,
+    pub allow_list: Option<AllowList>,
+    pub mint_limit: Option<MintLimit>,
+    pub nft_payment: Option<NftPayment>,
+    pub redeemed_amount: Option<RedeemedAmount>,
+    pub address_gate: Option<AddressGate>,
+    pub nft_gate: Option<NftGate>,
+    pub nft_burn: Option<NftBurn>,
+    pub token_burn: Option<TokenBurn>,
+    pub freeze_sol_payment: Option<FreezeSolPayment>,
+    pub freeze_token_payment: Option<FreezeTokenPayment>,
+    pub program_gate: Option<ProgramGate>,
+    pub allocation: Option<Allocation>,
+    pub token2022_payment: Option<Token2022Payment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub label: String,
+    pub guards: GuardSet,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandyGuard {
+    pub base: Pubkey,
+    pub bump: u8,
+    pub authority: Pubkey,
+    /// Guards that apply when minting without a group.
+    pub default: GuardSet,
+    pub groups: Vec<Group>,
+}
+
+#[derive(BorshDeserialize)]
+struct CandyGuardHeader {
+    base: Pubkey,
+    bump: u8,
+    authority: Pubkey,
+}
+
+/// Length of the `CandyGuard` header after the discriminator.
+const HEADER_SIZE: usize = 32 + 1 + 32;
+
+pub fn decode_candy_guard<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    candy_guard: P,
+) -> Result<CandyGuard> {
+    let pubkey = candy_guard.to_pubkey()?;
+    let account = client.get_account(&pubkey)?;
+
+    decode_candy_guard_from_bytes(&account.owner, &account.data)
+        .map_err(|e| MetabossError::from(e).for_account(&pubkey))
+}
+
+pub fn decode_candy_guard_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<CandyGuard, DecodeError> {
+    let body = check_anchor_account(owner, &CANDY_GUARD_PROGRAM_ID, data, "CandyGuard")?;
+
+    let header = CandyGuardHeader::deserialize(&mut &body[..])
+        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))?;
+
+    let mut reader = GuardReader {
+        data: body,
+        cursor: HEADER_SIZE,
+    };
+
+    let default = reader.guard_set()?;
+    let group_count = u32::from_le_bytes(reader.take(4)?.try_into().expect("slice is 4 bytes"));
+
+    let groups = (0..group_count)
+        .map(|_| {
+            let label = String::from_utf8_lossy(reader.take(MAX_LABEL_SIZE)?)
+                .trim_end_matches('\0')
+                .to_string();
+
+            Ok(Group {
+                label,
+                guards: reader.guard_set()?,
+            })
+        })
+        .collect::<Result<_, DecodeError>>()?;
+
+    Ok(CandyGuard {
+        base: header.base,
+        bump: header.bump,
+        authority: header.authority,
+        default,
+        groups,
+    })
+}
+
+struct GuardReader<'a> {
+    data: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> GuardReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let bytes = self
+            .data
+            .get(self.cursor..self.cursor + len)
+            .ok_or_else(|| {
+                DecodeError::DeserializationFailed("candy guard data is truncated".to_string())
+            })?;
+        self.cursor += len;
+
+        Ok(bytes)
+    }
+
+    fn guard_set(&mut self) -> Result<GuardSet, DecodeError> {
+        let features = u64::from_le_bytes(self.take(8)?.try_into().expect("slice is 8 bytes"));
+
+        if features >> GUARD_COUNT != 0 {
+            return Err(DecodeError::DeserializationFailed(format!(
+                "unsupported guards enabled in feature flags {features:#x}"
+            )));
+        }
+
+        let mut bit = 0;
+        let mut next = || {
+            let enabled = features & (1 << bit) != 0;
+            bit += 1;
+            enabled
+        };
+
+        Ok(GuardSet {
+            bot_tax: self.guard(next())?,
+            sol_payment: self.guard(next())?,
+            token_payment: self.guard(next())?,
+            start_date: self.guard(next())?,
+            third_party_signer: self.guard(next())?,
+            token_gate: self.guard(next())?,
+            gatekeeper: self.guard(next())?,
+            end_date: self.guard(next())?,
+            allow_list: self.guard(next())?,
+            mint_limit: self.guard(next())?,
+            nft_payment: self.guard(next())?,
+            redeemed_amount: self.guard(next())?,
+            address_gate: self.guard(next())?,
+            nft_gate: self.guard(next())?,
+            nft_burn: self.guard(next())?,
+            token_burn: self.guard(next())?,
+            freeze_sol_payment: self.guard(next())?,
+            freeze_token_payment: self.guard(next())?,
+            program_gate: self.guard(next())?,
+            allocation: self.guard(next())?,
+            token2022_payment: self.guard(next())?,
+        })
+    }
+
+    fn guard<G: Guard>(&mut self, enabled: bool) -> Result<Option<G>, DecodeError> {
+        if !enabled {
+            return Ok(None);
+        }
+
+        // Variable-length guards don't always fill their slot, so only read what they use.
+        let mut slot = self.take(G::SIZE)?;
+        G::deserialize(&mut slot)
+            .map(Some)
+            .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candy_machine::tests::discriminator;
+
+    fn push_guard<G: Guard + BorshSerialize>(data: &mut Vec<u8>, guard: &G) {
+        let start = data.len();
+        guard.serialize(data).unwrap();
+        data.resize(start + G::SIZE, 0);
+    }
+
+    #[test]
+    fn test_decode_candy_guard_from_bytes() {
+        let base = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let sol_payment = SolPayment {
+            lamports: 1_000_000_000,
+            destination: Pubkey::new_unique(),
+        };
+        let start_date = StartDate {
+            date: 1_700_000_000,
+        };
+        let program_gate = ProgramGate {
+            additional: vec![Pubkey::new_unique()],
+        };
+        let mint_limit = MintLimit { id: 1, limit: 2 };
+
+        let mut data = discriminator("CandyGuard");
+        data.extend(base.to_bytes());
+        data.push(255);
+        data.extend(authority.to_bytes());
+
+        // Default guards: sol payment (bit 1), start date (bit 3) and program gate (bit 18).
+        data.extend(((1u64 << 1) | (1 << 3) | (1 << 18)).to_le_bytes());
+        push_guard(&mut data, &sol_payment);
+        push_guard(&mut data, &start_date);
+        push_guard(&mut data, &program_gate);
+
+        // One group with a mint limit (bit 9).
+        data.extend(1u32.to_le_bytes());
+        data.extend(b"OG\0\0\0\0");
+        data.extend((1u64 << 9).to_le_bytes());
+        push_guard(&mut data, &mint_limit);
+
+        let guard = decode_candy_guard_from_bytes(&CANDY_GUARD_PROGRAM_ID, &data).unwrap();
+
+        assert_eq!(guard.base, base);
+        assert_eq!(guard.bump, 255);
+        assert_eq!(guard.authority, authority);
+        assert_eq!(
+            guard.default,
+            GuardSet {
+                sol_payment: Some(sol_payment),
+                start_date: Some(start_date),
+                program_gate: Some(program_gate),
+                ..GuardSet::default()
+            }
+        );
+        assert_eq!(
+            guard.groups,
+            vec![Group {
+                label: "OG".to_string(),
+                guards: GuardSet {
+                    mint_limit: Some(mint_limit),
+                    ..GuardSet::default()
+                },
+            }]
+        );
+
+        assert!(matches!(
+            decode_candy_guard_from_bytes(&CANDY_GUARD_PROGRAM_ID, &data[..data.len() - 1]),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+
+        // An enabled guard the crate doesn't know about.
+        let header_end = 8 + HEADER_SIZE;
+        data[header_end..header_end + 8].copy_from_slice(&(1u64 << GUARD_COUNT).to_le_bytes());
+        assert!(matches!(
+            decode_candy_guard_from_bytes(&CANDY_GUARD_PROGRAM_ID, &data),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+    }
+}
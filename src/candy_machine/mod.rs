@@ -0,0 +1,425 @@
+//! Candy Machine Core (v3) and Candy Guard accounts.
+//!
+//! Both programs are Anchor programs, so accounts start with an 8-byte discriminator.
+//! Everything after the fixed Candy Machine fields (its config lines and the bitmask of
+//! loaded lines) and everything after the Candy Guard header (its guard sets) is laid out
+//! by hand by the programs and decoded the same way here.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::types::Creator;
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+
+use crate::{
+    constants::CANDY_MACHINE_V3_PROGRAM_ID,
+    decode::{decode_metadata_from_bytes, errors::DecodeError, ToPubkey},
+    derive::derive_cmv3_authority_pda,
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+    snapshot::get_metadata_accounts_by_creator,
+};
+
+mod guard;
+
+pub use guard::*;
+
+/// Length of the fixed part of a Candy Machine account, with every string and vector at
+/// its maximum size. The config line section starts here.
+const HIDDEN_SECTION: usize = 850;
+
+// Without config line settings, the program stores each name and URI as a borsh string
+// (a `u32` length and the bytes) padded to these lengths.
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_URI_LENGTH: usize = 200;
+const CONFIG_LINE_SIZE: usize = 4 + MAX_NAME_LENGTH + 4 + MAX_URI_LENGTH;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountVersion {
+    V1,
+    V2,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigLineSettings {
+    pub prefix_name: String,
+    pub name_length: u32,
+    pub prefix_uri: String,
+    pub uri_length: u32,
+    pub is_sequential: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HiddenSettings {
+    pub name: String,
+    pub uri: String,
+    pub hash: [u8; 32],
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CandyMachineData {
+    pub items_available: u64,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+    pub max_supply: u64,
+    pub is_mutable: bool,
+    pub creators: Vec<Creator>,
+    pub config_line_settings: Option<ConfigLineSettings>,
+    pub hidden_settings: Option<HiddenSettings>,
+}
+
+/// A loaded config line. `name` and `uri` are stored without the prefixes from the
+/// machine's [`ConfigLineSettings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigLine {
+    pub index: u32,
+    pub name: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandyMachine {
+    pub version: AccountVersion,
+    pub token_standard: u8,
+    pub features: [u8; 6],
+    pub authority: Pubkey,
+    pub mint_authority: Pubkey,
+    pub collection_mint: Pubkey,
+    pub items_redeemed: u64,
+    pub data: CandyMachineData,
+    /// Number of config lines loaded so far.
+    pub items_loaded: u32,
+    /// Loaded config lines in index order. Always empty for machines with hidden settings.
+    pub config_lines: Vec<ConfigLine>,
+}
+
+#[derive(BorshDeserialize)]
+struct CandyMachineHeader {
+    version: AccountVersion,
+    token_standard: u8,
+    features: [u8; 6],
+    authority: Pubkey,
+    mint_authority: Pubkey,
+    collection_mint: Pubkey,
+    items_redeemed: u64,
+    data: CandyMachineData,
+}
+
+pub fn decode_candy_machine<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    candy_machine: P,
+) -> Result<CandyMachine> {
+    let pubkey = candy_machine.to_pubkey()?;
+    let account = client.get_account(&pubkey)?;
+
+    decode_candy_machine_from_bytes(&account.owner, &account.data)
+        .map_err(|e| MetabossError::from(e).for_account(&pubkey))
+}
+
+pub fn decode_candy_machine_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<CandyMachine, DecodeError> {
+    let mut body = check_anchor_account(owner, &CANDY_MACHINE_V3_PROGRAM_ID, data, "CandyMachine")?;
+
+    let header = CandyMachineHeader::deserialize(&mut body)
+        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))?;
+
+    let (items_loaded, config_lines) = if header.data.hidden_settings.is_some() {
+        (0, Vec::new())
+    } else {
+        config_lines(data, &header.data)?
+    };
+
+    Ok(CandyMachine {
+        version: header.version,
+        token_standard: header.token_standard,
+        features: header.features,
+        authority: header.authority,
+        mint_authority: header.mint_authority,
+        collection_mint: header.collection_mint,
+        items_redeemed: header.items_redeemed,
+        data: header.data,
+        items_loaded,
+        config_lines,
+    })
+}
+
+/// Mints of every NFT a v3 candy machine has minted.
+///
+/// The machine's authority PDA is the first, verified creator of everything it mints, so
+/// its metadata accounts are fetched by that creator and kept if they belong to the
+/// machine's verified collection.
+pub fn get_cmv3_mint_list<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    candy_machine: P,
+) -> Result<Vec<Pubkey>> {
+    let candy_machine = candy_machine.to_pubkey()?;
+    let machine = decode_candy_machine(client, candy_machine)?;
+    let authority_pda = derive_cmv3_authority_pda(&candy_machine);
+
+    let accounts = get_metadata_accounts_by_creator(client, &authority_pda.to_string(), 0)?;
+
+    let mut mints = Vec::new();
+    for (_, account) in accounts {
+        let metadata = decode_metadata_from_bytes(&account.owner, &account.data)?;
+
+        let in_collection = metadata
+            .collection
+            .is_some_and(|c| c.verified && c.key == machine.collection_mint);
+        if in_collection {
+            mints.push(metadata.mint);
+        }
+    }
+    mints.sort();
+
+    Ok(mints)
+}
+
+fn config_lines(
+    data: &[u8],
+    cm_data: &CandyMachineData,
+) -> Result<(u32, Vec<ConfigLine>), DecodeError> {
+    // Config line settings store the names and URIs as bare, zero-padded bytes.
+    let (name_length, line_length, prefixed) = match &cm_data.config_line_settings {
+        Some(settings) => (
+            settings.name_length as usize,
+            settings.name_length as usize + settings.uri_length as usize,
+            false,
+        ),
+        None => (4 + MAX_NAME_LENGTH, CONFIG_LINE_SIZE, true),
+    };
+    let items_available =
+        usize::try_from(cm_data.items_available).map_err(|_| DecodeError::NumericalOverflow)?;
+
+    let lines_start = HIDDEN_SECTION + 4;
+    let mask_start = items_available
+        .checked_mul(line_length)
+        .and_then(|len| len.checked_add(lines_start))
+        .ok_or(DecodeError::NumericalOverflow)?;
+    let mask_end = mask_start + items_available / 8 + 1;
+
+    if data.len() < mask_end {
+        return Err(DecodeError::DeserializationFailed(format!(
+            "candy machine account is {} bytes, expected at least {mask_end}",
+            data.len()
+        )));
+    }
+
+    let items_loaded = u32::from_le_bytes(
+        data[HIDDEN_SECTION..lines_start]
+            .try_into()
+            .expect("slice is 4 bytes"),
+    );
+
+    let config_lines = (0..items_available)
+        .filter(|i| data[mask_start + i / 8] & (1 << (7 - i % 8)) != 0)
+        .map(|i| {
+            let start = lines_start + i * line_length;
+            ConfigLine {
+                index: i as u32,
+                name: line_field(&data[start..start + name_length], prefixed),
+                uri: line_field(&data[start + name_length..start + line_length], prefixed),
+            }
+        })
+        .collect();
+
+    Ok((items_loaded, config_lines))
+}
+
+fn line_field(bytes: &[u8], prefixed: bool) -> String {
+    let bytes = match bytes.split_at_checked(4) {
+        Some((len, rest)) if prefixed => {
+            let len = u32::from_le_bytes(len.try_into().expect("slice is 4 bytes")) as usize;
+            &rest[..len.min(rest.len())]
+        }
+        _ => bytes,
+    };
+
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Check the owner and Anchor discriminator of an account, returning the data after the
+/// discriminator.
+fn check_anchor_account<'a>(
+    owner: &Pubkey,
+    program_id: &Pubkey,
+    data: &'a [u8],
+    name: &str,
+) -> Result<&'a [u8], DecodeError> {
+    if owner != program_id {
+        return Err(DecodeError::DeserializationFailed(format!(
+            "account is owned by {owner}, expected {program_id}"
+        )));
+    }
+
+    let discriminator = &hash(format!("account:{name}").as_bytes()).to_bytes()[..8];
+    match data.split_at_checked(8) {
+        Some((found, body)) if found == discriminator => Ok(body),
+        _ => Err(DecodeError::DeserializationFailed(format!(
+            "account is not a {name}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn discriminator(name: &str) -> Vec<u8> {
+        hash(format!("account:{name}").as_bytes()).to_bytes()[..8].to_vec()
+    }
+
+    fn candy_machine_data(config_line_settings: Option<ConfigLineSettings>) -> CandyMachineData {
+        CandyMachineData {
+            items_available: 10,
+            symbol: "DROP".to_string(),
+            seller_fee_basis_points: 500,
+            max_supply: 0,
+            is_mutable: true,
+            creators: vec![Creator {
+                address: Pubkey::new_unique(),
+                verified: false,
+                share: 100,
+            }],
+            config_line_settings,
+            hidden_settings: None,
+        }
+    }
+
+    fn candy_machine_account(cm_data: &CandyMachineData, lines: &[(usize, &str, &str)]) -> Vec<u8> {
+        let mut data = discriminator("CandyMachine");
+        AccountVersion::V2.serialize(&mut data).unwrap();
+        data.push(0);
+        data.extend([0; 6]);
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend(3u64.to_le_bytes());
+        cm_data.serialize(&mut data).unwrap();
+
+        // Without settings, names and URIs carry a borsh length prefix.
+        let (name_length, line_length) = match &cm_data.config_line_settings {
+            Some(s) => (
+                s.name_length as usize,
+                (s.name_length + s.uri_length) as usize,
+            ),
+            None => (4 + MAX_NAME_LENGTH, CONFIG_LINE_SIZE),
+        };
+        let field = |value: &str| match cm_data.config_line_settings {
+            Some(_) => value.as_bytes().to_vec(),
+            None => value.to_string().try_to_vec().unwrap(),
+        };
+        let items = cm_data.items_available as usize;
+        let lines_start = HIDDEN_SECTION + 4;
+        let mask_start = lines_start + items * line_length;
+        data.resize(mask_start + items / 8 + 1 + items * 4, 0);
+
+        data[HIDDEN_SECTION..lines_start].copy_from_slice(&(lines.len() as u32).to_le_bytes());
+        for (i, name, uri) in lines {
+            let (name, uri) = (field(name), field(uri));
+            let start = lines_start + i * line_length;
+            data[start..start + name.len()].copy_from_slice(&name);
+            let start = start + name_length;
+            data[start..start + uri.len()].copy_from_slice(&uri);
+            data[mask_start + i / 8] |= 1 << (7 - i % 8);
+        }
+
+        data
+    }
+
+    #[test]
+    fn test_decode_candy_machine_from_bytes() {
+        let settings = ConfigLineSettings {
+            prefix_name: "Drop #".to_string(),
+            name_length: 4,
+            prefix_uri: "https://arweave.net/".to_string(),
+            uri_length: 43,
+            is_sequential: false,
+        };
+        let cm_data = candy_machine_data(Some(settings));
+        let data = candy_machine_account(&cm_data, &[(0, "1", "first"), (9, "10", "tenth")]);
+
+        let machine = decode_candy_machine_from_bytes(&CANDY_MACHINE_V3_PROGRAM_ID, &data).unwrap();
+
+        assert_eq!(machine.version, AccountVersion::V2);
+        assert_eq!(machine.items_redeemed, 3);
+        assert_eq!(machine.data, cm_data);
+        assert_eq!(machine.items_loaded, 2);
+        assert_eq!(
+            machine.config_lines,
+            vec![
+                ConfigLine {
+                    index: 0,
+                    name: "1".to_string(),
+                    uri: "first".to_string(),
+                },
+                ConfigLine {
+                    index: 9,
+                    name: "10".to_string(),
+                    uri: "tenth".to_string(),
+                },
+            ]
+        );
+
+        assert!(matches!(
+            decode_candy_machine_from_bytes(&Pubkey::new_unique(), &data),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+        assert!(matches!(
+            decode_candy_machine_from_bytes(&CANDY_MACHINE_V3_PROGRAM_ID, &data[8..]),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+        assert!(matches!(
+            decode_candy_machine_from_bytes(&CANDY_MACHINE_V3_PROGRAM_ID, &data[..900]),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_candy_machine_without_config_line_settings() {
+        let cm_data = candy_machine_data(None);
+        let data = candy_machine_account(&cm_data, &[(2, "Drop #3", "https://example.com/3.json")]);
+
+        let machine = decode_candy_machine_from_bytes(&CANDY_MACHINE_V3_PROGRAM_ID, &data).unwrap();
+
+        assert_eq!(machine.items_loaded, 1);
+        assert_eq!(
+            machine.config_lines,
+            vec![ConfigLine {
+                index: 2,
+                name: "Drop #3".to_string(),
+                uri: "https://example.com/3.json".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_candy_machine_config_line_layout() {
+        // Laid out by offset, as the program writes a machine with 10 items and no config
+        // line settings: 854 bytes of header and loaded count, 10 lines of 240 bytes, a
+        // 2-byte mask and 40 bytes of mint indices.
+        let cm_data = candy_machine_data(None);
+        let mut data = candy_machine_account(&cm_data, &[]);
+        assert_eq!(data.len(), 3296);
+
+        data[850..854].copy_from_slice(&1u32.to_le_bytes());
+        data[1334..1338].copy_from_slice(&7u32.to_le_bytes());
+        data[1338..1345].copy_from_slice(b"Drop #3");
+        data[1370..1374].copy_from_slice(&26u32.to_le_bytes());
+        data[1374..1400].copy_from_slice(b"https://example.com/3.json");
+        data[3254] = 0b0010_0000;
+
+        let machine = decode_candy_machine_from_bytes(&CANDY_MACHINE_V3_PROGRAM_ID, &data).unwrap();
+
+        assert_eq!(machine.items_loaded, 1);
+        assert_eq!(
+            machine.config_lines,
+            vec![ConfigLine {
+                index: 2,
+                name: "Drop #3".to_string(),
+                uri: "https://example.com/3.json".to_string(),
+            }]
+        );
+    }
+}
@@ -1,12 +1,12 @@
 use std::{fmt::Display, str::FromStr};
 
-use anyhow::Result;
 use mpl_token_metadata::{
     accounts::Metadata,
     types::{Creator, ProgrammableConfig, TokenStandard},
 };
 
 use crate::decode::ToPubkey;
+use crate::errors::{MetabossError, Result};
 
 #[derive(Debug, Clone)]
 pub enum MetadataValue {
@@ -25,7 +25,7 @@ pub enum MetadataValue {
 }
 
 impl FromStr for MetadataValue {
-    type Err = anyhow::Error;
+    type Err = MetabossError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut split = s.split('=');
@@ -65,7 +65,7 @@ impl FromStr for MetadataValue {
             "collection_parent" => Ok(MetadataValue::CollectionParent(value.to_string())),
             "collection_verified" => Ok(MetadataValue::CollectionVerified(value.parse::<bool>()?)),
             "rule_set" => Ok(MetadataValue::RuleSet(value.to_string())),
-            _ => Err(anyhow::anyhow!("Invalid metadata key")),
+            _ => Err(MetabossError::validation("Invalid metadata key")),
         }
     }
 }
@@ -167,7 +167,7 @@ pub fn check_metadata_value(metadata: &Metadata, value: &MetadataValue) -> bool
     }
 }
 
-fn token_standard_to_string(token_standard: &TokenStandard) -> String {
+pub(crate) fn token_standard_to_string(token_standard: &TokenStandard) -> String {
     match token_standard {
         TokenStandard::Fungible => "fungible".to_string(),
         TokenStandard::FungibleAsset => "fungible_asset".to_string(),
@@ -22,9 +22,21 @@ pub const PUBKEY_LENGTH: usize = 32;
 pub const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 pub const AUTH_RULES_PROGRAM_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
 pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+pub const CANDY_MACHINE_V3_PROGRAM_ID: Pubkey =
+    pubkey!("CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR");
+pub const CANDY_GUARD_PROGRAM_ID: Pubkey = pubkey!("Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g");
 pub const MINT_LAYOUT_SIZE: u64 = 82;
 pub const EDITION_V1_BS58: &str = "2";
+/// Editions tracked by each V1 edition marker account.
+pub const EDITION_MARKER_BIT_SIZE: u64 = 248;
 
 pub const METADATA_PREFIX: &str = "metadata";
 pub const EDITION_PREFIX: &str = "edition";
+pub const EDITION_MARKER_V2_SEED: &str = "marker";
 pub const TOKEN_RECORD_SEED: &str = "token_record";
+pub const CANDY_MACHINE_SEED: &str = "candy_machine";
+pub const CANDY_GUARD_SEED: &str = "candy_guard";
+pub const RULE_SET_SEED: &str = "rule_set";
+
+/// Most accounts a single `getMultipleAccounts` request may ask for.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
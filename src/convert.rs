@@ -1,14 +1,14 @@
-use anyhow::{anyhow, Result};
 use mpl_token_metadata::types::{Creator, DataV2};
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
 
 use crate::data::{NftCreator, NftData};
+use crate::errors::{MetabossError, Result};
 
 pub fn convert_local_to_remote_data(local: NftData) -> Result<DataV2> {
     let creators = local
         .creators
-        .ok_or_else(|| anyhow!("No creators specified in json file!"))?
+        .ok_or_else(|| MetabossError::validation("No creators specified in json file!"))?
         .iter()
         .map(convert_creator)
         .collect::<Result<Vec<Creator>>>()?;
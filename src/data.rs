@@ -3,19 +3,19 @@ use std::{
     str::FromStr,
 };
 
-use anyhow::{anyhow, Result};
 use mpl_token_metadata::{
     accounts::Metadata,
     types::{Data, DataV2},
 };
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
 use solana_program::{program_pack::Pack, pubkey::Pubkey};
 use spl_token::state::Account as TokenAccount;
 
 use crate::{
-    decode::{decode_metadata, errors::DecodeError},
+    decode::decode_metadata,
     derive::{derive_edition_pda, derive_metadata_pda, derive_token_record_pda},
+    errors::{MetabossError, Result},
+    rpc::Rpc,
 };
 
 pub struct Asset {
@@ -43,11 +43,11 @@ impl Asset {
         derive_token_record_pda(&self.mint, token)
     }
 
-    pub fn get_metadata(&self, client: &RpcClient) -> Result<Metadata, DecodeError> {
+    pub fn get_metadata(&self, client: &(impl Rpc + ?Sized)) -> Result<Metadata> {
         decode_metadata(client, &self.metadata)
     }
 
-    pub(crate) fn _get_token_owner(client: &RpcClient, token: &Pubkey) -> Result<Pubkey> {
+    pub(crate) fn _get_token_owner(client: &(impl Rpc + ?Sized), token: &Pubkey) -> Result<Pubkey> {
         let data = client.get_account_data(token)?;
         let owner = TokenAccount::unpack(&data)?.owner;
         Ok(owner)
@@ -146,7 +146,6 @@ impl Display for PriorityFee {
     }
 }
 
-// Temporary simple priority fees
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub enum Priority {
     #[default]
@@ -155,19 +154,41 @@ pub enum Priority {
     Medium,
     High,
     Max,
+    /// A fixed compute unit price in micro-lamports.
+    Custom(FeeMicroLamports),
+    /// A percentile (0-100) of the fees recently paid to write the transaction's accounts.
+    Percentile(u8),
 }
 
 impl FromStr for Priority {
-    type Err = anyhow::Error;
+    type Err = MetabossError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
+        let s = s.to_lowercase();
+
+        if let Some((kind, value)) = s.split_once(':') {
+            return match kind {
+                "custom" => Ok(Self::Custom(value.parse()?)),
+                "percentile" => {
+                    let percentile: u8 = value.parse()?;
+                    if percentile > 100 {
+                        return Err(MetabossError::validation(
+                            "Percentile must be between 0 and 100",
+                        ));
+                    }
+                    Ok(Self::Percentile(percentile))
+                }
+                _ => Err(MetabossError::validation("Invalid priority")),
+            };
+        }
+
+        match s.as_str() {
             "none" => Ok(Self::None),
             "low" => Ok(Self::Low),
             "medium" => Ok(Self::Medium),
             "high" => Ok(Self::High),
             "max" => Ok(Self::Max),
-            _ => Err(anyhow!("Invalid priority".to_string())),
+            _ => Err(MetabossError::validation("Invalid priority")),
         }
     }
 }
@@ -180,6 +201,8 @@ impl Display for Priority {
             Self::Medium => write!(f, "Medium"),
             Self::High => write!(f, "High"),
             Self::Max => write!(f, "Max"),
+            Self::Custom(micro_lamports) => write!(f, "Custom:{}", micro_lamports),
+            Self::Percentile(percentile) => write!(f, "Percentile:{}", percentile),
         }
     }
 }
@@ -222,6 +245,21 @@ mod tests {
         assert!(Priority::from_str("invalid").is_err());
         assert!(Priority::from_str("").is_err());
         assert!(Priority::from_str("ultra").is_err());
+        assert!(Priority::from_str("custom:").is_err());
+        assert!(Priority::from_str("percentile:101").is_err());
+        assert!(Priority::from_str("fast:10").is_err());
+    }
+
+    #[test]
+    fn test_priority_from_str_custom_and_percentile() {
+        assert_eq!(
+            Priority::from_str("custom:5000").unwrap(),
+            Priority::Custom(5000)
+        );
+        assert_eq!(
+            Priority::from_str("Percentile:75").unwrap(),
+            Priority::Percentile(75)
+        );
     }
 
     // --- Priority::Display ---
@@ -243,6 +281,8 @@ mod tests {
             Priority::Medium,
             Priority::High,
             Priority::Max,
+            Priority::Custom(42),
+            Priority::Percentile(90),
         ];
         for variant in &variants {
             let displayed = format!("{}", variant);
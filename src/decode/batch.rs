@@ -0,0 +1,317 @@
+//! Decode many accounts with `getMultipleAccounts` instead of one request per account.
+//!
+//! Lookups are split into requests of [`MAX_MULTIPLE_ACCOUNTS`] which run on a few
+//! threads at once. Every function returns one result per input, in input order; a
+//! missing account, an account that fails to decode or a failed request only affects
+//! the items involved. The items of a failed request share its `MetabossError::Rpc`.
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use mpl_token_metadata::accounts::{Edition, MasterEdition, Metadata, TokenRecord};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::pubkey::Pubkey;
+use spl_token::state::{Account as Token, Mint};
+
+use super::{
+    decode_edition_from_bytes, decode_master_edition_from_bytes, decode_metadata_from_bytes,
+    decode_mint_from_bytes, decode_token_from_bytes, decode_token_record_from_bytes,
+    errors::DecodeError, ToPubkey,
+};
+use crate::{
+    constants::MAX_MULTIPLE_ACCOUNTS,
+    derive::*,
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+};
+
+/// Number of `getMultipleAccounts` requests in flight at the same time.
+const CONCURRENT_REQUESTS: usize = 8;
+
+/// Decode the metadata of every mint in `mint_addresses`.
+pub fn decode_metadata_batch<P: ToPubkey + Clone>(
+    client: &(impl Rpc + Sync + ?Sized),
+    mint_addresses: &[P],
+) -> Vec<Result<Metadata>> {
+    let addresses = derive_addresses(mint_addresses, derive_metadata_pda);
+
+    decode_batch(client, addresses, decode_metadata_from_bytes)
+}
+
+/// Decode the master edition of every mint in `mint_addresses`.
+pub fn decode_master_edition_batch<P: ToPubkey + Clone>(
+    client: &(impl Rpc + Sync + ?Sized),
+    mint_addresses: &[P],
+) -> Vec<Result<MasterEdition>> {
+    let addresses = derive_addresses(mint_addresses, derive_edition_pda);
+
+    decode_batch(client, addresses, decode_master_edition_from_bytes)
+}
+
+/// Decode the print edition of every mint in `mint_addresses`.
+pub fn decode_edition_batch<P: ToPubkey + Clone>(
+    client: &(impl Rpc + Sync + ?Sized),
+    mint_addresses: &[P],
+) -> Vec<Result<Edition>> {
+    let addresses = derive_addresses(mint_addresses, derive_edition_pda);
+
+    decode_batch(client, addresses, decode_edition_from_bytes)
+}
+
+pub fn decode_mint_batch<P: ToPubkey + Clone>(
+    client: &(impl Rpc + Sync + ?Sized),
+    mint_addresses: &[P],
+) -> Vec<Result<Mint>> {
+    let addresses = derive_addresses(mint_addresses, |pubkey| *pubkey);
+
+    decode_batch(client, addresses, decode_mint_from_bytes)
+}
+
+pub fn decode_token_batch<P: ToPubkey + Clone>(
+    client: &(impl Rpc + Sync + ?Sized),
+    token_addresses: &[P],
+) -> Vec<Result<Token>> {
+    let addresses = derive_addresses(token_addresses, |pubkey| *pubkey);
+
+    decode_batch(client, addresses, decode_token_from_bytes)
+}
+
+/// Decode the token record of every `(mint, token account)` pair.
+pub fn decode_token_record_batch<P1: ToPubkey + Clone, P2: ToPubkey + Clone>(
+    client: &(impl Rpc + Sync + ?Sized),
+    mints_and_tokens: &[(P1, P2)],
+) -> Vec<Result<TokenRecord>> {
+    let addresses = mints_and_tokens
+        .iter()
+        .map(|(mint, token)| {
+            Ok(derive_token_record_pda(
+                &mint.clone().to_pubkey()?,
+                &token.clone().to_pubkey()?,
+            ))
+        })
+        .collect();
+
+    decode_batch(client, addresses, decode_token_record_from_bytes)
+}
+
+fn derive_addresses<P: ToPubkey + Clone>(
+    addresses: &[P],
+    derive: impl Fn(&Pubkey) -> Pubkey,
+) -> Vec<Result<Pubkey>> {
+    addresses
+        .iter()
+        .map(|address| Ok(derive(&address.clone().to_pubkey()?)))
+        .collect()
+}
+
+pub(super) fn decode_batch<T, R>(
+    client: &R,
+    addresses: Vec<Result<Pubkey>>,
+    decode: fn(&Pubkey, &[u8]) -> Result<T, DecodeError>,
+) -> Vec<Result<T>>
+where
+    T: Send,
+    R: Rpc + Sync + ?Sized,
+{
+    // Addresses that failed to parse keep their error; the rest are looked up.
+    let mut lookups = Vec::new();
+    let results: Vec<Option<Result<T>>> = addresses
+        .into_iter()
+        .enumerate()
+        .map(|(index, address)| match address {
+            Ok(pubkey) => {
+                lookups.push((index, pubkey));
+                None
+            }
+            Err(err) => Some(Err(err)),
+        })
+        .collect();
+
+    let results = Mutex::new(results);
+    let queue = Mutex::new(lookups.chunks(MAX_MULTIPLE_ACCOUNTS));
+    let workers = CONCURRENT_REQUESTS.min(lookups.len().div_ceil(MAX_MULTIPLE_ACCOUNTS));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let next = queue.lock().unwrap().next();
+                let Some(chunk) = next else {
+                    break;
+                };
+
+                let decoded = decode_chunk(client, chunk, decode);
+
+                let mut results = results.lock().unwrap();
+                for ((index, _), result) in chunk.iter().zip(decoded) {
+                    results[*index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item is decoded or failed"))
+        .collect()
+}
+
+fn decode_chunk<T, R: Rpc + ?Sized>(
+    client: &R,
+    chunk: &[(usize, Pubkey)],
+    decode: fn(&Pubkey, &[u8]) -> Result<T, DecodeError>,
+) -> Vec<Result<T>> {
+    let pubkeys: Vec<Pubkey> = chunk.iter().map(|(_, pubkey)| *pubkey).collect();
+
+    let failed = |err: ClientError| {
+        let err = Arc::new(err);
+        pubkeys
+            .iter()
+            .map(|_| Err(MetabossError::Rpc(Arc::clone(&err))))
+            .collect()
+    };
+
+    match client.get_multiple_accounts(&pubkeys) {
+        // Accounts are matched to addresses by position, which a short or long response
+        // makes meaningless.
+        Ok(accounts) if accounts.len() != pubkeys.len() => failed(
+            ClientErrorKind::Custom(format!(
+                "requested {} accounts, but the node returned {}",
+                pubkeys.len(),
+                accounts.len()
+            ))
+            .into(),
+        ),
+        Ok(accounts) => pubkeys
+            .iter()
+            .zip(accounts)
+            .map(|(pubkey, account)| match account {
+                Some(account) => decode(&account.owner, &account.data)
+                    .map_err(|e| MetabossError::from(e).for_account(pubkey)),
+                None => Err(MetabossError::MissingAccount(*pubkey)),
+            })
+            .collect(),
+        Err(err) => failed(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::types::Key;
+    use solana_account_decoder::{encode_ui_account, UiAccountEncoding};
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+        rpc_response::{Response, RpcResponseContext},
+    };
+    use solana_sdk::account::Account;
+
+    #[test]
+    fn test_decode_metadata_batch() {
+        let mint = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint,
+            name: "Batch".to_string(),
+            symbol: "BAT".to_string(),
+            uri: "https://example.com/batch.json".to_string(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let account = Account {
+            lamports: 1,
+            data: metadata.try_to_vec().unwrap(),
+            owner: mpl_token_metadata::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        let metadata_pda = derive_metadata_pda(&mint);
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: vec![
+                    Some(encode_ui_account(
+                        &metadata_pda,
+                        &account,
+                        UiAccountEncoding::Base64,
+                        None,
+                        None,
+                    )),
+                    None,
+                ],
+            })
+            .unwrap(),
+        );
+        let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let mints = [
+            mint.to_string(),
+            "not a pubkey".to_string(),
+            missing.to_string(),
+        ];
+        let results = decode_metadata_batch(&client, &mints);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &metadata);
+        assert!(matches!(results[1], Err(MetabossError::Validation(_))));
+        assert!(matches!(
+            &results[2],
+            Err(MetabossError::MissingAccount(address)) if *address == derive_metadata_pda(&missing)
+        ));
+    }
+
+    #[test]
+    fn test_decode_batch_short_response_fails_every_item() {
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: vec![None::<solana_account_decoder::UiAccount>],
+            })
+            .unwrap(),
+        );
+        let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let results = decode_mint_batch(&client, &mints);
+
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Err(MetabossError::Rpc(_)))));
+    }
+
+    #[test]
+    fn test_decode_batch_failed_request_shares_error() {
+        let client = RpcClient::new_mock("fails".to_string());
+
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let results = decode_mint_batch(&client, &mints);
+
+        match (&results[0], &results[1]) {
+            (Err(MetabossError::Rpc(first)), Err(MetabossError::Rpc(second))) => {
+                assert!(Arc::ptr_eq(first, second))
+            }
+            other => panic!("expected RPC errors, got {other:?}"),
+        }
+    }
+}
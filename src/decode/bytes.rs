@@ -0,0 +1,303 @@
+//! Decoders for account data that has already been fetched, e.g. from Geyser, a
+//! `getProgramAccounts` snapshot or a file.
+//!
+//! Each function checks the account owner and, for Token Metadata accounts, the `Key`
+//! discriminator before deserializing. The client-fetching decoders convert their
+//! `DecodeError` into `MetabossError::Deserialize` tagged with the fetched account.
+
+use borsh::de::BorshDeserialize;
+use mpl_token_metadata::{
+    accounts::{
+        CollectionAuthorityRecord, Edition, EditionMarker, EditionMarkerV2, MasterEdition,
+        Metadata, MetadataDelegateRecord, TokenRecord, UseAuthorityRecord,
+    },
+    types::Key,
+};
+use solana_program::bpf_loader_upgradeable::{
+    UpgradeableLoaderState, ID as BPF_LOADER_UPGRADEABLE_ID,
+};
+use solana_sdk::pubkey::Pubkey;
+use spl_token::state::{Account as Token, Mint};
+
+use super::{
+    edition_from_data, edition_marker_from_data, edition_marker_v2_from_data, errors::DecodeError,
+    master_edition_from_data, metadata_from_data, mint_from_data, record_from_data,
+    token_from_data, upgradeable_loader_state_from_data,
+};
+
+pub fn decode_metadata_from_bytes(owner: &Pubkey, data: &[u8]) -> Result<Metadata, DecodeError> {
+    check_token_metadata_account(
+        owner,
+        data,
+        Key::MetadataV1,
+        DecodeError::DecodeMetadataFailed,
+    )?;
+
+    metadata_from_data(data)
+}
+
+/// Decode a V1 or V2 master edition. V1 accounts carry two more printing mints after the
+/// fields they share with V2, which are skipped.
+pub fn decode_master_edition_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<MasterEdition, DecodeError> {
+    check_token_metadata_keys(
+        owner,
+        data,
+        &[Key::MasterEditionV1, Key::MasterEditionV2],
+        DecodeError::DecodeMetadataFailed,
+    )?;
+
+    master_edition_from_data(data)
+}
+
+pub fn decode_edition_from_bytes(owner: &Pubkey, data: &[u8]) -> Result<Edition, DecodeError> {
+    check_token_metadata_account(
+        owner,
+        data,
+        Key::EditionV1,
+        DecodeError::DecodeMetadataFailed,
+    )?;
+
+    edition_from_data(data)
+}
+
+pub fn decode_edition_marker_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<EditionMarker, DecodeError> {
+    check_token_metadata_account(
+        owner,
+        data,
+        Key::EditionMarker,
+        DecodeError::DecodeMetadataFailed,
+    )?;
+
+    edition_marker_from_data(data)
+}
+
+pub fn decode_edition_marker_v2_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<EditionMarkerV2, DecodeError> {
+    check_token_metadata_account(
+        owner,
+        data,
+        Key::EditionMarkerV2,
+        DecodeError::DecodeMetadataFailed,
+    )?;
+
+    edition_marker_v2_from_data(data)
+}
+
+pub fn decode_mint_from_bytes(owner: &Pubkey, data: &[u8]) -> Result<Mint, DecodeError> {
+    check_owner(owner, &spl_token::ID, DecodeError::DecodeDataFailed)?;
+
+    mint_from_data(data)
+}
+
+pub fn decode_token_from_bytes(owner: &Pubkey, data: &[u8]) -> Result<Token, DecodeError> {
+    check_owner(owner, &spl_token::ID, DecodeError::DecodeMetadataFailed)?;
+
+    token_from_data(data)
+}
+
+pub fn decode_bpf_loader_upgradeable_state_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<UpgradeableLoaderState, DecodeError> {
+    check_owner(
+        owner,
+        &BPF_LOADER_UPGRADEABLE_ID,
+        DecodeError::DeserializationFailed,
+    )?;
+
+    upgradeable_loader_state_from_data(data)
+}
+
+pub fn decode_collection_authority_record_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<CollectionAuthorityRecord, DecodeError> {
+    decode_record_from_bytes(owner, data, Key::CollectionAuthorityRecord)
+}
+
+pub fn decode_use_authority_record_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<UseAuthorityRecord, DecodeError> {
+    decode_record_from_bytes(owner, data, Key::UseAuthorityRecord)
+}
+
+pub fn decode_metadata_delegate_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<MetadataDelegateRecord, DecodeError> {
+    decode_record_from_bytes(owner, data, Key::MetadataDelegate)
+}
+
+pub fn decode_token_record_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<TokenRecord, DecodeError> {
+    decode_record_from_bytes(owner, data, Key::TokenRecord)
+}
+
+pub(super) fn decode_record_from_bytes<T: BorshDeserialize>(
+    owner: &Pubkey,
+    data: &[u8],
+    key: Key,
+) -> Result<T, DecodeError> {
+    check_token_metadata_account(owner, data, key, DecodeError::DeserializationFailed)?;
+
+    record_from_data(data)
+}
+
+fn check_token_metadata_account(
+    owner: &Pubkey,
+    data: &[u8],
+    key: Key,
+    error: fn(String) -> DecodeError,
+) -> Result<(), DecodeError> {
+    check_token_metadata_keys(owner, data, &[key], error)
+}
+
+fn check_token_metadata_keys(
+    owner: &Pubkey,
+    data: &[u8],
+    keys: &[Key],
+    error: fn(String) -> DecodeError,
+) -> Result<(), DecodeError> {
+    check_owner(owner, &mpl_token_metadata::ID, error)?;
+
+    match data.first() {
+        Some(found) if keys.iter().any(|key| *found == *key as u8) => Ok(()),
+        Some(found) => {
+            let expected: Vec<String> = keys.iter().map(|key| format!("{key:?}")).collect();
+            Err(error(format!(
+                "expected {} account, found key {found}",
+                expected.join(" or ")
+            )))
+        }
+        None => Err(error("account data is empty".to_string())),
+    }
+}
+
+fn check_owner(
+    owner: &Pubkey,
+    expected: &Pubkey,
+    error: fn(String) -> DecodeError,
+) -> Result<(), DecodeError> {
+    if owner != expected {
+        return Err(error(format!(
+            "account is owned by {owner}, expected {expected}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::types::TokenState;
+    use solana_program::program_pack::Pack;
+
+    fn token_record() -> TokenRecord {
+        TokenRecord {
+            key: Key::TokenRecord,
+            bump: 254,
+            state: TokenState::Unlocked,
+            rule_set_revision: None,
+            delegate: None,
+            delegate_role: None,
+            locked_transfer: None,
+        }
+    }
+
+    #[test]
+    fn test_decode_token_record_from_bytes() {
+        let mut data = token_record().try_to_vec().unwrap();
+        data.resize(TokenRecord::LEN, 0);
+
+        assert_eq!(
+            decode_token_record_from_bytes(&mpl_token_metadata::ID, &data).unwrap(),
+            token_record()
+        );
+
+        assert!(matches!(
+            decode_token_record_from_bytes(&spl_token::ID, &data),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+
+        // Same owner, wrong account type.
+        assert!(matches!(
+            decode_metadata_delegate_from_bytes(&mpl_token_metadata::ID, &data),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+        assert!(matches!(
+            decode_metadata_from_bytes(&mpl_token_metadata::ID, &data),
+            Err(DecodeError::DecodeMetadataFailed(_))
+        ));
+        assert!(matches!(
+            decode_token_record_from_bytes(&mpl_token_metadata::ID, &[]),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_master_edition_from_bytes() {
+        let master = MasterEdition {
+            key: Key::MasterEditionV2,
+            supply: 1,
+            max_supply: Some(10),
+        };
+        let data = master.try_to_vec().unwrap();
+        assert_eq!(
+            decode_master_edition_from_bytes(&mpl_token_metadata::ID, &data).unwrap(),
+            master
+        );
+
+        // V1 appends its printing mint and one-time printing authorization mint.
+        let v1 = MasterEdition {
+            key: Key::MasterEditionV1,
+            ..master
+        };
+        let mut data = v1.try_to_vec().unwrap();
+        data.extend([1; 64]);
+        assert_eq!(
+            decode_master_edition_from_bytes(&mpl_token_metadata::ID, &data).unwrap(),
+            v1
+        );
+
+        data[0] = Key::EditionV1 as u8;
+        assert!(matches!(
+            decode_master_edition_from_bytes(&mpl_token_metadata::ID, &data),
+            Err(DecodeError::DecodeMetadataFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_mint_from_bytes() {
+        let mint = Mint {
+            supply: 1,
+            decimals: 0,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+
+        assert_eq!(decode_mint_from_bytes(&spl_token::ID, &data).unwrap(), mint);
+        assert!(matches!(
+            decode_mint_from_bytes(&mpl_token_metadata::ID, &data),
+            Err(DecodeError::DecodeDataFailed(_))
+        ));
+        assert!(matches!(
+            decode_token_from_bytes(&spl_token::ID, &data),
+            Err(DecodeError::DecodeMetadataFailed(_))
+        ));
+    }
+}
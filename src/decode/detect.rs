@@ -0,0 +1,276 @@
+use mpl_token_metadata::{
+    accounts::{
+        CollectionAuthorityRecord, DeprecatedMasterEditionV1, Edition, EditionMarker,
+        EditionMarkerV2, MasterEdition, Metadata, MetadataDelegateRecord, TokenRecord,
+        UseAuthorityRecord,
+    },
+    types::Key,
+};
+use solana_program::{
+    bpf_loader_upgradeable::{UpgradeableLoaderState, ID as BPF_LOADER_UPGRADEABLE_ID},
+    program_pack::Pack,
+};
+use solana_sdk::pubkey::Pubkey;
+use spl_token::state::{Account as Token, Mint};
+use spl_token_2022::extension::AccountType;
+
+use super::{
+    decode_bpf_loader_upgradeable_state_from_bytes, decode_collection_authority_record_from_bytes,
+    decode_edition_from_bytes, decode_edition_marker_from_bytes,
+    decode_edition_marker_v2_from_bytes, decode_master_edition_from_bytes,
+    decode_metadata_delegate_from_bytes, decode_metadata_from_bytes, decode_mint_from_bytes,
+    decode_mint_with_extensions_from_bytes, decode_record_from_bytes, decode_token_from_bytes,
+    decode_token_record_from_bytes, decode_token_with_extensions_from_bytes,
+    decode_use_authority_record_from_bytes, errors::DecodeError,
+    token_2022::token_2022_account_type, MintWithExtensions, ToPubkey, TokenWithExtensions,
+};
+use crate::{
+    candy_machine::{
+        decode_candy_guard_from_bytes, decode_candy_machine_from_bytes, CandyGuard, CandyMachine,
+    },
+    constants::{CANDY_GUARD_PROGRAM_ID, CANDY_MACHINE_V3_PROGRAM_ID},
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+};
+
+/// Any account type the crate can decode, as detected by [`decode_any`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedAccount {
+    Metadata(Box<Metadata>),
+    MasterEditionV1(DeprecatedMasterEditionV1),
+    MasterEditionV2(MasterEdition),
+    Edition(Edition),
+    EditionMarker(EditionMarker),
+    EditionMarkerV2(EditionMarkerV2),
+    TokenRecord(TokenRecord),
+    MetadataDelegateRecord(MetadataDelegateRecord),
+    CollectionAuthorityRecord(CollectionAuthorityRecord),
+    UseAuthorityRecord(UseAuthorityRecord),
+    Mint(Mint),
+    Token(Token),
+    UpgradeableLoaderState(UpgradeableLoaderState),
+    Token2022Mint(Box<MintWithExtensions>),
+    Token2022Account(Box<TokenWithExtensions>),
+    CandyMachine(Box<CandyMachine>),
+    CandyGuard(Box<CandyGuard>),
+}
+
+/// Fetch an account and decode it as whichever supported type it is.
+///
+/// Token Metadata accounts are told apart by their `Key` byte, SPL Token accounts by
+/// their data length, Token-2022 accounts by their account type byte and Candy Machine v3
+/// and Candy Guard accounts by their owner. Anything else fails with
+/// `MetabossError::Deserialize` naming the account.
+pub fn decode_any<P: ToPubkey>(client: &(impl Rpc + ?Sized), address: P) -> Result<DecodedAccount> {
+    let pubkey = address.to_pubkey()?;
+    let account = client.get_account(&pubkey)?;
+
+    decode_any_from_bytes(&account.owner, &account.data)
+        .map_err(|e| MetabossError::from(e).for_account(&pubkey))
+}
+
+/// Like [`decode_any`], for account data that has already been fetched.
+pub fn decode_any_from_bytes(owner: &Pubkey, data: &[u8]) -> Result<DecodedAccount, DecodeError> {
+    if *owner == mpl_token_metadata::ID {
+        decode_token_metadata_account(owner, data)
+    } else if *owner == spl_token::ID {
+        match data.len() {
+            Mint::LEN => decode_mint_from_bytes(owner, data).map(DecodedAccount::Mint),
+            Token::LEN => decode_token_from_bytes(owner, data).map(DecodedAccount::Token),
+            len => Err(DecodeError::DecodeDataFailed(format!(
+                "unrecognized SPL Token account of {len} bytes"
+            ))),
+        }
+    } else if *owner == spl_token_2022::ID {
+        match token_2022_account_type(data) {
+            Some(AccountType::Mint) => decode_mint_with_extensions_from_bytes(owner, data)
+                .map(|mint| DecodedAccount::Token2022Mint(Box::new(mint))),
+            Some(AccountType::Account) => decode_token_with_extensions_from_bytes(owner, data)
+                .map(|token| DecodedAccount::Token2022Account(Box::new(token))),
+            _ => Err(DecodeError::DecodeDataFailed(format!(
+                "unrecognized Token-2022 account of {} bytes",
+                data.len()
+            ))),
+        }
+    } else if *owner == BPF_LOADER_UPGRADEABLE_ID {
+        decode_bpf_loader_upgradeable_state_from_bytes(owner, data)
+            .map(DecodedAccount::UpgradeableLoaderState)
+    } else if *owner == CANDY_MACHINE_V3_PROGRAM_ID {
+        decode_candy_machine_from_bytes(owner, data)
+            .map(|machine| DecodedAccount::CandyMachine(Box::new(machine)))
+    } else if *owner == CANDY_GUARD_PROGRAM_ID {
+        decode_candy_guard_from_bytes(owner, data)
+            .map(|guard| DecodedAccount::CandyGuard(Box::new(guard)))
+    } else {
+        Err(DecodeError::DecodeDataFailed(format!(
+            "unrecognized account owner {owner}"
+        )))
+    }
+}
+
+fn decode_token_metadata_account(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<DecodedAccount, DecodeError> {
+    let key = *data
+        .first()
+        .ok_or_else(|| DecodeError::DecodeDataFailed("account data is empty".to_string()))?;
+
+    match key {
+        k if k == Key::MetadataV1 as u8 => {
+            decode_metadata_from_bytes(owner, data).map(|md| DecodedAccount::Metadata(Box::new(md)))
+        }
+        k if k == Key::MasterEditionV1 as u8 => {
+            decode_record_from_bytes(owner, data, Key::MasterEditionV1)
+                .map(DecodedAccount::MasterEditionV1)
+        }
+        k if k == Key::MasterEditionV2 as u8 => {
+            decode_master_edition_from_bytes(owner, data).map(DecodedAccount::MasterEditionV2)
+        }
+        k if k == Key::EditionV1 as u8 => {
+            decode_edition_from_bytes(owner, data).map(DecodedAccount::Edition)
+        }
+        k if k == Key::EditionMarker as u8 => {
+            decode_edition_marker_from_bytes(owner, data).map(DecodedAccount::EditionMarker)
+        }
+        k if k == Key::EditionMarkerV2 as u8 => {
+            decode_edition_marker_v2_from_bytes(owner, data).map(DecodedAccount::EditionMarkerV2)
+        }
+        k if k == Key::TokenRecord as u8 => {
+            decode_token_record_from_bytes(owner, data).map(DecodedAccount::TokenRecord)
+        }
+        k if k == Key::MetadataDelegate as u8 => decode_metadata_delegate_from_bytes(owner, data)
+            .map(DecodedAccount::MetadataDelegateRecord),
+        k if k == Key::CollectionAuthorityRecord as u8 => {
+            decode_collection_authority_record_from_bytes(owner, data)
+                .map(DecodedAccount::CollectionAuthorityRecord)
+        }
+        k if k == Key::UseAuthorityRecord as u8 => {
+            decode_use_authority_record_from_bytes(owner, data)
+                .map(DecodedAccount::UseAuthorityRecord)
+        }
+        k => Err(DecodeError::DecodeDataFailed(format!(
+            "unsupported Token Metadata account key {k}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_decode_any_from_bytes() {
+        let edition = Edition {
+            key: Key::EditionV1,
+            parent: Pubkey::new_unique(),
+            edition: 7,
+        };
+        let data = edition.try_to_vec().unwrap();
+        assert_eq!(
+            decode_any_from_bytes(&mpl_token_metadata::ID, &data).unwrap(),
+            DecodedAccount::Edition(edition)
+        );
+
+        let master = MasterEdition {
+            key: Key::MasterEditionV2,
+            supply: 3,
+            max_supply: Some(10),
+        };
+        let data = master.try_to_vec().unwrap();
+        assert_eq!(
+            decode_any_from_bytes(&mpl_token_metadata::ID, &data).unwrap(),
+            DecodedAccount::MasterEditionV2(master)
+        );
+
+        let mint = Mint {
+            decimals: 0,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        assert_eq!(
+            decode_any_from_bytes(&spl_token::ID, &data).unwrap(),
+            DecodedAccount::Mint(mint)
+        );
+
+        let state = UpgradeableLoaderState::Program {
+            programdata_address: Pubkey::new_unique(),
+        };
+        let data = bincode::serialize(&state).unwrap();
+        assert_eq!(
+            decode_any_from_bytes(&BPF_LOADER_UPGRADEABLE_ID, &data).unwrap(),
+            DecodedAccount::UpgradeableLoaderState(state)
+        );
+
+        let mut token_data = vec![0; Token::LEN];
+        Token::pack(
+            Token {
+                state: spl_token::state::AccountState::Initialized,
+                ..Token::default()
+            },
+            &mut token_data,
+        )
+        .unwrap();
+        assert!(matches!(
+            decode_any_from_bytes(&spl_token_2022::ID, &token_data),
+            Ok(DecodedAccount::Token2022Account(_))
+        ));
+
+        assert!(matches!(
+            decode_any_from_bytes(&Pubkey::new_unique(), &data),
+            Err(DecodeError::DecodeDataFailed(_))
+        ));
+        assert!(matches!(
+            decode_any_from_bytes(&spl_token::ID, &[0; 10]),
+            Err(DecodeError::DecodeDataFailed(_))
+        ));
+        assert!(matches!(
+            decode_any_from_bytes(&mpl_token_metadata::ID, &[Key::HolderDelegate as u8]),
+            Err(DecodeError::DecodeDataFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_any_names_the_account() {
+        use solana_account_decoder::{encode_ui_account, UiAccountEncoding};
+        use solana_client::{
+            rpc_client::{Mocks, RpcClient},
+            rpc_request::RpcRequest,
+            rpc_response::{Response, RpcResponseContext},
+        };
+        use solana_sdk::account::Account;
+
+        let pubkey = Pubkey::new_unique();
+        let account = Account {
+            lamports: 1,
+            data: vec![1, 2, 3],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: Some(encode_ui_account(
+                    &pubkey,
+                    &account,
+                    UiAccountEncoding::Base64,
+                    None,
+                    None,
+                )),
+            })
+            .unwrap(),
+        );
+        let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        assert!(matches!(
+            decode_any(&client, pubkey),
+            Err(MetabossError::Deserialize { account: Some(account), .. }) if account == pubkey
+        ));
+    }
+}
@@ -0,0 +1,340 @@
+//! Which edition numbers of a master edition have been printed, read from its edition
+//! marker ledgers.
+//!
+//! Regular master editions spread the ledger over V1 markers that track
+//! [`EDITION_MARKER_BIT_SIZE`] editions each. Programmable master editions use a single
+//! `EditionMarkerV2` whose ledger grows as editions are printed. In both, edition `n` is
+//! bit `7 - n % 8` of byte `n / 8`, counted from the start of the marker's range.
+
+use std::collections::BTreeSet;
+
+use mpl_token_metadata::{
+    accounts::{EditionMarker, EditionMarkerV2},
+    types::Key,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use super::{
+    batch::decode_batch, decode_edition_marker_from_bytes, decode_edition_marker_v2_from_bytes,
+    decode_master_edition_from_mint, errors::DecodeError, ToPubkey,
+};
+use crate::{
+    constants::EDITION_MARKER_BIT_SIZE,
+    derive::*,
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+};
+
+/// Default number of V1 edition markers [`decode_printed_editions`] reads, covering
+/// editions up to 247,999. With the V2 marker, that is 1,001 addresses, or eleven
+/// `getMultipleAccounts` requests.
+pub const MAX_SCANNED_EDITION_MARKERS: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EditionMarkerVersion {
+    V1,
+    V2,
+}
+
+/// Printed edition numbers of a master edition.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PrintedEditions {
+    pub version: EditionMarkerVersion,
+    pub supply: u64,
+    pub max_supply: Option<u64>,
+    pub minted: BTreeSet<u64>,
+    /// Highest edition number checked, when the marker limit cut the scan short of the
+    /// max supply (or the supply of an unlimited master edition). Editions past it are
+    /// unknown and never reported as free.
+    pub scan_limit: Option<u64>,
+}
+
+impl PrintedEditions {
+    pub fn is_minted(&self, edition: u64) -> bool {
+        self.minted.contains(&edition)
+    }
+
+    /// Unprinted edition numbers up to the max supply, or up to the highest printed
+    /// edition for unlimited master editions.
+    pub fn free(&self) -> Vec<u64> {
+        let last = self
+            .max_supply
+            .unwrap_or_else(|| self.minted.last().copied().unwrap_or(0));
+        let last = self.scan_limit.map_or(last, |limit| last.min(limit));
+
+        (1..=last).filter(|n| !self.is_minted(*n)).collect()
+    }
+
+    /// The lowest edition number that can still be printed, if any.
+    pub fn next_free(&self) -> Option<u64> {
+        let next = (1..).find(|n| !self.is_minted(*n))?;
+
+        match (self.max_supply, self.scan_limit) {
+            (Some(max_supply), _) if next > max_supply => None,
+            (_, Some(limit)) if next > limit => None,
+            _ => Some(next),
+        }
+    }
+}
+
+/// Read the edition markers of the master edition for `master_mint`.
+///
+/// The V2 marker and every V1 marker covering editions up to the max supply (or the
+/// current supply for unlimited master editions) are fetched in one batch, reading at
+/// most [`MAX_SCANNED_EDITION_MARKERS`] V1 markers. Edition markers don't reference their
+/// master edition, so they can't be found with `getProgramAccounts` and a huge max
+/// supply would otherwise mean millions of lookups. Editions past a cut-short scan are
+/// reported in [`PrintedEditions::scan_limit`]; past a complete scan of an unlimited
+/// master edition they count as free.
+pub fn decode_printed_editions<P: ToPubkey>(
+    client: &(impl Rpc + Sync + ?Sized),
+    master_mint: P,
+) -> Result<PrintedEditions> {
+    decode_printed_editions_with_limit(client, master_mint, MAX_SCANNED_EDITION_MARKERS)
+}
+
+/// Like [`decode_printed_editions`], reading at most `max_markers` V1 edition markers.
+pub fn decode_printed_editions_with_limit<P: ToPubkey>(
+    client: &(impl Rpc + Sync + ?Sized),
+    master_mint: P,
+    max_markers: u64,
+) -> Result<PrintedEditions> {
+    let mint = master_mint.to_pubkey()?;
+    let master = decode_master_edition_from_mint(client, mint)?;
+
+    let last = master.max_supply.unwrap_or(master.supply);
+    let markers = (last / EDITION_MARKER_BIT_SIZE + 1).min(max_markers.max(1));
+    let scanned_through = markers.saturating_mul(EDITION_MARKER_BIT_SIZE) - 1;
+
+    let addresses = std::iter::once(derive_edition_marker_v2_pda(&mint))
+        .chain((0..markers).map(|i| derive_edition_marker_pda(&mint, i * EDITION_MARKER_BIT_SIZE)))
+        .map(Ok)
+        .collect();
+
+    let mut results = decode_batch(client, addresses, decode_marker).into_iter();
+
+    let v2 = match results.next() {
+        Some(Ok(Marker::V2(marker))) => Some(marker),
+        Some(Err(MetabossError::MissingAccount(_))) | None => None,
+        Some(Ok(Marker::V1(_))) => {
+            return Err(MetabossError::deserialize(
+                Some(derive_edition_marker_v2_pda(&mint)),
+                "expected an EditionMarkerV2 account",
+            ))
+        }
+        Some(Err(err)) => return Err(err),
+    };
+
+    let minted = match &v2 {
+        Some(marker) => minted_from_ledger(&marker.ledger, 0),
+        None => {
+            let mut minted = BTreeSet::new();
+            for (i, result) in results.enumerate() {
+                match result {
+                    Ok(Marker::V1(marker)) => minted.extend(minted_from_ledger(
+                        &marker.ledger,
+                        i as u64 * EDITION_MARKER_BIT_SIZE,
+                    )),
+                    Ok(Marker::V2(_)) => {
+                        return Err(MetabossError::deserialize(
+                            Some(derive_edition_marker_pda(
+                                &mint,
+                                i as u64 * EDITION_MARKER_BIT_SIZE,
+                            )),
+                            "expected an EditionMarker account",
+                        ))
+                    }
+                    // Markers are only created once an edition in their range is printed.
+                    Err(MetabossError::MissingAccount(_)) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            minted
+        }
+    };
+
+    Ok(PrintedEditions {
+        version: if v2.is_some() {
+            EditionMarkerVersion::V2
+        } else {
+            EditionMarkerVersion::V1
+        },
+        supply: master.supply,
+        max_supply: master.max_supply,
+        minted,
+        scan_limit: (last > scanned_through).then_some(scanned_through),
+    })
+}
+
+enum Marker {
+    V1(EditionMarker),
+    V2(EditionMarkerV2),
+}
+
+fn decode_marker(owner: &Pubkey, data: &[u8]) -> Result<Marker, DecodeError> {
+    match data.first() {
+        Some(key) if *key == Key::EditionMarkerV2 as u8 => {
+            decode_edition_marker_v2_from_bytes(owner, data).map(Marker::V2)
+        }
+        _ => decode_edition_marker_from_bytes(owner, data).map(Marker::V1),
+    }
+}
+
+/// Edition numbers set in `ledger`, whose first bit is edition `first`. Edition 0 is
+/// the master edition itself and never counts as printed.
+fn minted_from_ledger(ledger: &[u8], first: u64) -> BTreeSet<u64> {
+    ledger
+        .iter()
+        .enumerate()
+        .flat_map(|(index, byte)| {
+            (0..8)
+                .filter(move |bit| byte & (1 << (7 - bit)) != 0)
+                .map(move |bit| first + index as u64 * 8 + bit)
+        })
+        .filter(|edition| *edition != 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::accounts::MasterEdition;
+    use serde_json::json;
+    use solana_account_decoder::{encode_ui_account, UiAccountEncoding};
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+        rpc_response::{Response, RpcResponseContext},
+    };
+    use solana_sdk::account::Account;
+
+    fn token_metadata_account(data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1,
+            data,
+            owner: mpl_token_metadata::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_minted_from_ledger() {
+        // Editions 1 and 2 in the first byte, 8 and 15 in the second.
+        let ledger = [0b0110_0000, 0b1000_0001];
+
+        assert_eq!(
+            minted_from_ledger(&ledger, 0),
+            BTreeSet::from([1, 2, 8, 15])
+        );
+        assert_eq!(
+            minted_from_ledger(&ledger, 248),
+            BTreeSet::from([249, 250, 256, 263])
+        );
+    }
+
+    // A client serving `master` and a single V1 marker with editions 1 and 3 printed.
+    fn v1_client(mint: &Pubkey, master: MasterEdition) -> RpcClient {
+        let mut ledger = [0; 31];
+        ledger[0] = 0b0101_0000;
+        let marker = EditionMarker {
+            key: Key::EditionMarker,
+            ledger,
+        };
+
+        let ui = |pubkey: &Pubkey, account: &Account| {
+            encode_ui_account(pubkey, account, UiAccountEncoding::Base64, None, None)
+        };
+        let master_pda = derive_edition_pda(mint);
+        let marker_pda = derive_edition_marker_pda(mint, 0);
+
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: Some(ui(
+                    &master_pda,
+                    &token_metadata_account(master.try_to_vec().unwrap()),
+                )),
+            })
+            .unwrap(),
+        );
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            json!({
+                "context": { "slot": 1 },
+                "value": [
+                    null,
+                    ui(&marker_pda, &token_metadata_account(marker.try_to_vec().unwrap())),
+                ],
+            }),
+        );
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+    }
+
+    #[test]
+    fn test_decode_printed_editions_v1() {
+        let mint = Pubkey::new_unique();
+        let client = v1_client(
+            &mint,
+            MasterEdition {
+                key: Key::MasterEditionV2,
+                supply: 2,
+                max_supply: Some(4),
+            },
+        );
+
+        let printed = decode_printed_editions(&client, mint).unwrap();
+
+        assert_eq!(printed.version, EditionMarkerVersion::V1);
+        assert_eq!(printed.minted, BTreeSet::from([1, 3]));
+        assert!(printed.is_minted(3));
+        assert_eq!(printed.free(), vec![2, 4]);
+        assert_eq!(printed.next_free(), Some(2));
+        assert_eq!(printed.scan_limit, None);
+    }
+
+    #[test]
+    fn test_decode_printed_editions_stops_at_marker_limit() {
+        let mint = Pubkey::new_unique();
+        let client = v1_client(
+            &mint,
+            MasterEdition {
+                key: Key::MasterEditionV2,
+                supply: 2,
+                max_supply: Some(u64::MAX),
+            },
+        );
+
+        // The mock only answers a request for the V2 marker and one V1 marker.
+        let printed = decode_printed_editions_with_limit(&client, mint, 1).unwrap();
+
+        assert_eq!(printed.minted, BTreeSet::from([1, 3]));
+        assert_eq!(printed.scan_limit, Some(EDITION_MARKER_BIT_SIZE - 1));
+        assert_eq!(printed.free().len(), 245);
+        assert_eq!(printed.next_free(), Some(2));
+    }
+
+    #[test]
+    fn test_printed_editions_next_free() {
+        let full = PrintedEditions {
+            version: EditionMarkerVersion::V2,
+            supply: 2,
+            max_supply: Some(2),
+            minted: BTreeSet::from([1, 2]),
+            scan_limit: None,
+        };
+        assert_eq!(full.next_free(), None);
+        assert!(full.free().is_empty());
+
+        let unlimited = PrintedEditions {
+            max_supply: None,
+            minted: BTreeSet::from([1, 2, 4]),
+            ..full
+        };
+        assert_eq!(unlimited.next_free(), Some(3));
+        assert_eq!(unlimited.free(), vec![3]);
+    }
+}
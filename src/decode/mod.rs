@@ -1,18 +1,34 @@
-use anyhow::Result;
 use borsh::de::BorshDeserialize;
 use mpl_token_metadata::accounts::{
-    CollectionAuthorityRecord, Edition, EditionMarker, MasterEdition, Metadata,
+    CollectionAuthorityRecord, Edition, EditionMarker, EditionMarkerV2, MasterEdition, Metadata,
     MetadataDelegateRecord, TokenRecord, UseAuthorityRecord,
 };
-use solana_client::rpc_client::RpcClient;
 use solana_program::{bpf_loader_upgradeable::UpgradeableLoaderState, program_pack::Pack};
-use solana_sdk::{account_utils::StateMut, pubkey::Pubkey};
+use solana_sdk::pubkey::Pubkey;
 use spl_token::state::{Account as Token, Mint};
 use std::str::FromStr;
 
+mod batch;
+mod bytes;
+mod detect;
+mod editions;
 pub mod errors;
-use crate::{derive::*, nft::get_nft_token_account};
+mod token_2022;
+mod view;
+
+use crate::{
+    derive::*,
+    errors::{MetabossError, Result},
+    nft::get_nft_token_account,
+    rpc::Rpc,
+};
+pub use batch::*;
+pub use bytes::*;
+pub use detect::*;
+pub use editions::*;
 use errors::DecodeError;
+pub use token_2022::*;
+pub use view::*;
 
 pub trait ToPubkey {
     fn to_pubkey(self) -> Result<Pubkey, DecodeError>;
@@ -36,53 +52,22 @@ impl ToPubkey for Pubkey {
     }
 }
 
-pub fn decode_metadata(client: &RpcClient, pubkey: &Pubkey) -> Result<Metadata, DecodeError> {
-    let account_data = client
-        .get_account_data(pubkey)
-        .map_err(|e| DecodeError::ClientError(Box::new(e.kind)))?;
-
-    Metadata::safe_deserialize(account_data.as_ref())
-        .map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
+pub fn decode_metadata(client: &(impl Rpc + ?Sized), pubkey: &Pubkey) -> Result<Metadata> {
+    fetch_and_decode(client, pubkey, metadata_from_data)
 }
 
-pub fn decode_master(client: &RpcClient, pubkey: &Pubkey) -> Result<MasterEdition, DecodeError> {
-    let account_data = match client.get_account_data(pubkey) {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(Box::new(err.kind)));
-        }
-    };
-
-    let master_edition: MasterEdition =
-        match <MasterEdition as BorshDeserialize>::deserialize(&mut account_data.as_slice()) {
-            Ok(m) => m,
-            Err(err) => return Err(DecodeError::DecodeMetadataFailed(err.to_string())),
-        };
-
-    Ok(master_edition)
+pub fn decode_master(client: &(impl Rpc + ?Sized), pubkey: &Pubkey) -> Result<MasterEdition> {
+    fetch_and_decode(client, pubkey, master_edition_from_data)
 }
 
-pub fn decode_edition(client: &RpcClient, pubkey: &Pubkey) -> Result<Edition, DecodeError> {
-    let account_data = match client.get_account_data(pubkey) {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(Box::new(err.kind)));
-        }
-    };
-
-    let edition: Edition =
-        match <Edition as BorshDeserialize>::deserialize(&mut account_data.as_slice()) {
-            Ok(e) => e,
-            Err(err) => return Err(DecodeError::DecodeMetadataFailed(err.to_string())),
-        };
-
-    Ok(edition)
+pub fn decode_edition(client: &(impl Rpc + ?Sized), pubkey: &Pubkey) -> Result<Edition> {
+    fetch_and_decode(client, pubkey, edition_from_data)
 }
 
 pub fn decode_metadata_from_mint<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     mint_address: P,
-) -> Result<Metadata, DecodeError> {
+) -> Result<Metadata> {
     let pubkey = mint_address.to_pubkey()?;
     let metadata_pda = derive_metadata_pda(&pubkey);
 
@@ -90,9 +75,9 @@ pub fn decode_metadata_from_mint<P: ToPubkey>(
 }
 
 pub fn decode_master_edition_from_mint<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     mint_address: P,
-) -> Result<MasterEdition, DecodeError> {
+) -> Result<MasterEdition> {
     let pubkey = mint_address.to_pubkey()?;
 
     let edition_pda = derive_edition_pda(&pubkey);
@@ -101,9 +86,9 @@ pub fn decode_master_edition_from_mint<P: ToPubkey>(
 }
 
 pub fn decode_edition_from_mint<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     mint_address: P,
-) -> Result<Edition, DecodeError> {
+) -> Result<Edition> {
     let pubkey = mint_address.to_pubkey()?;
 
     let edition_pda = derive_edition_pda(&pubkey);
@@ -111,50 +96,23 @@ pub fn decode_edition_from_mint<P: ToPubkey>(
     decode_edition(client, &edition_pda)
 }
 
-pub fn decode_mint<P: ToPubkey>(client: &RpcClient, mint_address: P) -> Result<Mint, DecodeError> {
+pub fn decode_mint<P: ToPubkey>(client: &(impl Rpc + ?Sized), mint_address: P) -> Result<Mint> {
     let pubkey = mint_address.to_pubkey()?;
 
-    let account = match client.get_account(&pubkey) {
-        Ok(account) => account,
-        Err(err) => {
-            return Err(DecodeError::ClientError(Box::new(err.kind)));
-        }
-    };
-
-    let mint = match Mint::unpack(&account.data) {
-        Ok(m) => m,
-        Err(err) => return Err(DecodeError::DecodeDataFailed(err.to_string())),
-    };
-
-    Ok(mint)
+    fetch_and_decode(client, &pubkey, mint_from_data)
 }
 
-pub fn decode_token<P: ToPubkey>(
-    client: &RpcClient,
-    token_address: P,
-) -> Result<Token, DecodeError> {
+pub fn decode_token<P: ToPubkey>(client: &(impl Rpc + ?Sized), token_address: P) -> Result<Token> {
     let pubkey = token_address.to_pubkey()?;
 
-    let account_data = match client.get_account_data(&pubkey) {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(Box::new(err.kind)));
-        }
-    };
-
-    let token_account: Token = match Token::unpack(&account_data) {
-        Ok(t) => t,
-        Err(err) => return Err(DecodeError::DecodeMetadataFailed(err.to_string())),
-    };
-
-    Ok(token_account)
+    fetch_and_decode(client, &pubkey, token_from_data)
 }
 
 pub fn decode_edition_marker_from_mint<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     mint_address: P,
     edition_num: u64,
-) -> Result<EditionMarker, DecodeError> {
+) -> Result<EditionMarker> {
     let pubkey = mint_address.to_pubkey()?;
 
     let edition_marker_pda = derive_edition_marker_pda(&pubkey, edition_num);
@@ -163,113 +121,133 @@ pub fn decode_edition_marker_from_mint<P: ToPubkey>(
 }
 
 pub fn decode_edition_marker<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     pubkey: &Pubkey,
-) -> Result<EditionMarker, DecodeError> {
-    let account_data = match client.get_account_data(pubkey) {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(Box::new(err.kind)));
-        }
-    };
+) -> Result<EditionMarker> {
+    fetch_and_decode(client, pubkey, edition_marker_from_data)
+}
 
-    let edition_marker: EditionMarker =
-        match <EditionMarker as BorshDeserialize>::deserialize(&mut account_data.as_slice()) {
-            Ok(e) => e,
-            Err(err) => return Err(DecodeError::DecodeMetadataFailed(err.to_string())),
-        };
+pub fn decode_edition_marker_v2_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<EditionMarkerV2> {
+    let pubkey = mint_address.to_pubkey()?;
 
-    Ok(edition_marker)
+    decode_edition_marker_v2(client, &derive_edition_marker_v2_pda(&pubkey))
+}
+
+pub fn decode_edition_marker_v2(
+    client: &(impl Rpc + ?Sized),
+    pubkey: &Pubkey,
+) -> Result<EditionMarkerV2> {
+    fetch_and_decode(client, pubkey, edition_marker_v2_from_data)
 }
 
 pub fn decode_bpf_loader_upgradeable_state<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     program_address: P,
-) -> Result<UpgradeableLoaderState, DecodeError> {
+) -> Result<UpgradeableLoaderState> {
     let pubkey = program_address.to_pubkey()?;
 
-    let account = client
-        .get_account(&pubkey)
-        .map_err(|err| DecodeError::ClientError(Box::new(err.kind)))?;
-
-    let upgradeable_loader_state: UpgradeableLoaderState = account
-        .state()
-        .map_err(|err| DecodeError::DeserializationFailed(err.to_string()))?;
-
-    Ok(upgradeable_loader_state)
+    fetch_and_decode(client, &pubkey, upgradeable_loader_state_from_data)
 }
 
 pub fn decode_collection_authority_record<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     address: P,
-) -> Result<CollectionAuthorityRecord, DecodeError> {
-    let pubkey = address.to_pubkey()?;
-
-    let account_data = client
-        .get_account_data(&pubkey)
-        .map_err(|e| DecodeError::ClientError(Box::new(e.kind)))?;
-
-    <CollectionAuthorityRecord as BorshDeserialize>::deserialize(&mut account_data.as_slice())
-        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
+) -> Result<CollectionAuthorityRecord> {
+    decode_record(client, &address.to_pubkey()?)
 }
 
 pub fn decode_use_authority_record<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     address: P,
-) -> Result<UseAuthorityRecord, DecodeError> {
-    let pubkey = address.to_pubkey()?;
-
-    let account_data = client
-        .get_account_data(&pubkey)
-        .map_err(|e| DecodeError::ClientError(Box::new(e.kind)))?;
-
-    <UseAuthorityRecord as BorshDeserialize>::deserialize(&mut account_data.as_slice())
-        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
+) -> Result<UseAuthorityRecord> {
+    decode_record(client, &address.to_pubkey()?)
 }
 
 pub fn decode_metadata_delegate<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     address: P,
-) -> Result<MetadataDelegateRecord, DecodeError> {
-    let pubkey = address.to_pubkey()?;
-
-    let account_data = client
-        .get_account_data(&pubkey)
-        .map_err(|e| DecodeError::ClientError(Box::new(e.kind)))?;
-
-    <MetadataDelegateRecord as BorshDeserialize>::deserialize(&mut account_data.as_slice())
-        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
+) -> Result<MetadataDelegateRecord> {
+    decode_record(client, &address.to_pubkey()?)
 }
 
 pub fn decode_token_record<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     address: P,
-) -> Result<TokenRecord, DecodeError> {
-    let pubkey = address.to_pubkey()?;
-
-    let account_data = client
-        .get_account_data(&pubkey)
-        .map_err(|e| DecodeError::ClientError(Box::new(e.kind)))?;
-
-    <TokenRecord as BorshDeserialize>::deserialize(&mut account_data.as_slice())
-        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
+) -> Result<TokenRecord> {
+    decode_record(client, &address.to_pubkey()?)
 }
 
 pub fn decode_token_record_from_mint<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     address: P,
-) -> Result<TokenRecord, DecodeError> {
+) -> Result<TokenRecord> {
     let mint_pubkey = address.to_pubkey()?;
 
-    let token_pubkey = get_nft_token_account(client, &mint_pubkey.to_string())
-        .map_err(|e| DecodeError::GeneralError(e.to_string()))?;
+    let token_pubkey = get_nft_token_account(client, &mint_pubkey.to_string())?;
 
     let token_record_pda = derive_token_record_pda(&mint_pubkey, &token_pubkey);
 
-    let account_data = client
-        .get_account_data(&token_record_pda)
-        .map_err(|e| DecodeError::ClientError(Box::new(e.kind)))?;
+    decode_record(client, &token_record_pda)
+}
+
+fn decode_record<T: BorshDeserialize>(client: &(impl Rpc + ?Sized), pubkey: &Pubkey) -> Result<T> {
+    fetch_and_decode(client, pubkey, record_from_data)
+}
+
+/// Fetch `pubkey` and decode its data, attributing a decode failure to the account.
+fn fetch_and_decode<T>(
+    client: &(impl Rpc + ?Sized),
+    pubkey: &Pubkey,
+    decode: fn(&[u8]) -> Result<T, DecodeError>,
+) -> Result<T> {
+    let data = client.get_account_data(pubkey)?;
+
+    decode(&data).map_err(|e| MetabossError::from(e).for_account(pubkey))
+}
+
+// Deserializers shared by the blocking and async decode functions.
+
+pub(crate) fn metadata_from_data(data: &[u8]) -> Result<Metadata, DecodeError> {
+    Metadata::safe_deserialize(data).map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
+}
+
+pub(crate) fn master_edition_from_data(mut data: &[u8]) -> Result<MasterEdition, DecodeError> {
+    <MasterEdition as BorshDeserialize>::deserialize(&mut data)
+        .map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
+}
+
+pub(crate) fn edition_from_data(mut data: &[u8]) -> Result<Edition, DecodeError> {
+    <Edition as BorshDeserialize>::deserialize(&mut data)
+        .map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
+}
+
+pub(crate) fn edition_marker_from_data(mut data: &[u8]) -> Result<EditionMarker, DecodeError> {
+    <EditionMarker as BorshDeserialize>::deserialize(&mut data)
+        .map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
+}
+
+pub(crate) fn edition_marker_v2_from_data(mut data: &[u8]) -> Result<EditionMarkerV2, DecodeError> {
+    <EditionMarkerV2 as BorshDeserialize>::deserialize(&mut data)
+        .map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
+}
+
+pub(crate) fn mint_from_data(data: &[u8]) -> Result<Mint, DecodeError> {
+    Mint::unpack(data).map_err(|e| DecodeError::DecodeDataFailed(e.to_string()))
+}
+
+pub(crate) fn token_from_data(data: &[u8]) -> Result<Token, DecodeError> {
+    Token::unpack(data).map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
+}
+
+pub(crate) fn upgradeable_loader_state_from_data(
+    data: &[u8],
+) -> Result<UpgradeableLoaderState, DecodeError> {
+    bincode::deserialize(data).map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
+}
 
-    <TokenRecord as BorshDeserialize>::deserialize(&mut account_data.as_slice())
-        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
+pub(crate) fn record_from_data<T: BorshDeserialize>(mut data: &[u8]) -> Result<T, DecodeError> {
+    T::deserialize(&mut data).map_err(|e| DecodeError::DeserializationFailed(e.to_string()))
 }
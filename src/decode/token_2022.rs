@@ -0,0 +1,308 @@
+//! Mint and token account decoding for both the SPL Token and Token-2022 programs.
+//!
+//! `decode_mint` and `decode_token` only understand SPL Token accounts. These functions
+//! also read Token-2022 accounts, returning the base state along with the extensions
+//! the crate knows how to parse.
+
+use solana_program::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        group_member_pointer::GroupMemberPointer,
+        group_pointer::GroupPointer,
+        metadata_pointer::MetadataPointer,
+        mint_close_authority::MintCloseAuthority,
+        non_transferable::{NonTransferable, NonTransferableAccount},
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+        transfer_hook::{TransferHook, TransferHookAccount},
+        AccountType, BaseState, BaseStateWithExtensions, Extension, ExtensionType,
+        StateWithExtensions,
+    },
+    state::{Account, Mint, Multisig},
+};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use super::{errors::DecodeError, ToPubkey};
+use crate::{
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MintWithExtensions {
+    /// The token program that owns the mint.
+    pub program_id: Pubkey,
+    pub base: Mint,
+    pub extensions: MintExtensions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MintExtensions {
+    /// Every extension on the mint, including ones that aren't parsed below.
+    pub types: Vec<ExtensionType>,
+    pub metadata_pointer: Option<MetadataPointer>,
+    pub token_metadata: Option<TokenMetadata>,
+    pub transfer_fee_config: Option<TransferFeeConfig>,
+    pub transfer_hook: Option<TransferHook>,
+    pub non_transferable: bool,
+    pub permanent_delegate: Option<PermanentDelegate>,
+    pub mint_close_authority: Option<MintCloseAuthority>,
+    pub group_pointer: Option<GroupPointer>,
+    pub group_member_pointer: Option<GroupMemberPointer>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenWithExtensions {
+    /// The token program that owns the token account.
+    pub program_id: Pubkey,
+    pub base: Account,
+    pub extensions: TokenExtensions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenExtensions {
+    /// Every extension on the token account, including ones that aren't parsed below.
+    pub types: Vec<ExtensionType>,
+    /// Withheld transfer fees.
+    pub transfer_fee_amount: Option<TransferFeeAmount>,
+    pub transfer_hook: Option<TransferHookAccount>,
+    pub non_transferable: bool,
+}
+
+pub fn decode_mint_with_extensions<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<MintWithExtensions> {
+    let pubkey = mint_address.to_pubkey()?;
+    let account = client.get_account(&pubkey)?;
+
+    decode_mint_with_extensions_from_bytes(&account.owner, &account.data)
+        .map_err(|e| MetabossError::from(e).for_account(&pubkey))
+}
+
+pub fn decode_token_with_extensions<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    token_address: P,
+) -> Result<TokenWithExtensions> {
+    let pubkey = token_address.to_pubkey()?;
+    let account = client.get_account(&pubkey)?;
+
+    decode_token_with_extensions_from_bytes(&account.owner, &account.data)
+        .map_err(|e| MetabossError::from(e).for_account(&pubkey))
+}
+
+pub fn decode_mint_with_extensions_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<MintWithExtensions, DecodeError> {
+    let error = DecodeError::DecodeDataFailed;
+    check_token_program(owner, error)?;
+
+    let state = StateWithExtensions::<Mint>::unpack(data).map_err(|e| error(e.to_string()))?;
+    let types = state
+        .get_extension_types()
+        .map_err(|e| error(e.to_string()))?;
+
+    let token_metadata = if types.contains(&ExtensionType::TokenMetadata) {
+        Some(
+            state
+                .get_variable_len_extension::<TokenMetadata>()
+                .map_err(|e| error(e.to_string()))?,
+        )
+    } else {
+        None
+    };
+
+    let extensions = MintExtensions {
+        metadata_pointer: extension(&state, &types, error)?,
+        token_metadata,
+        transfer_fee_config: extension(&state, &types, error)?,
+        transfer_hook: extension(&state, &types, error)?,
+        non_transferable: types.contains(&NonTransferable::TYPE),
+        permanent_delegate: extension(&state, &types, error)?,
+        mint_close_authority: extension(&state, &types, error)?,
+        group_pointer: extension(&state, &types, error)?,
+        group_member_pointer: extension(&state, &types, error)?,
+        types,
+    };
+
+    Ok(MintWithExtensions {
+        program_id: *owner,
+        base: state.base,
+        extensions,
+    })
+}
+
+pub fn decode_token_with_extensions_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<TokenWithExtensions, DecodeError> {
+    let error = DecodeError::DecodeMetadataFailed;
+    check_token_program(owner, error)?;
+
+    let state = StateWithExtensions::<Account>::unpack(data).map_err(|e| error(e.to_string()))?;
+    let types = state
+        .get_extension_types()
+        .map_err(|e| error(e.to_string()))?;
+
+    let extensions = TokenExtensions {
+        transfer_fee_amount: extension(&state, &types, error)?,
+        transfer_hook: extension(&state, &types, error)?,
+        non_transferable: types.contains(&NonTransferableAccount::TYPE),
+        types,
+    };
+
+    Ok(TokenWithExtensions {
+        program_id: *owner,
+        base: state.base,
+        extensions,
+    })
+}
+
+/// Whether Token-2022 account data holds a mint or a token account, judging by its
+/// length and account type byte.
+pub(crate) fn token_2022_account_type(data: &[u8]) -> Option<AccountType> {
+    match data.len() {
+        Mint::LEN => Some(AccountType::Mint),
+        Account::LEN => Some(AccountType::Account),
+        Multisig::LEN => None,
+        len if len > Account::LEN => match data[Account::LEN] {
+            t if t == AccountType::Mint as u8 => Some(AccountType::Mint),
+            t if t == AccountType::Account as u8 => Some(AccountType::Account),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn check_token_program(
+    owner: &Pubkey,
+    error: fn(String) -> DecodeError,
+) -> Result<(), DecodeError> {
+    if *owner != spl_token::ID && *owner != spl_token_2022::ID {
+        return Err(error(format!(
+            "account is owned by {owner}, which isn't a token program"
+        )));
+    }
+
+    Ok(())
+}
+
+fn extension<S, V>(
+    state: &StateWithExtensions<S>,
+    types: &[ExtensionType],
+    error: fn(String) -> DecodeError,
+) -> Result<Option<V>, DecodeError>
+where
+    S: BaseState + Pack,
+    V: Extension + bytemuck::Pod,
+{
+    if !types.contains(&V::TYPE) {
+        return Ok(None);
+    }
+
+    state
+        .get_extension::<V>()
+        .copied()
+        .map(Some)
+        .map_err(|e| error(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_pod::optional_keys::OptionalNonZeroPubkey;
+    use spl_token_2022::extension::{BaseStateWithExtensionsMut, StateWithExtensionsMut};
+
+    #[test]
+    fn test_decode_token_2022_mint_with_extensions() {
+        let mint_address = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let token_metadata = TokenMetadata {
+            update_authority: OptionalNonZeroPubkey(authority),
+            mint: mint_address,
+            name: "Extended".to_string(),
+            symbol: "EXT".to_string(),
+            uri: "https://example.com/extended.json".to_string(),
+            additional_metadata: vec![("edition".to_string(), "1".to_string())],
+        };
+
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[
+            ExtensionType::MetadataPointer,
+            ExtensionType::NonTransferable,
+        ])
+        .unwrap()
+            + token_metadata.tlv_size_of().unwrap();
+        let mut data = vec![0; len];
+
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let pointer = state.init_extension::<MetadataPointer>(true).unwrap();
+        pointer.authority = OptionalNonZeroPubkey(authority);
+        pointer.metadata_address = OptionalNonZeroPubkey(mint_address);
+        state.init_extension::<NonTransferable>(true).unwrap();
+        state.base = Mint {
+            mint_authority: Some(authority).into(),
+            supply: 1,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        state
+            .init_variable_len_extension(&token_metadata, false)
+            .unwrap();
+
+        assert_eq!(token_2022_account_type(&data), Some(AccountType::Mint));
+
+        let decoded = decode_mint_with_extensions_from_bytes(&spl_token_2022::ID, &data).unwrap();
+
+        assert_eq!(decoded.program_id, spl_token_2022::ID);
+        assert_eq!(decoded.base.supply, 1);
+        assert_eq!(
+            decoded.extensions.types,
+            vec![
+                ExtensionType::MetadataPointer,
+                ExtensionType::NonTransferable,
+                ExtensionType::TokenMetadata
+            ]
+        );
+        assert_eq!(
+            Option::<Pubkey>::from(
+                decoded
+                    .extensions
+                    .metadata_pointer
+                    .unwrap()
+                    .metadata_address
+            ),
+            Some(mint_address)
+        );
+        assert_eq!(decoded.extensions.token_metadata, Some(token_metadata));
+        assert!(decoded.extensions.non_transferable);
+        assert_eq!(decoded.extensions.transfer_fee_config, None);
+
+        assert!(matches!(
+            decode_mint_with_extensions_from_bytes(&Pubkey::new_unique(), &data),
+            Err(DecodeError::DecodeDataFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_spl_token_account_with_extensions() {
+        let token = Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 1,
+            state: spl_token_2022::state::AccountState::Initialized,
+            ..Account::default()
+        };
+        let mut data = vec![0; Account::LEN];
+        Account::pack(token, &mut data).unwrap();
+
+        let decoded = decode_token_with_extensions_from_bytes(&spl_token::ID, &data).unwrap();
+
+        assert_eq!(decoded.base, token);
+        assert_eq!(decoded.extensions, TokenExtensions::default());
+    }
+}
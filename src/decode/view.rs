@@ -0,0 +1,460 @@
+//! Normalized, JSON-friendly views of decoded Token Metadata accounts.
+//!
+//! Views trim the null padding from strings, render pubkeys as base58 strings and enums
+//! as snake_case names, so their serde form is a stable schema for `decode` output.
+//! Token standards use the same names as [`crate::check::MetadataValue`].
+
+use mpl_token_metadata::{
+    accounts::{
+        CollectionAuthorityRecord, Edition, MasterEdition, Metadata, MetadataDelegateRecord,
+        TokenRecord, UseAuthorityRecord,
+    },
+    types::{CollectionDetails, ProgrammableConfig, TokenDelegateRole, TokenState, UseMethod},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{decode_any_from_bytes, decode_metadata_from_bytes, DecodedAccount, ToPubkey};
+use crate::{
+    check::token_standard_to_string,
+    derive::*,
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataView {
+    pub mint: String,
+    pub update_authority: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CreatorView>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<String>,
+    pub collection: Option<CollectionView>,
+    pub uses: Option<UsesView>,
+    /// Size of a sized collection parent.
+    pub collection_size: Option<u64>,
+    pub rule_set: Option<String>,
+    /// Only filled in by [`decode_metadata_view`].
+    pub edition: Option<EditionView>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatorView {
+    pub address: String,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionView {
+    pub mint: String,
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsesView {
+    pub use_method: UseMethodView,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UseMethodView {
+    Burn,
+    Multiple,
+    Single,
+}
+
+/// A master edition or a print edition, tagged by `type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EditionView {
+    Master {
+        supply: u64,
+        max_supply: Option<u64>,
+    },
+    Print {
+        /// The master edition account the print was made from.
+        parent: String,
+        number: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenRecordView {
+    pub bump: u8,
+    pub state: TokenStateView,
+    pub rule_set_revision: Option<u64>,
+    pub delegate: Option<String>,
+    pub delegate_role: Option<TokenDelegateRoleView>,
+    pub locked_transfer: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStateView {
+    Unlocked,
+    Locked,
+    Listed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenDelegateRoleView {
+    Sale,
+    Transfer,
+    Utility,
+    Staking,
+    Standard,
+    LockedTransfer,
+    Migration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataDelegateRecordView {
+    pub bump: u8,
+    pub mint: String,
+    pub delegate: String,
+    pub update_authority: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionAuthorityRecordView {
+    pub bump: u8,
+    pub update_authority: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UseAuthorityRecordView {
+    pub bump: u8,
+    pub allowed_uses: u64,
+}
+
+/// Decode the metadata of `mint_address` along with its master or print edition, if it
+/// has one. Both accounts are fetched in one request.
+pub fn decode_metadata_view<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<MetadataView> {
+    let mint = mint_address.to_pubkey()?;
+    let metadata_pda = derive_metadata_pda(&mint);
+
+    let edition_pda = derive_edition_pda(&mint);
+    let accounts = client.get_multiple_accounts(&[metadata_pda, edition_pda])?;
+
+    let (metadata, edition) = match accounts.as_slice() {
+        [Some(metadata), edition] => (metadata, edition),
+        _ => return Err(MetabossError::MissingAccount(metadata_pda)),
+    };
+
+    let metadata = decode_metadata_from_bytes(&metadata.owner, &metadata.data)
+        .map_err(|e| MetabossError::from(e).for_account(&metadata_pda))?;
+    let mut view = MetadataView::from(metadata);
+
+    view.edition = match edition {
+        Some(edition) => match decode_any_from_bytes(&edition.owner, &edition.data)
+            .map_err(|e| MetabossError::from(e).for_account(&edition_pda))?
+        {
+            DecodedAccount::MasterEditionV1(master) => Some(EditionView::Master {
+                supply: master.supply,
+                max_supply: master.max_supply,
+            }),
+            DecodedAccount::MasterEditionV2(master) => Some(master.into()),
+            DecodedAccount::Edition(edition) => Some(edition.into()),
+            _ => {
+                return Err(MetabossError::deserialize(
+                    Some(edition_pda),
+                    "expected an edition account",
+                ))
+            }
+        },
+        None => None,
+    };
+
+    Ok(view)
+}
+
+fn trim_padding(value: &str) -> String {
+    value.trim_matches(char::from(0)).to_string()
+}
+
+impl From<Metadata> for MetadataView {
+    fn from(metadata: Metadata) -> Self {
+        Self {
+            mint: metadata.mint.to_string(),
+            update_authority: metadata.update_authority.to_string(),
+            name: trim_padding(&metadata.name),
+            symbol: trim_padding(&metadata.symbol),
+            uri: trim_padding(&metadata.uri),
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata
+                .creators
+                .unwrap_or_default()
+                .into_iter()
+                .map(|creator| CreatorView {
+                    address: creator.address.to_string(),
+                    verified: creator.verified,
+                    share: creator.share,
+                })
+                .collect(),
+            primary_sale_happened: metadata.primary_sale_happened,
+            is_mutable: metadata.is_mutable,
+            edition_nonce: metadata.edition_nonce,
+            token_standard: metadata
+                .token_standard
+                .as_ref()
+                .map(token_standard_to_string),
+            collection: metadata.collection.map(|collection| CollectionView {
+                mint: collection.key.to_string(),
+                verified: collection.verified,
+            }),
+            uses: metadata.uses.map(|uses| UsesView {
+                use_method: match uses.use_method {
+                    UseMethod::Burn => UseMethodView::Burn,
+                    UseMethod::Multiple => UseMethodView::Multiple,
+                    UseMethod::Single => UseMethodView::Single,
+                },
+                remaining: uses.remaining,
+                total: uses.total,
+            }),
+            collection_size: match metadata.collection_details {
+                Some(CollectionDetails::V1 { size }) => Some(size),
+                // V2 collections don't track their size.
+                Some(CollectionDetails::V2 { .. }) | None => None,
+            },
+            rule_set: match metadata.programmable_config {
+                Some(ProgrammableConfig::V1 { rule_set }) => rule_set.map(|r| r.to_string()),
+                None => None,
+            },
+            edition: None,
+        }
+    }
+}
+
+impl From<MasterEdition> for EditionView {
+    fn from(master: MasterEdition) -> Self {
+        Self::Master {
+            supply: master.supply,
+            max_supply: master.max_supply,
+        }
+    }
+}
+
+impl From<Edition> for EditionView {
+    fn from(edition: Edition) -> Self {
+        Self::Print {
+            parent: edition.parent.to_string(),
+            number: edition.edition,
+        }
+    }
+}
+
+impl From<TokenRecord> for TokenRecordView {
+    fn from(record: TokenRecord) -> Self {
+        Self {
+            bump: record.bump,
+            state: match record.state {
+                TokenState::Unlocked => TokenStateView::Unlocked,
+                TokenState::Locked => TokenStateView::Locked,
+                TokenState::Listed => TokenStateView::Listed,
+            },
+            rule_set_revision: record.rule_set_revision,
+            delegate: record.delegate.map(|d| d.to_string()),
+            delegate_role: record.delegate_role.map(|role| match role {
+                TokenDelegateRole::Sale => TokenDelegateRoleView::Sale,
+                TokenDelegateRole::Transfer => TokenDelegateRoleView::Transfer,
+                TokenDelegateRole::Utility => TokenDelegateRoleView::Utility,
+                TokenDelegateRole::Staking => TokenDelegateRoleView::Staking,
+                TokenDelegateRole::Standard => TokenDelegateRoleView::Standard,
+                TokenDelegateRole::LockedTransfer => TokenDelegateRoleView::LockedTransfer,
+                TokenDelegateRole::Migration => TokenDelegateRoleView::Migration,
+            }),
+            locked_transfer: record.locked_transfer.map(|l| l.to_string()),
+        }
+    }
+}
+
+impl From<MetadataDelegateRecord> for MetadataDelegateRecordView {
+    fn from(record: MetadataDelegateRecord) -> Self {
+        Self {
+            bump: record.bump,
+            mint: record.mint.to_string(),
+            delegate: record.delegate.to_string(),
+            update_authority: record.update_authority.to_string(),
+        }
+    }
+}
+
+impl From<CollectionAuthorityRecord> for CollectionAuthorityRecordView {
+    fn from(record: CollectionAuthorityRecord) -> Self {
+        Self {
+            bump: record.bump,
+            update_authority: record.update_authority.map(|u| u.to_string()),
+        }
+    }
+}
+
+impl From<UseAuthorityRecord> for UseAuthorityRecordView {
+    fn from(record: UseAuthorityRecord) -> Self {
+        Self {
+            bump: record.bump,
+            allowed_uses: record.allowed_uses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::types::{Collection, Creator, Key, TokenStandard};
+    use serde_json::json;
+    use solana_account_decoder::{encode_ui_account, UiAccountEncoding};
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+    };
+    use solana_sdk::{account::Account, pubkey::Pubkey};
+
+    fn metadata(mint: Pubkey) -> Metadata {
+        Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint,
+            name: "View #1\0\0\0".to_string(),
+            symbol: "VIEW\0".to_string(),
+            uri: "https://example.com/1.json\0\0".to_string(),
+            seller_fee_basis_points: 500,
+            creators: Some(vec![Creator {
+                address: Pubkey::new_unique(),
+                verified: true,
+                share: 100,
+            }]),
+            primary_sale_happened: true,
+            is_mutable: true,
+            edition_nonce: Some(255),
+            token_standard: Some(TokenStandard::ProgrammableNonFungible),
+            collection: Some(Collection {
+                verified: true,
+                key: Pubkey::new_unique(),
+            }),
+            uses: None,
+            collection_details: None,
+            programmable_config: Some(ProgrammableConfig::V1 { rule_set: None }),
+        }
+    }
+
+    #[test]
+    fn test_decode_metadata_view() {
+        let mint = Pubkey::new_unique();
+        let metadata = metadata(mint);
+        let edition = Edition {
+            key: Key::EditionV1,
+            parent: Pubkey::new_unique(),
+            edition: 12,
+        };
+        let account = |data| Account {
+            lamports: 1,
+            data,
+            owner: mpl_token_metadata::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            json!({
+                "context": { "slot": 1 },
+                "value": [
+                    encode_ui_account(
+                        &derive_metadata_pda(&mint),
+                        &account(metadata.try_to_vec().unwrap()),
+                        UiAccountEncoding::Base64,
+                        None,
+                        None,
+                    ),
+                    encode_ui_account(
+                        &derive_edition_pda(&mint),
+                        &account(edition.try_to_vec().unwrap()),
+                        UiAccountEncoding::Base64,
+                        None,
+                        None,
+                    ),
+                ],
+            }),
+        );
+        let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let view = decode_metadata_view(&client, mint).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&view).unwrap(),
+            json!({
+                "mint": mint.to_string(),
+                "update_authority": metadata.update_authority.to_string(),
+                "name": "View #1",
+                "symbol": "VIEW",
+                "uri": "https://example.com/1.json",
+                "seller_fee_basis_points": 500,
+                "creators": [{
+                    "address": metadata.creators.as_ref().unwrap()[0].address.to_string(),
+                    "verified": true,
+                    "share": 100,
+                }],
+                "primary_sale_happened": true,
+                "is_mutable": true,
+                "edition_nonce": 255,
+                "token_standard": "programmable_nonfungible",
+                "collection": {
+                    "mint": metadata.collection.as_ref().unwrap().key.to_string(),
+                    "verified": true,
+                },
+                "uses": null,
+                "collection_size": null,
+                "rule_set": null,
+                "edition": {
+                    "type": "print",
+                    "parent": edition.parent.to_string(),
+                    "number": 12,
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<MetadataView>(serde_json::to_value(&view).unwrap()).unwrap(),
+            view
+        );
+    }
+
+    #[test]
+    fn test_token_record_view() {
+        let delegate = Pubkey::new_unique();
+        let record = TokenRecord {
+            key: Key::TokenRecord,
+            bump: 254,
+            state: TokenState::Locked,
+            rule_set_revision: Some(1),
+            delegate: Some(delegate),
+            delegate_role: Some(TokenDelegateRole::LockedTransfer),
+            locked_transfer: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(TokenRecordView::from(record)).unwrap(),
+            json!({
+                "bump": 254,
+                "state": "locked",
+                "rule_set_revision": 1,
+                "delegate": delegate.to_string(),
+                "delegate_role": "locked_transfer",
+                "locked_transfer": null,
+            })
+        );
+    }
+}
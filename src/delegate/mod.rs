@@ -1,45 +1,74 @@
-use anyhow::Result;
 use borsh::BorshSerialize;
 use mpl_token_metadata::{
-    accounts::{MetadataDelegateRecord, TokenRecord},
+    accounts::{Metadata, MetadataDelegateRecord, TokenRecord},
     hooked::MetadataDelegateRoleSeed,
     types::{DelegateArgs, MetadataDelegateRole, ProgrammableConfig, TokenStandard},
     ID,
 };
-use solana_client::rpc_client::RpcClient;
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     sysvar,
 };
-use solana_sdk::{
-    signature::{Keypair, Signature},
-    signer::Signer,
-};
+use solana_sdk::{signature::Signature, signer::Signer};
 
 use crate::{
     constants::{AUTH_RULES_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID},
     data::Asset,
     decode::ToPubkey,
+    errors::Result,
     nft::get_nft_token_account,
-    transaction::send_and_confirm_tx,
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::{send_and_confirm_tx_with_compute_budget, ComputeBudget},
 };
 
 const DELEGATE_IX: u8 = 44;
 
 pub enum DelegateAssetArgs<'a, P1, P2, P3: ToPubkey> {
     V1 {
-        payer: Option<&'a Keypair>,
-        authority: &'a Keypair,
+        payer: Option<&'a dyn Signer>,
+        authority: &'a dyn Signer,
         mint: P1,
         token: Option<P2>,
         delegate: P3,
         delegate_args: DelegateArgs,
+        compute_budget: ComputeBudget,
     },
 }
 
+impl<'a, P1, P2, P3> DelegateAssetArgs<'a, P1, P2, P3>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<DelegateAssetArgs<'a, Pubkey, Pubkey, Pubkey>> {
+        let DelegateAssetArgs::V1 {
+            payer,
+            authority,
+            mint,
+            token,
+            delegate,
+            delegate_args,
+            compute_budget,
+        } = self;
+
+        Ok(DelegateAssetArgs::V1 {
+            payer,
+            authority,
+            mint: mint.to_pubkey()?,
+            token: token.map(|t| t.to_pubkey()).transpose()?,
+            delegate: delegate.to_pubkey()?,
+            delegate_args,
+            compute_budget,
+        })
+    }
+}
+
 pub fn delegate_asset<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: DelegateAssetArgs<P1, P2, P3>,
 ) -> Result<Signature>
 where
@@ -53,7 +82,7 @@ where
 }
 
 pub fn delegate_asset_ix<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: DelegateAssetArgs<P1, P2, P3>,
 ) -> Result<Instruction>
 where
@@ -66,8 +95,22 @@ where
     }
 }
 
+pub fn delegate_asset_simulate<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: DelegateAssetArgs<P1, P2, P3>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    match args {
+        DelegateAssetArgs::V1 { .. } => delegate_asset_v1_simulate(client, args),
+    }
+}
+
 fn delegate_asset_v1<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: DelegateAssetArgs<P1, P2, P3>,
 ) -> Result<Signature>
 where
@@ -76,18 +119,47 @@ where
     P3: ToPubkey,
 {
     let DelegateAssetArgs::V1 {
-        payer, authority, ..
+        payer,
+        authority,
+        ref compute_budget,
+        ..
     } = args;
 
     let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
 
     let delegate_ix = delegate_asset_v1_ix(client, args)?;
 
-    send_and_confirm_tx(client, &[payer, authority], &[delegate_ix])
+    send_and_confirm_tx_with_compute_budget(
+        client,
+        &[payer, authority],
+        &[delegate_ix],
+        &compute_budget,
+    )
+}
+
+fn delegate_asset_v1_simulate<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: DelegateAssetArgs<P1, P2, P3>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let DelegateAssetArgs::V1 {
+        payer, authority, ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+
+    let ix = delegate_asset_v1_ix(client, args)?;
+
+    simulate_tx(client, &[payer, authority], &[ix])
 }
 
 fn delegate_asset_v1_ix<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: DelegateAssetArgs<P1, P2, P3>,
 ) -> Result<Instruction>
 where
@@ -95,30 +167,39 @@ where
     P2: ToPubkey,
     P3: ToPubkey,
 {
+    let args = args.resolve()?;
+    let DelegateAssetArgs::V1 { mint, token, .. } = args;
+
+    let token = match token {
+        Some(token) => token,
+        None => get_nft_token_account(client, &mint.to_string())?,
+    };
+
+    let md = Asset::new(mint).get_metadata(client)?;
+
+    Ok(build_delegate_asset_v1_ix(args, token, md))
+}
+
+/// Build the delegate instruction from resolved arguments, the asset's token account and
+/// its metadata.
+pub(crate) fn build_delegate_asset_v1_ix(
+    args: DelegateAssetArgs<Pubkey, Pubkey, Pubkey>,
+    token: Pubkey,
+    md: Metadata,
+) -> Instruction {
     let DelegateAssetArgs::V1 {
         payer,
         authority,
         mint,
-        token,
         delegate,
         delegate_args,
+        ..
     } = args;
 
     let payer = payer.unwrap_or(authority);
 
-    let mint = mint.to_pubkey()?;
     let mut asset = Asset::new(mint);
 
-    let token = if let Some(t) = token {
-        t.to_pubkey()?
-    } else {
-        get_nft_token_account(client, &mint.to_string())?
-    };
-
-    let md = asset.get_metadata(client)?;
-
-    let delegate = delegate.to_pubkey()?;
-
     let (auth_rules, auth_rules_program) =
         if let Some(ProgrammableConfig::V1 { rule_set: rules }) = md.programmable_config {
             (rules, Some(AUTH_RULES_PROGRAM_ID))
@@ -234,9 +315,7 @@ where
         delegate_accounts.master_edition = asset.edition;
     }
 
-    let delegate_ix = delegate_ix(delegate_accounts, delegate_args);
-
-    Ok(delegate_ix)
+    delegate_ix(delegate_accounts, delegate_args)
 }
 
 fn delegate_ix(accounts: DelegateAccounts, args: DelegateArgs) -> Instruction {
@@ -39,7 +39,7 @@ pub fn derive_edition_pda(pubkey: &Pubkey) -> Pubkey {
 pub fn derive_edition_marker_pda(pubkey: &Pubkey, edition_num: u64) -> Pubkey {
     let metaplex_pubkey = ID;
 
-    let num: String = (edition_num / 248).to_string();
+    let num: String = (edition_num / EDITION_MARKER_BIT_SIZE).to_string();
 
     let seeds = &[
         METADATA_PREFIX.as_bytes(),
@@ -53,6 +53,23 @@ pub fn derive_edition_marker_pda(pubkey: &Pubkey, edition_num: u64) -> Pubkey {
     pda
 }
 
+/// The single edition marker used by programmable master editions, which tracks every
+/// edition number in one growing ledger.
+pub fn derive_edition_marker_v2_pda(pubkey: &Pubkey) -> Pubkey {
+    let metaplex_pubkey = ID;
+
+    let seeds = &[
+        METADATA_PREFIX.as_bytes(),
+        metaplex_pubkey.as_ref(),
+        pubkey.as_ref(),
+        EDITION_PREFIX.as_bytes(),
+        EDITION_MARKER_V2_SEED.as_bytes(),
+    ];
+
+    let (pda, _) = Pubkey::find_program_address(seeds, &metaplex_pubkey);
+    pda
+}
+
 pub fn derive_cmv2_pda(pubkey: &Pubkey) -> Pubkey {
     let cmv2_pubkey = Pubkey::from_str("cndy3Z4yapfJBmL3ShUp5exZKqR3z33thTzeNMm2gRZ")
         .expect("Failed to parse pubkey from candy machine program id!");
@@ -63,6 +80,48 @@ pub fn derive_cmv2_pda(pubkey: &Pubkey) -> Pubkey {
     pda
 }
 
+/// The Candy Machine v3 authority PDA, which is the mint authority's delegate and the
+/// first, verified creator of every NFT the machine mints.
+pub fn derive_cmv3_authority_pda(candy_machine: &Pubkey) -> Pubkey {
+    let (pda, _) = Pubkey::find_program_address(
+        &[CANDY_MACHINE_SEED.as_bytes(), candy_machine.as_ref()],
+        &CANDY_MACHINE_V3_PROGRAM_ID,
+    );
+
+    pda
+}
+
+/// The Candy Guard account created from `base`.
+pub fn derive_candy_guard_pda(base: &Pubkey) -> Pubkey {
+    let (pda, _) = Pubkey::find_program_address(
+        &[CANDY_GUARD_SEED.as_bytes(), base.as_ref()],
+        &CANDY_GUARD_PROGRAM_ID,
+    );
+
+    pda
+}
+
+/// The auth-rules RuleSet named `name` and owned by `owner`.
+pub fn derive_rule_set_pda(owner: &Pubkey, name: &str) -> Pubkey {
+    let (pda, _) = Pubkey::find_program_address(
+        &[RULE_SET_SEED.as_bytes(), owner.as_ref(), name.as_bytes()],
+        &AUTH_RULES_PROGRAM_ID,
+    );
+
+    pda
+}
+
+/// The buffer `owner` writes serialized RuleSets to when they're too large for one
+/// transaction.
+pub fn derive_rule_set_buffer_pda(owner: &Pubkey) -> Pubkey {
+    let (pda, _) = Pubkey::find_program_address(
+        &[RULE_SET_SEED.as_bytes(), owner.as_ref()],
+        &AUTH_RULES_PROGRAM_ID,
+    );
+
+    pda
+}
+
 pub fn derive_token_record_pda(mint: &Pubkey, token: &Pubkey) -> Pubkey {
     let (pda, _bump) = Pubkey::find_program_address(
         &[
@@ -167,6 +226,40 @@ mod tests {
         assert_eq!(derive_cmv2_pda(&candy_machine_pubkey), expected_pda);
     }
 
+    #[test]
+    fn test_derive_cmv3_authority_pda() {
+        let candy_machine_pubkey =
+            Pubkey::from_str("3qt9aBBmTSMxyzFEcwzZnFeV4tCZzPkTYVqPP7Bw5zUh").unwrap();
+        let expected_pda =
+            Pubkey::from_str("4Q9K16D3J9uNLChALdkhv7btXGwhsntc3Bpa1zT87VZM").unwrap();
+        assert_eq!(
+            derive_cmv3_authority_pda(&candy_machine_pubkey),
+            expected_pda
+        );
+    }
+
+    #[test]
+    fn test_derive_candy_guard_pda() {
+        let base = Pubkey::from_str("3qt9aBBmTSMxyzFEcwzZnFeV4tCZzPkTYVqPP7Bw5zUh").unwrap();
+        let expected_pda =
+            Pubkey::from_str("GCrofaDc4dVvEYw58Vdi3d2FKhwJnCRce2tqSF6WUxR4").unwrap();
+        assert_eq!(derive_candy_guard_pda(&base), expected_pda);
+    }
+
+    #[test]
+    fn test_derive_rule_set_pdas() {
+        let owner = Pubkey::from_str("3qt9aBBmTSMxyzFEcwzZnFeV4tCZzPkTYVqPP7Bw5zUh").unwrap();
+
+        assert_eq!(
+            derive_rule_set_pda(&owner, "royalties"),
+            Pubkey::from_str("FfQhdneSU5te8rzmstsTtfWPB7gNcdoWz2Lj35facXJu").unwrap()
+        );
+        assert_eq!(
+            derive_rule_set_buffer_pda(&owner),
+            Pubkey::from_str("GBiSCcHZZBSLXNAa7bvWwKFWZqkpNoQRNTxDp7yzcHfQ").unwrap()
+        );
+    }
+
     #[test]
     fn test_derive_edition_marker_pda() {
         let mint = Pubkey::from_str("H9UJFx7HknQ9GUz7RBqqV9SRnht6XaVDh2cZS3Huogpf").unwrap();
@@ -181,6 +274,16 @@ mod tests {
         assert_ne!(pda_248, Pubkey::default());
     }
 
+    #[test]
+    fn test_derive_edition_marker_v2_pda() {
+        let mint = Pubkey::from_str("H9UJFx7HknQ9GUz7RBqqV9SRnht6XaVDh2cZS3Huogpf").unwrap();
+
+        assert_eq!(
+            derive_edition_marker_v2_pda(&mint),
+            mpl_token_metadata::accounts::EditionMarkerV2::find_pda(&mint).0
+        );
+    }
+
     #[test]
     fn test_derive_token_record_pda() {
         let mint = Pubkey::from_str("H9UJFx7HknQ9GUz7RBqqV9SRnht6XaVDh2cZS3Huogpf").unwrap();
@@ -0,0 +1,286 @@
+use std::{str::FromStr, sync::Arc};
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonce_utils,
+    rpc_request::RpcError,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_sdk::{
+    message::CompileError, sanitize::SanitizeError, signature::Signature, signer::SignerError,
+    transaction::TransactionError,
+};
+use thiserror::Error;
+
+use crate::{
+    decode::errors::DecodeError, program_errors::ProgramFailure, snapshot::errors::SnapshotError,
+};
+
+pub type Result<T, E = MetabossError> = std::result::Result<T, E>;
+
+/// Error returned by the library's public operations.
+#[derive(Error, Debug)]
+pub enum MetabossError {
+    /// The RPC request failed or the node returned an error. Shared, so that a request
+    /// covering several items can fail each of them with the same error.
+    #[error("RPC request failed: {0}")]
+    Rpc(#[source] Arc<ClientError>),
+
+    #[error("account {0} not found")]
+    MissingAccount(Pubkey),
+
+    #[error("failed to deserialize {}: {reason}", account_label(.account))]
+    Deserialize {
+        account: Option<Pubkey>,
+        reason: String,
+    },
+
+    /// Invalid input, or on-chain state that doesn't allow the requested operation.
+    #[error("{0}")]
+    Validation(String),
+
+    /// An instruction of the transaction failed.
+    #[error(transparent)]
+    Program(Box<ProgramFailure>),
+
+    /// The transaction failed for a reason not tied to a single instruction.
+    #[error("transaction failed: {0}")]
+    Transaction(#[from] TransactionError),
+
+    #[error("missing signature from {0}")]
+    SignerMissing(Pubkey),
+
+    #[error("signing failed: {0}")]
+    Signer(#[from] SignerError),
+
+    /// The transaction landed, but failed for a reason not tied to a single instruction.
+    #[error("transaction {signature} failed: {error}")]
+    FailedOnChain {
+        signature: Signature,
+        error: TransactionError,
+    },
+
+    #[error("transaction {0} expired before landing")]
+    Expired(Signature),
+
+    /// The transaction was sent, but whether it landed couldn't be determined.
+    #[error("status of transaction {signature} is unknown: {reason}")]
+    UnknownStatus {
+        signature: Signature,
+        reason: String,
+    },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+fn account_label(account: &Option<Pubkey>) -> String {
+    match account {
+        Some(pubkey) => format!("account {pubkey}"),
+        None => "account data".to_string(),
+    }
+}
+
+impl MetabossError {
+    pub fn validation<S: Into<String>>(message: S) -> Self {
+        Self::Validation(message.into())
+    }
+
+    pub fn deserialize<E: ToString>(account: Option<Pubkey>, err: E) -> Self {
+        Self::Deserialize {
+            account,
+            reason: err.to_string(),
+        }
+    }
+
+    /// Attribute a deserialization failure to the account it was read from.
+    pub(crate) fn for_account(self, account: &Pubkey) -> Self {
+        match self {
+            Self::Deserialize {
+                account: None,
+                reason,
+            } => Self::Deserialize {
+                account: Some(*account),
+                reason,
+            },
+            err => err,
+        }
+    }
+}
+
+impl From<ClientError> for MetabossError {
+    fn from(err: ClientError) -> Self {
+        match missing_account(&err.kind) {
+            Some(pubkey) => Self::MissingAccount(pubkey),
+            None => Self::Rpc(Arc::new(err)),
+        }
+    }
+}
+
+impl From<ClientErrorKind> for MetabossError {
+    fn from(kind: ClientErrorKind) -> Self {
+        ClientError::from(kind).into()
+    }
+}
+
+// The RPC client reports a missing account as a user-facing message containing the pubkey.
+fn missing_account(kind: &ClientErrorKind) -> Option<Pubkey> {
+    let ClientErrorKind::RpcError(RpcError::ForUser(message)) = kind else {
+        return None;
+    };
+
+    let rest = message.strip_prefix("AccountNotFound: pubkey=")?;
+    let pubkey = rest.split(|c: char| !c.is_alphanumeric()).next()?;
+
+    Pubkey::from_str(pubkey).ok()
+}
+
+impl From<ProgramFailure> for MetabossError {
+    fn from(failure: ProgramFailure) -> Self {
+        Self::Program(Box::new(failure))
+    }
+}
+
+impl From<DecodeError> for MetabossError {
+    fn from(err: DecodeError) -> Self {
+        match err {
+            DecodeError::ClientError(kind) => (*kind).into(),
+            DecodeError::MissingAccount(account) => match Pubkey::from_str(&account) {
+                Ok(pubkey) => Self::MissingAccount(pubkey),
+                Err(_) => Self::Validation(format!("account {account} not found")),
+            },
+            DecodeError::PubkeyParseFailed(value) => {
+                Self::Validation(format!("failed to parse {value} into a Pubkey"))
+            }
+            DecodeError::GeneralError(message) => Self::Validation(message),
+            err @ DecodeError::RuleSetRevisionNotAvailable => Self::Validation(err.to_string()),
+            DecodeError::DecodeMetadataFailed(reason)
+            | DecodeError::DecodeDataFailed(reason)
+            | DecodeError::DeserializationFailed(reason) => Self::Deserialize {
+                account: None,
+                reason,
+            },
+            err @ DecodeError::NumericalOverflow => Self::deserialize(None, err),
+        }
+    }
+}
+
+impl From<SnapshotError> for MetabossError {
+    fn from(err: SnapshotError) -> Self {
+        match err {
+            SnapshotError::ClientError(kind) => (*kind).into(),
+            SnapshotError::MissingAccount(account) => match Pubkey::from_str(&account) {
+                Ok(pubkey) => Self::MissingAccount(pubkey),
+                Err(_) => Self::Validation(format!("account {account} not found")),
+            },
+            SnapshotError::PubkeyParseFailed(value) => {
+                Self::Validation(format!("failed to parse {value} into a Pubkey"))
+            }
+        }
+    }
+}
+
+impl From<nonce_utils::Error> for MetabossError {
+    fn from(err: nonce_utils::Error) -> Self {
+        match err {
+            nonce_utils::Error::InvalidAccountOwner
+            | nonce_utils::Error::InvalidAccountData
+            | nonce_utils::Error::UnexpectedDataSize => Self::deserialize(None, err),
+            nonce_utils::Error::Client(message) => {
+                match missing_account(&ClientErrorKind::RpcError(RpcError::ForUser(
+                    message.clone(),
+                ))) {
+                    Some(pubkey) => Self::MissingAccount(pubkey),
+                    None => Self::Rpc(Arc::new(ClientErrorKind::Custom(message).into())),
+                }
+            }
+            err => Self::Validation(err.to_string()),
+        }
+    }
+}
+
+impl From<ProgramError> for MetabossError {
+    fn from(err: ProgramError) -> Self {
+        Self::Validation(err.to_string())
+    }
+}
+
+impl From<CompileError> for MetabossError {
+    fn from(err: CompileError) -> Self {
+        Self::Validation(format!("failed to compile transaction message: {err}"))
+    }
+}
+
+impl From<SanitizeError> for MetabossError {
+    fn from(err: SanitizeError) -> Self {
+        Self::deserialize(None, err)
+    }
+}
+
+impl From<bincode::Error> for MetabossError {
+    fn from(err: bincode::Error) -> Self {
+        Self::deserialize(None, err)
+    }
+}
+
+impl From<serde_json::Error> for MetabossError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::deserialize(None, err)
+    }
+}
+
+impl From<std::num::ParseIntError> for MetabossError {
+    fn from(err: std::num::ParseIntError) -> Self {
+        Self::Validation(err.to_string())
+    }
+}
+
+impl From<std::str::ParseBoolError> for MetabossError {
+    fn from(err: std::str::ParseBoolError) -> Self {
+        Self::Validation(err.to_string())
+    }
+}
+
+impl From<solana_program::pubkey::ParsePubkeyError> for MetabossError {
+    fn from(err: solana_program::pubkey::ParsePubkeyError) -> Self {
+        Self::Validation(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_error_keeps_missing_pubkey() {
+        let pubkey = Pubkey::new_unique();
+        let err: ClientError =
+            RpcError::ForUser(format!("AccountNotFound: pubkey={pubkey}: not found")).into();
+
+        assert!(matches!(
+            MetabossError::from(err),
+            MetabossError::MissingAccount(p) if p == pubkey
+        ));
+
+        let other: ClientError = RpcError::ForUser("node is behind".to_string()).into();
+        assert!(matches!(MetabossError::from(other), MetabossError::Rpc(_)));
+    }
+
+    #[test]
+    fn test_decode_error_categories() {
+        let pubkey = Pubkey::new_unique();
+
+        assert!(matches!(
+            MetabossError::from(DecodeError::MissingAccount(pubkey.to_string())),
+            MetabossError::MissingAccount(p) if p == pubkey
+        ));
+        assert!(matches!(
+            MetabossError::from(DecodeError::DeserializationFailed("bad".to_string())),
+            MetabossError::Deserialize { account: None, .. }
+        ));
+        assert!(matches!(
+            MetabossError::from(DecodeError::PubkeyParseFailed("xyz".to_string())),
+            MetabossError::Validation(_)
+        ));
+    }
+}
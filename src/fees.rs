@@ -0,0 +1,176 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::data::{FeeMicroLamports, Priority};
+use crate::errors::Result;
+use crate::rpc::Rpc;
+
+/// Default ceiling on the total priority fee paid by a single transaction.
+pub const DEFAULT_MAX_PRIORITY_FEE_LAMPORTS: u64 = 1_000_000;
+
+/// `getRecentPrioritizationFees` accepts at most this many accounts.
+pub const MAX_FEE_ACCOUNTS: usize = 128;
+
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Compute unit price for `priority`, capped so that `compute_units` at that price costs
+/// no more than `max_fee_lamports`.
+///
+/// Percentile priorities are estimated from the fees recently paid to write the
+/// accounts the instructions lock as writable.
+pub fn get_priority_fee(
+    client: &(impl Rpc + ?Sized),
+    priority: &Priority,
+    ixs: &[Instruction],
+    compute_units: u64,
+    max_fee_lamports: u64,
+) -> Result<FeeMicroLamports> {
+    let micro_lamports = match priority {
+        Priority::Percentile(percentile) => {
+            get_recent_fee_percentile(client, &writable_accounts(ixs), *percentile)?
+        }
+        _ => fixed_priority_fee(priority).unwrap_or_default(),
+    };
+
+    Ok(cap_priority_fee(
+        micro_lamports,
+        compute_units,
+        max_fee_lamports,
+    ))
+}
+
+/// Compute unit price in micro-lamports for priorities that don't need an estimate.
+pub fn fixed_priority_fee(priority: &Priority) -> Option<FeeMicroLamports> {
+    match priority {
+        Priority::None => Some(20),        // 1       lamports
+        Priority::Low => Some(20_000),     // 1_000   lamports  ~$1 for 10k updates
+        Priority::Medium => Some(200_000), // 10_000  lamports  ~$10 for 10k updates
+        Priority::High => Some(1_000_000), // 50_000  lamports  ~$0.01/update @ $150 SOL
+        Priority::Max => Some(2_000_000),  // 100_000 lamports  ~$0.02/update @ $150 SOL
+        Priority::Custom(micro_lamports) => Some(*micro_lamports),
+        Priority::Percentile(_) => None,
+    }
+}
+
+/// The given percentile of the prioritization fees paid in recent slots by transactions
+/// that write-locked any of `accounts`.
+pub fn get_recent_fee_percentile(
+    client: &(impl Rpc + ?Sized),
+    accounts: &[Pubkey],
+    percentile: u8,
+) -> Result<FeeMicroLamports> {
+    let accounts = &accounts[..accounts.len().min(MAX_FEE_ACCOUNTS)];
+
+    let mut fees: Vec<u64> = client
+        .get_recent_prioritization_fees(accounts)?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+
+    Ok(fee_percentile(&mut fees, percentile))
+}
+
+/// Accounts written by the instructions, excluding signers such as the fee payer whose
+/// contention doesn't affect landing.
+pub fn writable_accounts(ixs: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = Vec::new();
+
+    for meta in ixs.iter().flat_map(|ix| ix.accounts.iter()) {
+        if meta.is_writable && !meta.is_signer && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+
+    accounts
+}
+
+/// Nearest-rank percentile of `fees`, or zero if there are none.
+pub fn fee_percentile(fees: &mut [u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+
+    fees.sort_unstable();
+
+    let percentile = percentile.min(100) as usize;
+    let rank = (percentile * fees.len()).div_ceil(100);
+
+    fees[rank.saturating_sub(1)]
+}
+
+/// Lower the unit price so `compute_units * micro_lamports` stays within `max_fee_lamports`.
+pub fn cap_priority_fee(
+    micro_lamports: FeeMicroLamports,
+    compute_units: u64,
+    max_fee_lamports: u64,
+) -> FeeMicroLamports {
+    if compute_units == 0 {
+        return micro_lamports;
+    }
+
+    let max_price = max_fee_lamports as u128 * MICRO_LAMPORTS_PER_LAMPORT / compute_units as u128;
+
+    micro_lamports.min(max_price.min(u64::MAX as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::instruction::AccountMeta;
+
+    #[test]
+    fn test_fee_percentile() {
+        let mut fees = vec![50, 10, 40, 20, 30];
+
+        assert_eq!(fee_percentile(&mut fees, 0), 10);
+        assert_eq!(fee_percentile(&mut fees, 50), 30);
+        assert_eq!(fee_percentile(&mut fees, 75), 40);
+        assert_eq!(fee_percentile(&mut fees, 100), 50);
+        assert_eq!(fee_percentile(&mut fees, 255), 50);
+        assert_eq!(fee_percentile(&mut [], 50), 0);
+    }
+
+    #[test]
+    fn test_cap_priority_fee() {
+        // 200k units at 1M micro-lamports is 200k lamports.
+        assert_eq!(cap_priority_fee(1_000_000, 200_000, 1_000_000), 1_000_000);
+        assert_eq!(cap_priority_fee(1_000_000, 200_000, 100_000), 500_000);
+        assert_eq!(cap_priority_fee(1_000_000, 0, 0), 1_000_000);
+    }
+
+    #[test]
+    fn test_writable_accounts() {
+        let payer = Pubkey::new_unique();
+        let metadata = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+
+        let ixs = vec![
+            Instruction::new_with_bytes(
+                program,
+                &[],
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new(metadata, false),
+                    AccountMeta::new_readonly(mint, false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program,
+                &[],
+                vec![
+                    AccountMeta::new(metadata, false),
+                    AccountMeta::new(mint, false),
+                ],
+            ),
+        ];
+
+        assert_eq!(writable_accounts(&ixs), vec![metadata, mint]);
+    }
+
+    #[test]
+    fn test_fixed_priority_fee() {
+        assert_eq!(fixed_priority_fee(&Priority::Medium), Some(200_000));
+        assert_eq!(fixed_priority_fee(&Priority::Custom(7)), Some(7));
+        assert_eq!(fixed_priority_fee(&Priority::Percentile(50)), None);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod bulk;
 pub mod burn;
+pub mod candy_machine;
 pub mod check;
 pub mod constants;
 pub mod convert;
@@ -6,9 +8,21 @@ pub mod data;
 pub mod decode;
 pub mod delegate;
 pub mod derive;
+pub mod errors;
+pub mod fees;
+pub mod lookup_table;
 pub mod mint;
 pub mod nft;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub mod nonce;
+pub mod offline;
+pub mod packer;
+pub mod program_errors;
 pub mod revoke;
+pub mod rpc;
+pub mod rule_set;
+pub mod simulate;
 pub mod snapshot;
 pub mod transaction;
 pub mod transfer;
@@ -0,0 +1,223 @@
+use solana_address_lookup_table_interface::{
+    instruction::{
+        close_lookup_table, create_lookup_table, deactivate_lookup_table, extend_lookup_table,
+        freeze_lookup_table,
+    },
+    state::AddressLookupTable,
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey, sysvar};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, message::AddressLookupTableAccount, signature::Signature,
+    signer::Signer,
+};
+
+use crate::{
+    constants::{AUTH_RULES_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID},
+    decode::ToPubkey,
+    derive::{derive_edition_pda, derive_metadata_pda},
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+    transaction::send_and_confirm_tx,
+};
+
+/// Maximum number of addresses added per extend transaction so it stays under the
+/// transaction size limit.
+pub const MAX_EXTEND_ADDRESSES: usize = 20;
+
+pub struct CreateLookupTableResult {
+    pub signature: Signature,
+    pub lookup_table: Pubkey,
+}
+
+pub fn create_lookup_table_ix(
+    client: &(impl Rpc + ?Sized),
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Result<(Instruction, Pubkey)> {
+    // The table address is derived from a recent slot, which must still be in the
+    // slot hashes sysvar when the transaction lands.
+    let recent_slot = client.get_slot_with_commitment(CommitmentConfig::finalized())?;
+
+    Ok(create_lookup_table(*authority, *payer, recent_slot))
+}
+
+/// Create a new, empty address lookup table owned by `authority`.
+pub fn create_lut(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    payer: Option<&dyn Signer>,
+) -> Result<CreateLookupTableResult> {
+    let payer = payer.unwrap_or(authority);
+
+    let (ix, lookup_table) = create_lookup_table_ix(client, &authority.pubkey(), &payer.pubkey())?;
+
+    let signature = send_and_confirm_tx(client, &[payer, authority], &[ix])?;
+
+    Ok(CreateLookupTableResult {
+        signature,
+        lookup_table,
+    })
+}
+
+/// Append addresses to a lookup table, splitting them across as many transactions
+/// as needed.
+pub fn extend_lut<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    payer: Option<&dyn Signer>,
+    lookup_table: P,
+    addresses: &[Pubkey],
+) -> Result<Vec<Signature>> {
+    let payer = payer.unwrap_or(authority);
+    let lookup_table = lookup_table.to_pubkey()?;
+
+    addresses
+        .chunks(MAX_EXTEND_ADDRESSES)
+        .map(|chunk| {
+            let ix = extend_lookup_table(
+                lookup_table,
+                authority.pubkey(),
+                Some(payer.pubkey()),
+                chunk.to_vec(),
+            );
+            send_and_confirm_tx(client, &[payer, authority], &[ix])
+        })
+        .collect()
+}
+
+/// Freeze a lookup table so it can never be extended, deactivated or closed.
+pub fn freeze_lut<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    lookup_table: P,
+) -> Result<Signature> {
+    let ix = freeze_lookup_table(lookup_table.to_pubkey()?, authority.pubkey());
+
+    send_and_confirm_tx(client, &[authority], &[ix])
+}
+
+/// Deactivate a lookup table. It can be closed once the deactivation slot is no
+/// longer in the slot hashes sysvar.
+pub fn deactivate_lut<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    lookup_table: P,
+) -> Result<Signature> {
+    let ix = deactivate_lookup_table(lookup_table.to_pubkey()?, authority.pubkey());
+
+    send_and_confirm_tx(client, &[authority], &[ix])
+}
+
+/// Close a deactivated lookup table and send its rent to `recipient`.
+pub fn close_lut<P1: ToPubkey, P2: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    lookup_table: P1,
+    recipient: P2,
+) -> Result<Signature> {
+    let ix = close_lookup_table(
+        lookup_table.to_pubkey()?,
+        authority.pubkey(),
+        recipient.to_pubkey()?,
+    );
+
+    send_and_confirm_tx(client, &[authority], &[ix])
+}
+
+/// Fetch a lookup table in the form expected when compiling v0 messages.
+pub fn get_lookup_table_account<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    lookup_table: P,
+) -> Result<AddressLookupTableAccount> {
+    let key = lookup_table.to_pubkey()?;
+    let data = client.get_account_data(&key)?;
+
+    let table = AddressLookupTable::deserialize(&data)
+        .map_err(|e| MetabossError::deserialize(Some(key), e))?;
+
+    Ok(AddressLookupTableAccount {
+        key,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+/// Accounts shared by every Token Metadata instruction that targets items of the given
+/// collection.
+pub fn collection_lookup_table_addresses(collection_mint: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        mpl_token_metadata::ID,
+        sysvar::instructions::ID,
+        AUTH_RULES_PROGRAM_ID,
+        SYSTEM_PROGRAM_ID,
+        SPL_TOKEN_PROGRAM_ID,
+        spl_associated_token_account::ID,
+        *collection_mint,
+        derive_metadata_pda(collection_mint),
+        derive_edition_pda(collection_mint),
+    ]
+}
+
+/// Create a lookup table pre-populated with the accounts from
+/// [`collection_lookup_table_addresses`] and any `extra_addresses`.
+pub fn create_collection_lut<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    payer: Option<&dyn Signer>,
+    collection_mint: P,
+    extra_addresses: &[Pubkey],
+) -> Result<CreateLookupTableResult> {
+    let payer = payer.unwrap_or(authority);
+    let collection_mint = collection_mint.to_pubkey()?;
+
+    let mut addresses = collection_lookup_table_addresses(&collection_mint);
+    for address in extra_addresses {
+        if !addresses.contains(address) {
+            addresses.push(*address);
+        }
+    }
+
+    let (create_ix, lookup_table) =
+        create_lookup_table_ix(client, &authority.pubkey(), &payer.pubkey())?;
+
+    let mut chunks = addresses.chunks(MAX_EXTEND_ADDRESSES);
+
+    // The table can be created and extended in the same transaction.
+    let mut instructions = vec![create_ix];
+    if let Some(first) = chunks.next() {
+        instructions.push(extend_lookup_table(
+            lookup_table,
+            authority.pubkey(),
+            Some(payer.pubkey()),
+            first.to_vec(),
+        ));
+    }
+    let signature = send_and_confirm_tx(client, &[payer, authority], &instructions)?;
+
+    for chunk in chunks {
+        extend_lut(client, authority, Some(payer), lookup_table, chunk)?;
+    }
+
+    Ok(CreateLookupTableResult {
+        signature,
+        lookup_table,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_lookup_table_addresses() {
+        let collection_mint = Pubkey::new_unique();
+
+        let addresses = collection_lookup_table_addresses(&collection_mint);
+
+        assert!(addresses.contains(&mpl_token_metadata::ID));
+        assert!(addresses.contains(&sysvar::instructions::ID));
+        assert!(addresses.contains(&AUTH_RULES_PROGRAM_ID));
+        assert!(addresses.contains(&derive_metadata_pda(&collection_mint)));
+        assert!(addresses.contains(&derive_edition_pda(&collection_mint)));
+        assert!(addresses.len() <= MAX_EXTEND_ADDRESSES);
+    }
+}
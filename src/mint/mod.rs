@@ -1,5 +1,4 @@
 use crate::constants::SYSTEM_PROGRAM_ID;
-use anyhow::{bail, Result};
 use mpl_token_metadata::{
     instructions::{
         CreateMasterEditionV3Builder, CreateMetadataAccountV3Builder, CreateV1Builder,
@@ -11,9 +10,8 @@ use mpl_token_metadata::{
     ID,
 };
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
+use solana_program::instruction::Instruction;
 use solana_sdk::{
-    compute_budget::ComputeBudgetInstruction,
     pubkey::Pubkey,
     signature::Signature,
     signer::{keypair::Keypair, Signer},
@@ -28,10 +26,15 @@ use spl_token::{
 };
 use std::str::FromStr;
 
+use crate::convert::convert_local_to_remote_data;
 use crate::{
-    constants::MINT_LAYOUT_SIZE, data::Priority, decode::ToPubkey, transaction::get_compute_units,
+    constants::MINT_LAYOUT_SIZE,
+    decode::ToPubkey,
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::{send_and_confirm_tx_with_compute_budget, ComputeBudget},
 };
-use crate::{convert::convert_local_to_remote_data, transaction::send_and_confirm_tx};
 use crate::{
     data::{Asset, NftData},
     derive::derive_token_record_pda,
@@ -68,8 +71,8 @@ pub struct AssetData {
 
 pub enum MintAssetArgs<'a, P: ToPubkey> {
     V1 {
-        payer: Option<&'a Keypair>,
-        authority: &'a Keypair,
+        payer: Option<&'a dyn Signer>,
+        authority: &'a dyn Signer,
         receiver: P,
         mint: Option<Keypair>,
         asset_data: AssetData,
@@ -77,7 +80,7 @@ pub enum MintAssetArgs<'a, P: ToPubkey> {
         mint_decimals: Option<u8>,
         amount: u64,
         authorization_data: Option<AuthorizationData>,
-        priority: Priority,
+        compute_budget: ComputeBudget,
     },
 }
 
@@ -86,13 +89,91 @@ pub struct MintResult {
     pub mint: Pubkey,
 }
 
-pub fn mint_asset<P: ToPubkey>(client: &RpcClient, args: MintAssetArgs<P>) -> Result<MintResult> {
+pub struct MintIxResult {
+    pub instructions: Vec<Instruction>,
+    /// The new mint account, which must also sign the transaction.
+    pub mint: Keypair,
+}
+
+pub fn mint_asset<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: MintAssetArgs<P>,
+) -> Result<MintResult> {
     match args {
         MintAssetArgs::V1 { .. } => mint_asset_v1(client, args),
     }
 }
 
-fn mint_asset_v1<P: ToPubkey>(client: &RpcClient, args: MintAssetArgs<P>) -> Result<MintResult> {
+/// Minting reads no on-chain state; `client` is taken to mirror the other `*_ix` functions.
+pub fn mint_asset_ix<P: ToPubkey>(
+    _client: &(impl Rpc + ?Sized),
+    args: MintAssetArgs<P>,
+) -> Result<MintIxResult> {
+    match args {
+        MintAssetArgs::V1 { .. } => mint_asset_v1_ix(args),
+    }
+}
+
+pub fn mint_asset_simulate<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: MintAssetArgs<P>,
+) -> Result<SimulationResult> {
+    match args {
+        MintAssetArgs::V1 { .. } => mint_asset_v1_simulate(client, args),
+    }
+}
+
+fn mint_asset_v1<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: MintAssetArgs<P>,
+) -> Result<MintResult> {
+    let MintAssetArgs::V1 {
+        payer,
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
+
+    let MintIxResult {
+        instructions,
+        mint: mint_signer,
+    } = mint_asset_v1_ix(args)?;
+
+    let signers: Vec<&dyn Signer> = vec![payer, authority, &mint_signer];
+
+    let sig =
+        send_and_confirm_tx_with_compute_budget(client, &signers, &instructions, &compute_budget)?;
+
+    Ok(MintResult {
+        signature: sig,
+        mint: mint_signer.pubkey(),
+    })
+}
+
+fn mint_asset_v1_simulate<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: MintAssetArgs<P>,
+) -> Result<SimulationResult> {
+    let MintAssetArgs::V1 {
+        payer, authority, ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+
+    let MintIxResult {
+        instructions,
+        mint: mint_signer,
+    } = mint_asset_v1_ix(args)?;
+
+    simulate_tx(client, &[payer, authority, &mint_signer], &instructions)
+}
+
+/// Build the create and mint instructions. Minting needs no on-chain state, so this is
+/// shared as-is by the blocking and async clients.
+pub(crate) fn mint_asset_v1_ix<P: ToPubkey>(args: MintAssetArgs<P>) -> Result<MintIxResult> {
     let MintAssetArgs::V1 {
         payer,
         authority,
@@ -103,7 +184,7 @@ fn mint_asset_v1<P: ToPubkey>(client: &RpcClient, args: MintAssetArgs<P>) -> Res
         mint_decimals,
         amount,
         authorization_data,
-        priority,
+        ..
     } = args;
 
     let mint_signer = if let Some(mint) = mint {
@@ -121,7 +202,9 @@ fn mint_asset_v1<P: ToPubkey>(client: &RpcClient, args: MintAssetArgs<P>) -> Res
 
     if let Some(decimals) = mint_decimals {
         if decimals > 9 {
-            bail!("Decimals must be less than or equal to 9");
+            return Err(MetabossError::validation(
+                "Decimals must be less than or equal to 9",
+            ));
         }
     }
 
@@ -189,7 +272,9 @@ fn mint_asset_v1<P: ToPubkey>(client: &RpcClient, args: MintAssetArgs<P>) -> Res
         TokenStandard::NonFungible | TokenStandard::ProgrammableNonFungible
     ) {
         if amount != 1 {
-            bail!("Non-fungible assets must have an amount of 1");
+            return Err(MetabossError::validation(
+                "Non-fungible assets must have an amount of 1",
+            ));
         }
         asset.add_edition();
         create_builder.master_edition(asset.edition);
@@ -209,38 +294,40 @@ fn mint_asset_v1<P: ToPubkey>(client: &RpcClient, args: MintAssetArgs<P>) -> Res
 
     let instructions = vec![create_ix, mint_ix];
 
-    let micro_lamports = match priority {
-        Priority::None => 20,        // 1       lamports
-        Priority::Low => 20_000,     // 1_000   lamports  ~$1 for 10k updates
-        Priority::Medium => 200_000, // 10_000  lamports  ~$10 for 10k updates
-        Priority::High => 1_000_000, // 50_000  lamports  ~$0.01/update @ $150 SOL
-        Priority::Max => 2_000_000,  // 100_000 lamports  ~$0.02/update @ $150 SOL
-    };
-
-    let signers = vec![payer, authority, &mint_signer];
-
-    let units = get_compute_units(client, &instructions, &signers)?.unwrap_or(200_000);
-    let mut final_instructions = vec![
-        ComputeBudgetInstruction::set_compute_unit_limit(units as u32),
-        ComputeBudgetInstruction::set_compute_unit_price(micro_lamports),
-    ];
-    final_instructions.extend(instructions);
-
-    let sig = send_and_confirm_tx(client, &signers, &final_instructions)?;
-
-    Ok(MintResult {
-        signature: sig,
-        mint: asset.mint,
+    Ok(MintIxResult {
+        instructions,
+        mint: mint_signer,
     })
 }
 
 pub fn mint(
-    client: &RpcClient,
-    funder: Keypair,
+    client: &(impl Rpc + ?Sized),
+    funder: &dyn Signer,
+    receiver: Pubkey,
+    nft_data: NftData,
+    immutable: bool,
+    primary_sale_happened: bool,
+) -> Result<(Signature, Pubkey)> {
+    mint_with_compute_budget(
+        client,
+        funder,
+        receiver,
+        nft_data,
+        immutable,
+        primary_sale_happened,
+        &ComputeBudget::default(),
+    )
+}
+
+/// Like [`mint`] but with a caller-chosen compute unit limit and priority fee.
+pub fn mint_with_compute_budget(
+    client: &(impl Rpc + ?Sized),
+    funder: &dyn Signer,
     receiver: Pubkey,
     nft_data: NftData,
     immutable: bool,
     primary_sale_happened: bool,
+    compute_budget: &ComputeBudget,
 ) -> Result<(Signature, Pubkey)> {
     let metaplex_program_id = ID;
     let mint = Keypair::new();
@@ -350,7 +437,12 @@ pub fn mint(
         instructions.push(ix);
     }
 
-    let sig = send_and_confirm_tx(client, &[&funder, &mint], &instructions)?;
+    let sig = send_and_confirm_tx_with_compute_budget(
+        client,
+        &[funder, &mint],
+        &instructions,
+        compute_budget,
+    )?;
 
     Ok((sig, mint.pubkey()))
 }
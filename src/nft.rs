@@ -1,52 +1,52 @@
 use std::str::FromStr;
 
-use anyhow::{bail, Result};
-use serde::Deserialize;
-use serde_json::json;
-use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+use serde_json::{json, Value};
+use solana_client::{rpc_request::RpcRequest, rpc_response::RpcTokenAccountBalance};
 use solana_program::pubkey::Pubkey;
 
-pub fn get_nft_token_account(client: &RpcClient, mint: &str) -> Result<Pubkey> {
+use crate::errors::{MetabossError, Result};
+use crate::rpc::Rpc;
+
+pub fn get_nft_token_account(client: &(impl Rpc + ?Sized), mint: &str) -> Result<Pubkey> {
+    let largest_accounts = client.get_token_largest_accounts(&Pubkey::from_str(mint)?)?;
+
+    single_nft_token_account(mint, largest_accounts)
+}
+
+pub(crate) fn nft_token_account_request(mint: &str) -> (RpcRequest, Value) {
     let request = RpcRequest::Custom {
         method: "getTokenLargestAccounts",
     };
     let params = json!([mint.to_string(), { "commitment": "confirmed" }]);
-    let result: JRpcResponse = client.send(request, params)?;
 
-    let token_accounts: Vec<TokenAccount> = result
-        .value
+    (request, params)
+}
+
+/// Pick the only account holding exactly one token out of the largest accounts of `mint`.
+pub(crate) fn single_nft_token_account(
+    mint: &str,
+    largest_accounts: Vec<RpcTokenAccountBalance>,
+) -> Result<Pubkey> {
+    let token_accounts: Vec<RpcTokenAccountBalance> = largest_accounts
         .into_iter()
-        .filter(|account| account.amount.parse::<u64>().unwrap() == 1)
+        .filter(|account| account.amount.amount.parse::<u64>().unwrap() == 1)
         .collect();
 
     if token_accounts.len() > 1 {
-        bail!(
+        return Err(MetabossError::Validation(format!(
             "Mint account {} had more than one token account with 1 token",
             mint
-        );
+        )));
     }
 
     if token_accounts.is_empty() {
-        bail!("Mint account {} had zero token accounts with 1 token", mint);
+        return Err(MetabossError::Validation(format!(
+            "Mint account {} had zero token accounts with 1 token",
+            mint
+        )));
     }
 
     let token_pubkey = Pubkey::from_str(&token_accounts[0].address)?;
 
     Ok(token_pubkey)
 }
-
-#[derive(Debug, Deserialize)]
-pub struct JRpcResponse {
-    value: Vec<TokenAccount>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct TokenAccount {
-    pub address: String,
-    pub amount: String,
-    pub decimals: u8,
-    #[serde(rename = "uiAmount")]
-    pub ui_amount: f32,
-    #[serde(rename = "uiAmountString")]
-    pub ui_amount_string: String,
-}
@@ -0,0 +1,48 @@
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signature;
+
+use super::{
+    decode::decode_metadata, rpc::Rpc, transaction::send_and_confirm_tx_with_compute_budget,
+};
+use crate::{
+    burn::{build_burn_asset_v1_ix, BurnAssetArgs},
+    data::Asset,
+    decode::ToPubkey,
+    errors::Result,
+};
+
+pub async fn burn_asset<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<'_, P1, P2>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let BurnAssetArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
+
+    let burn_ix = burn_asset_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[burn_ix], &compute_budget).await
+}
+
+pub async fn burn_asset_ix<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: BurnAssetArgs<'_, P1, P2>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let args = args.resolve()?;
+    let BurnAssetArgs::V1 { mint, .. } = args;
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    Ok(build_burn_asset_v1_ix(args, md))
+}
@@ -0,0 +1,179 @@
+use borsh::de::BorshDeserialize;
+use mpl_token_metadata::accounts::{
+    CollectionAuthorityRecord, Edition, EditionMarker, EditionMarkerV2, MasterEdition, Metadata,
+    MetadataDelegateRecord, TokenRecord, UseAuthorityRecord,
+};
+use solana_program::bpf_loader_upgradeable::UpgradeableLoaderState;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::state::{Account as Token, Mint};
+
+use super::{nft::get_nft_token_account, rpc::Rpc};
+use crate::{
+    decode::{
+        edition_from_data, edition_marker_from_data, edition_marker_v2_from_data,
+        errors::DecodeError, master_edition_from_data, metadata_from_data, mint_from_data,
+        record_from_data, token_from_data, upgradeable_loader_state_from_data, ToPubkey,
+    },
+    derive::*,
+    errors::{MetabossError, Result},
+};
+
+pub async fn decode_metadata(client: &(impl Rpc + ?Sized), pubkey: &Pubkey) -> Result<Metadata> {
+    fetch_and_decode(client, pubkey, metadata_from_data).await
+}
+
+pub async fn decode_master(client: &(impl Rpc + ?Sized), pubkey: &Pubkey) -> Result<MasterEdition> {
+    fetch_and_decode(client, pubkey, master_edition_from_data).await
+}
+
+pub async fn decode_edition(client: &(impl Rpc + ?Sized), pubkey: &Pubkey) -> Result<Edition> {
+    fetch_and_decode(client, pubkey, edition_from_data).await
+}
+
+pub async fn decode_metadata_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<Metadata> {
+    let pubkey = mint_address.to_pubkey()?;
+
+    decode_metadata(client, &derive_metadata_pda(&pubkey)).await
+}
+
+pub async fn decode_master_edition_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<MasterEdition> {
+    let pubkey = mint_address.to_pubkey()?;
+
+    decode_master(client, &derive_edition_pda(&pubkey)).await
+}
+
+pub async fn decode_edition_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<Edition> {
+    let pubkey = mint_address.to_pubkey()?;
+
+    decode_edition(client, &derive_edition_pda(&pubkey)).await
+}
+
+pub async fn decode_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<Mint> {
+    let pubkey = mint_address.to_pubkey()?;
+
+    fetch_and_decode(client, &pubkey, mint_from_data).await
+}
+
+pub async fn decode_token<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    token_address: P,
+) -> Result<Token> {
+    let pubkey = token_address.to_pubkey()?;
+
+    fetch_and_decode(client, &pubkey, token_from_data).await
+}
+
+pub async fn decode_edition_marker_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+    edition_num: u64,
+) -> Result<EditionMarker> {
+    let pubkey = mint_address.to_pubkey()?;
+
+    decode_edition_marker(client, &derive_edition_marker_pda(&pubkey, edition_num)).await
+}
+
+pub async fn decode_edition_marker(
+    client: &(impl Rpc + ?Sized),
+    pubkey: &Pubkey,
+) -> Result<EditionMarker> {
+    fetch_and_decode(client, pubkey, edition_marker_from_data).await
+}
+
+pub async fn decode_edition_marker_v2_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint_address: P,
+) -> Result<EditionMarkerV2> {
+    let pubkey = mint_address.to_pubkey()?;
+
+    decode_edition_marker_v2(client, &derive_edition_marker_v2_pda(&pubkey)).await
+}
+
+pub async fn decode_edition_marker_v2(
+    client: &(impl Rpc + ?Sized),
+    pubkey: &Pubkey,
+) -> Result<EditionMarkerV2> {
+    fetch_and_decode(client, pubkey, edition_marker_v2_from_data).await
+}
+
+pub async fn decode_bpf_loader_upgradeable_state<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    program_address: P,
+) -> Result<UpgradeableLoaderState> {
+    let pubkey = program_address.to_pubkey()?;
+
+    fetch_and_decode(client, &pubkey, upgradeable_loader_state_from_data).await
+}
+
+pub async fn decode_collection_authority_record<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    address: P,
+) -> Result<CollectionAuthorityRecord> {
+    decode_record(client, &address.to_pubkey()?).await
+}
+
+pub async fn decode_use_authority_record<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    address: P,
+) -> Result<UseAuthorityRecord> {
+    decode_record(client, &address.to_pubkey()?).await
+}
+
+pub async fn decode_metadata_delegate<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    address: P,
+) -> Result<MetadataDelegateRecord> {
+    decode_record(client, &address.to_pubkey()?).await
+}
+
+pub async fn decode_token_record<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    address: P,
+) -> Result<TokenRecord> {
+    decode_record(client, &address.to_pubkey()?).await
+}
+
+pub async fn decode_token_record_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    address: P,
+) -> Result<TokenRecord> {
+    let mint_pubkey = address.to_pubkey()?;
+
+    let token_pubkey = get_nft_token_account(client, &mint_pubkey.to_string()).await?;
+
+    decode_record(
+        client,
+        &derive_token_record_pda(&mint_pubkey, &token_pubkey),
+    )
+    .await
+}
+
+async fn decode_record<T: BorshDeserialize>(
+    client: &(impl Rpc + ?Sized),
+    pubkey: &Pubkey,
+) -> Result<T> {
+    fetch_and_decode(client, pubkey, record_from_data).await
+}
+
+/// Fetch `pubkey` and decode its data, attributing a decode failure to the account.
+async fn fetch_and_decode<T>(
+    client: &(impl Rpc + ?Sized),
+    pubkey: &Pubkey,
+    decode: fn(&[u8]) -> Result<T, DecodeError>,
+) -> Result<T> {
+    let data = client.get_account_data(pubkey).await?;
+
+    decode(&data).map_err(|e| MetabossError::from(e).for_account(pubkey))
+}
@@ -0,0 +1,65 @@
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signature;
+
+use super::{
+    decode::decode_metadata, nft::get_nft_token_account, rpc::Rpc,
+    transaction::send_and_confirm_tx_with_compute_budget,
+};
+use crate::{
+    data::Asset,
+    decode::ToPubkey,
+    delegate::{build_delegate_asset_v1_ix, DelegateAssetArgs},
+    errors::Result,
+};
+
+pub async fn delegate_asset<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: DelegateAssetArgs<'_, P1, P2, P3>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let DelegateAssetArgs::V1 {
+        payer,
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
+
+    let delegate_ix = delegate_asset_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(
+        client,
+        &[payer, authority],
+        &[delegate_ix],
+        &compute_budget,
+    )
+    .await
+}
+
+pub async fn delegate_asset_ix<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: DelegateAssetArgs<'_, P1, P2, P3>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let args = args.resolve()?;
+    let DelegateAssetArgs::V1 { mint, token, .. } = args;
+
+    let token = match token {
+        Some(token) => token,
+        None => get_nft_token_account(client, &mint.to_string()).await?,
+    };
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    Ok(build_delegate_asset_v1_ix(args, token, md))
+}
@@ -0,0 +1,52 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use super::rpc::Rpc;
+use crate::{
+    data::{FeeMicroLamports, Priority},
+    errors::Result,
+    fees::{
+        cap_priority_fee, fee_percentile, fixed_priority_fee, writable_accounts, MAX_FEE_ACCOUNTS,
+    },
+};
+
+/// Compute unit price for `priority`, capped so that `compute_units` at that price costs
+/// no more than `max_fee_lamports`.
+pub async fn get_priority_fee(
+    client: &(impl Rpc + ?Sized),
+    priority: &Priority,
+    ixs: &[Instruction],
+    compute_units: u64,
+    max_fee_lamports: u64,
+) -> Result<FeeMicroLamports> {
+    let micro_lamports = match priority {
+        Priority::Percentile(percentile) => {
+            get_recent_fee_percentile(client, &writable_accounts(ixs), *percentile).await?
+        }
+        _ => fixed_priority_fee(priority).unwrap_or_default(),
+    };
+
+    Ok(cap_priority_fee(
+        micro_lamports,
+        compute_units,
+        max_fee_lamports,
+    ))
+}
+
+/// The given percentile of the prioritization fees paid in recent slots by transactions
+/// that write-locked any of `accounts`.
+pub async fn get_recent_fee_percentile(
+    client: &(impl Rpc + ?Sized),
+    accounts: &[Pubkey],
+    percentile: u8,
+) -> Result<FeeMicroLamports> {
+    let accounts = &accounts[..accounts.len().min(MAX_FEE_ACCOUNTS)];
+
+    let mut fees: Vec<u64> = client
+        .get_recent_prioritization_fees(accounts)
+        .await?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+
+    Ok(fee_percentile(&mut fees, percentile))
+}
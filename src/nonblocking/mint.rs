@@ -0,0 +1,48 @@
+use solana_sdk::signer::Signer;
+
+use super::{rpc::Rpc, transaction::send_and_confirm_tx_with_compute_budget};
+use crate::{
+    decode::ToPubkey,
+    errors::Result,
+    mint::{mint_asset_v1_ix, MintAssetArgs, MintIxResult, MintResult},
+};
+
+pub async fn mint_asset<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: MintAssetArgs<'_, P>,
+) -> Result<MintResult> {
+    let MintAssetArgs::V1 {
+        payer,
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
+
+    let MintIxResult {
+        instructions,
+        mint: mint_signer,
+    } = mint_asset_ix(client, args).await?;
+
+    let signers: Vec<&dyn Signer> = vec![payer, authority, &mint_signer];
+
+    let signature =
+        send_and_confirm_tx_with_compute_budget(client, &signers, &instructions, &compute_budget)
+            .await?;
+
+    Ok(MintResult {
+        signature,
+        mint: mint_signer.pubkey(),
+    })
+}
+
+/// Minting reads no on-chain state, so this resolves immediately; it exists to mirror the
+/// blocking API.
+pub async fn mint_asset_ix<P: ToPubkey>(
+    _client: &(impl Rpc + ?Sized),
+    args: MintAssetArgs<'_, P>,
+) -> Result<MintIxResult> {
+    mint_asset_v1_ix(args)
+}
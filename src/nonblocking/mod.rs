@@ -0,0 +1,27 @@
+//! Async counterparts of the blocking API. Functions take any [`rpc::Rpc`], such as the
+//! nonblocking `RpcClient`.
+//!
+//! Modules mirror their blocking namesakes and reuse the same argument types, so
+//! `metaboss_lib::burn::burn_asset` becomes `metaboss_lib::nonblocking::burn::burn_asset`.
+//! Instructions are built by the same code as the blocking API; only the RPC calls
+//! differ.
+//!
+//! The operation argument enums hold `&dyn Signer`, which is not `Sync`, so the futures
+//! returned by the operation functions are not `Send`. Drive them on the current task,
+//! or build the instructions with the `*_ix` functions and send them with signers that
+//! are `Send + Sync`.
+
+pub mod burn;
+pub mod decode;
+pub mod delegate;
+pub mod fees;
+pub mod mint;
+pub mod nft;
+pub mod revoke;
+pub mod rpc;
+pub mod snapshot;
+pub mod transaction;
+pub mod transfer;
+pub mod unverify;
+pub mod update;
+pub mod verify;
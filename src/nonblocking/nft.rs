@@ -0,0 +1,14 @@
+use std::str::FromStr;
+
+use solana_program::pubkey::Pubkey;
+
+use super::rpc::Rpc;
+use crate::{errors::Result, nft::single_nft_token_account};
+
+pub async fn get_nft_token_account(client: &(impl Rpc + ?Sized), mint: &str) -> Result<Pubkey> {
+    let largest_accounts = client
+        .get_token_largest_accounts(&Pubkey::from_str(mint)?)
+        .await?;
+
+    single_nft_token_account(mint, largest_accounts)
+}
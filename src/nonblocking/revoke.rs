@@ -0,0 +1,65 @@
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signature;
+
+use super::{
+    decode::decode_metadata, nft::get_nft_token_account, rpc::Rpc,
+    transaction::send_and_confirm_tx_with_compute_budget,
+};
+use crate::{
+    data::Asset,
+    decode::ToPubkey,
+    errors::Result,
+    revoke::{build_revoke_asset_v1_ix, RevokeAssetArgs},
+};
+
+pub async fn revoke_asset<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: RevokeAssetArgs<'_, P1, P2, P3>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let RevokeAssetArgs::V1 {
+        payer,
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
+
+    let revoke_ix = revoke_asset_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(
+        client,
+        &[payer, authority],
+        &[revoke_ix],
+        &compute_budget,
+    )
+    .await
+}
+
+pub async fn revoke_asset_ix<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: RevokeAssetArgs<'_, P1, P2, P3>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let args = args.resolve()?;
+    let RevokeAssetArgs::V1 { mint, token, .. } = args;
+
+    let token = match token {
+        Some(token) => token,
+        None => get_nft_token_account(client, &mint.to_string()).await?,
+    };
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    Ok(build_revoke_asset_v1_ix(args, token, md))
+}
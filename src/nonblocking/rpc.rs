@@ -0,0 +1,288 @@
+//! Async counterpart of [`crate::rpc::Rpc`].
+//!
+//! Every function in `nonblocking` takes `&(impl Rpc + ?Sized)`, so the nonblocking
+//! `RpcClient`, `&dyn Rpc`, [`RecordingRpc`](crate::rpc::RecordingRpc) and
+//! [`ReplayRpc`](crate::rpc::ReplayRpc) can all be passed.
+
+// The methods mirror `RpcClient`, whose error type is large.
+#![allow(clippy::result_large_err)]
+
+use async_trait::async_trait;
+use solana_client::{
+    client_error::Result as ClientResult,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{
+        RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig,
+    },
+    rpc_response::{
+        Response, RpcPrioritizationFee, RpcResult, RpcSimulateTransactionResult,
+        RpcTokenAccountBalance,
+    },
+};
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{self, VersionedTransaction},
+};
+use solana_transaction_status_client_types::TransactionStatus;
+
+use crate::nft::nft_token_account_request;
+
+/// RPC methods used by the async API. See the blocking [`crate::rpc::Rpc`] for the
+/// conventions implementations follow.
+#[async_trait]
+pub trait Rpc: Send + Sync {
+    /// Commitment used by the methods that don't take one explicitly.
+    fn commitment(&self) -> CommitmentConfig {
+        CommitmentConfig::confirmed()
+    }
+
+    /// Fetch an account, failing if it doesn't exist.
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account>;
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        self.get_account(pubkey).await.map(|account| account.data)
+    }
+
+    /// Fetch several accounts at once, with `None` for the ones that don't exist.
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey])
+        -> ClientResult<Vec<Option<Account>>>;
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>>;
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64>;
+
+    /// Largest token accounts of `mint`, as returned by `getTokenLargestAccounts`.
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>>;
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)>;
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        self.get_latest_blockhash_with_commitment(self.commitment())
+            .await
+            .map(|(blockhash, _)| blockhash)
+    }
+
+    async fn get_block_height_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<u64>;
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot>;
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>>;
+
+    /// Like [`Rpc::get_signature_statuses`] but also searches the ledger history, not just
+    /// the recent status cache.
+    async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>>;
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        let statuses = self
+            .get_signature_statuses(std::slice::from_ref(signature))
+            .await?;
+
+        Ok(statuses
+            .value
+            .into_iter()
+            .next()
+            .flatten()
+            .map(|status| status.status))
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>>;
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult>;
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    async fn send_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.send_transaction_with_config(transaction, RpcSendTransactionConfig::default())
+            .await
+    }
+
+    /// Send a transaction and wait until it reaches the client's commitment.
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature>;
+
+    /// Like [`Rpc::send_and_confirm_transaction`], showing progress where the
+    /// implementation supports it.
+    async fn send_and_confirm_transaction_with_spinner(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.send_and_confirm_transaction(transaction).await
+    }
+}
+
+#[async_trait]
+impl Rpc for RpcClient {
+    fn commitment(&self) -> CommitmentConfig {
+        RpcClient::commitment(self)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        RpcClient::get_account(self, pubkey).await
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        RpcClient::get_account_data(self, pubkey).await
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        RpcClient::get_multiple_accounts(self, pubkeys).await
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        RpcClient::get_program_accounts_with_config(self, program_id, config).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        RpcClient::get_minimum_balance_for_rent_exemption(self, data_len).await
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>> {
+        // Sent as a custom request so the commitment is always confirmed, regardless
+        // of the client's.
+        let (request, params) = nft_token_account_request(&mint.to_string());
+
+        let response: Response<Vec<RpcTokenAccountBalance>> = self.send(request, params).await?;
+
+        Ok(response.value)
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        RpcClient::get_latest_blockhash_with_commitment(self, commitment).await
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        RpcClient::get_latest_blockhash(self).await
+    }
+
+    async fn get_block_height_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        RpcClient::get_block_height_with_commitment(self, commitment).await
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        RpcClient::get_slot_with_commitment(self, commitment).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        RpcClient::get_signature_statuses(self, signatures).await
+    }
+
+    async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        RpcClient::get_signature_statuses_with_history(self, signatures).await
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        RpcClient::get_signature_status(self, signature).await
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        RpcClient::get_recent_prioritization_fees(self, addresses).await
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        RpcClient::simulate_transaction_with_config(self, transaction, config).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_transaction_with_config(self, transaction, config).await
+    }
+
+    async fn send_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_transaction(self, transaction).await
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_and_confirm_transaction(self, transaction).await
+    }
+
+    async fn send_and_confirm_transaction_with_spinner(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_and_confirm_transaction_with_spinner(self, transaction).await
+    }
+}
@@ -0,0 +1,60 @@
+use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
+use solana_client::rpc_config::RpcProgramAccountsConfig;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use super::rpc::Rpc;
+use crate::{
+    errors::Result,
+    snapshot::{
+        edition_accounts_by_master_config, holder_token_accounts_config,
+        metadata_by_creator_config, metadata_by_update_authority_config,
+    },
+};
+
+pub async fn get_metadata_accounts_by_update_authority(
+    client: &(impl Rpc + ?Sized),
+    update_authority: &str,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = metadata_by_update_authority_config(update_authority);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config).await
+}
+
+pub async fn get_metadata_accounts_by_creator(
+    client: &(impl Rpc + ?Sized),
+    creator_id: &str,
+    creator_position: usize,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = metadata_by_creator_config(creator_id, creator_position);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config).await
+}
+
+pub async fn get_holder_token_accounts(
+    client: &(impl Rpc + ?Sized),
+    mint_account: String,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = holder_token_accounts_config(mint_account);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config).await
+}
+
+pub async fn get_edition_accounts_by_master(
+    client: &(impl Rpc + ?Sized),
+    parent_pubkey: &str,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = edition_accounts_by_master_config(parent_pubkey);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config).await
+}
+
+async fn get_program_accounts(
+    client: &(impl Rpc + ?Sized),
+    program_id: &Pubkey,
+    config: RpcProgramAccountsConfig,
+) -> Result<Vec<(Pubkey, Account)>> {
+    client
+        .get_program_accounts_with_config(program_id, config)
+        .await
+        .map_err(Into::into)
+}
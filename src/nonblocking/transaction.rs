@@ -0,0 +1,473 @@
+use solana_client::nonce_utils;
+use solana_program::instruction::Instruction;
+use solana_sdk::{
+    hash::Hash,
+    message::AddressLookupTableAccount,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::Signer,
+    system_instruction::advance_nonce_account,
+    transaction::{Transaction, VersionedTransaction},
+};
+use tokio::time::sleep;
+
+use super::{fees::get_priority_fee, rpc::Rpc};
+use crate::{
+    errors::Result,
+    program_errors::map_client_error,
+    transaction::{
+        compile_versioned_tx, compute_units_simulation_config, prepend_compute_budget,
+        units_from_simulation, BlockhashSource, ComputeBudget, PollAction, RebroadcastConfig,
+        RebroadcastPoll, TxOutcome, REBROADCAST_SEND_CONFIG, RETRY_DELAYS,
+    },
+};
+
+/// Resolve the blockhash for `source`, returning it together with the instructions to
+/// send, which are prefixed with the nonce advance instruction when a durable nonce is used.
+pub async fn resolve_blockhash(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    source: &BlockhashSource,
+) -> Result<(Vec<Instruction>, Hash)> {
+    match source {
+        BlockhashSource::Latest => Ok((ixs.to_vec(), client.get_latest_blockhash().await?)),
+        BlockhashSource::Fixed(hash) => Ok((ixs.to_vec(), *hash)),
+        BlockhashSource::DurableNonce {
+            nonce_account,
+            nonce_authority,
+        } => {
+            let account = client.get_account(nonce_account).await?;
+            let data = nonce_utils::data_from_account(&account)?;
+
+            let mut instructions = Vec::with_capacity(ixs.len() + 1);
+            instructions.push(advance_nonce_account(nonce_account, nonce_authority));
+            instructions.extend_from_slice(ixs);
+
+            Ok((instructions, data.blockhash()))
+        }
+    }
+}
+
+pub async fn send_and_confirm_tx(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+) -> Result<Signature> {
+    send_and_confirm_tx_with_blockhash(client, signers, ixs, &BlockhashSource::Latest).await
+}
+
+/// Like [`send_and_confirm_tx`] but signs against the given [`BlockhashSource`], e.g. a
+/// durable nonce. The nonce authority must be one of the `signers`.
+pub async fn send_and_confirm_tx_with_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    blockhash: &BlockhashSource,
+) -> Result<Signature> {
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash).await?;
+    let tx = legacy_tx(signers, &ixs, blockhash);
+
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| map_client_error(e, &ixs))?;
+
+    Ok(signature)
+}
+
+pub async fn send_and_confirm_tx_with_retries(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+) -> Result<Signature> {
+    send_and_confirm_tx_with_retries_and_blockhash(client, signers, ixs, &BlockhashSource::Latest)
+        .await
+}
+
+pub async fn send_and_confirm_tx_with_retries_and_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    blockhash: &BlockhashSource,
+) -> Result<Signature> {
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash).await?;
+    let tx = legacy_tx(signers, &ixs, blockhash);
+
+    send_with_retries(client, &tx, &ixs).await
+}
+
+/// Send a v0 transaction compiled against the provided address lookup tables.
+///
+/// The first signer is the fee payer.
+pub async fn send_and_confirm_versioned_tx(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Signature> {
+    send_and_confirm_versioned_tx_with_blockhash(
+        client,
+        signers,
+        ixs,
+        lookup_tables,
+        &BlockhashSource::Latest,
+    )
+    .await
+}
+
+pub async fn send_and_confirm_versioned_tx_with_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: &BlockhashSource,
+) -> Result<Signature> {
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash).await?;
+    let tx = compile_versioned_tx(signers, &ixs, lookup_tables, blockhash)?;
+
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| map_client_error(e, &ixs))?;
+
+    Ok(signature)
+}
+
+pub async fn send_and_confirm_versioned_tx_with_retries(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Signature> {
+    send_and_confirm_versioned_tx_with_retries_and_blockhash(
+        client,
+        signers,
+        ixs,
+        lookup_tables,
+        &BlockhashSource::Latest,
+    )
+    .await
+}
+
+pub async fn send_and_confirm_versioned_tx_with_retries_and_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: &BlockhashSource,
+) -> Result<Signature> {
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash).await?;
+    let tx = compile_versioned_tx(signers, &ixs, lookup_tables, blockhash)?;
+
+    send_with_retries(client, &tx, &ixs).await
+}
+
+/// Send `ixs` and keep rebroadcasting the signed transaction until it lands or its
+/// blockhash expires. See the blocking
+/// [`send_and_confirm_tx_with_rebroadcast`](crate::transaction::send_and_confirm_tx_with_rebroadcast).
+pub async fn send_and_confirm_tx_with_rebroadcast(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    config: &RebroadcastConfig,
+) -> Result<TxOutcome> {
+    rebroadcast_until_expiry(client, ixs, config, |blockhash| {
+        Ok(legacy_tx(signers, ixs, blockhash))
+    })
+    .await
+}
+
+/// Versioned counterpart of [`send_and_confirm_tx_with_rebroadcast`].
+pub async fn send_and_confirm_versioned_tx_with_rebroadcast(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    config: &RebroadcastConfig,
+) -> Result<TxOutcome> {
+    rebroadcast_until_expiry(client, ixs, config, |blockhash| {
+        compile_versioned_tx(signers, ixs, lookup_tables, blockhash)
+    })
+    .await
+}
+
+async fn rebroadcast_until_expiry<F>(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    config: &RebroadcastConfig,
+    build_tx: F,
+) -> Result<TxOutcome>
+where
+    F: Fn(Hash) -> Result<VersionedTransaction>,
+{
+    let mut outcome = None;
+    for _ in 0..config.attempts() {
+        let (blockhash, last_valid_block_height) = client
+            .get_latest_blockhash_with_commitment(config.commitment)
+            .await?;
+        let tx = build_tx(blockhash)?;
+
+        let attempt = broadcast_and_poll(client, ixs, config, &tx, last_valid_block_height).await?;
+        if !matches!(attempt, TxOutcome::Expired { .. }) {
+            return Ok(attempt);
+        }
+        outcome = Some(attempt);
+    }
+
+    Ok(outcome.expect("at least one attempt is made"))
+}
+
+async fn broadcast_and_poll(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    config: &RebroadcastConfig,
+    tx: &VersionedTransaction,
+    last_valid_block_height: u64,
+) -> Result<TxOutcome> {
+    let signature = tx.signatures[0];
+
+    // A rejected first send never reached the cluster, so it is a definite failure.
+    client
+        .send_transaction(tx)
+        .await
+        .map_err(|e| map_client_error(e, ixs))?;
+
+    let mut poll = RebroadcastPoll::new(signature, ixs, config, last_valid_block_height);
+    let mut action = PollAction::PollStatus;
+    loop {
+        action = match action {
+            PollAction::PollStatus => {
+                let status = client
+                    .get_signature_statuses(&[signature])
+                    .await
+                    .map(|response| response.value.into_iter().next().flatten())
+                    .map_err(|e| e.to_string());
+                poll.on_status(status)
+            }
+            PollAction::CheckBlockHeight => {
+                let height = client
+                    .get_block_height_with_commitment(config.commitment)
+                    .await
+                    .map_err(|e| e.to_string());
+                poll.on_block_height(height)
+            }
+            PollAction::Wait { rebroadcast } => {
+                if rebroadcast {
+                    // Errors are ignored here; the next status poll decides what happened.
+                    let _ = client
+                        .send_transaction_with_config(tx, REBROADCAST_SEND_CONFIG)
+                        .await;
+                }
+                sleep(config.poll_interval).await;
+                PollAction::PollStatus
+            }
+            PollAction::Finish(outcome) => return Ok(*outcome),
+        };
+    }
+}
+
+pub async fn get_compute_units(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    signers: &[&dyn Signer],
+) -> Result<Option<u64>> {
+    let tx = legacy_tx(
+        signers,
+        ixs,
+        Hash::new_from_array(Pubkey::default().to_bytes()), // dummy value
+    );
+
+    let sim_result = client
+        .simulate_transaction_with_config(&tx, compute_units_simulation_config())
+        .await?;
+
+    units_from_simulation(sim_result.value, ixs)
+}
+
+/// Versioned counterpart of [`get_compute_units`] for transactions using lookup tables.
+pub async fn get_compute_units_versioned(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    signers: &[&dyn Signer],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Option<u64>> {
+    let tx = compile_versioned_tx(
+        signers,
+        ixs,
+        lookup_tables,
+        Hash::new_from_array(Pubkey::default().to_bytes()), // dummy value
+    )?;
+
+    let sim_result = client
+        .simulate_transaction_with_config(&tx, compute_units_simulation_config())
+        .await?;
+
+    units_from_simulation(sim_result.value, ixs)
+}
+
+/// Prefix `ixs` with the compute unit limit and price instructions described by `budget`.
+pub async fn add_compute_budget(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    budget: &ComputeBudget,
+) -> Result<Vec<Instruction>> {
+    let units = match budget.compute_units {
+        Some(units) => units as u64,
+        None => get_compute_units(client, ixs, signers)
+            .await?
+            .unwrap_or(budget.fallback_units()),
+    };
+
+    let micro_lamports = get_priority_fee(
+        client,
+        &budget.priority,
+        ixs,
+        units,
+        budget.max_fee_lamports,
+    )
+    .await?;
+
+    Ok(prepend_compute_budget(units, micro_lamports, ixs))
+}
+
+/// Send `ixs` with compute unit limit and priority fee instructions added. This is the
+/// path every async operation sends through.
+pub async fn send_and_confirm_tx_with_compute_budget(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    budget: &ComputeBudget,
+) -> Result<Signature> {
+    let instructions = add_compute_budget(client, signers, ixs, budget).await?;
+
+    send_and_confirm_tx(client, signers, &instructions).await
+}
+
+fn legacy_tx(
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    blockhash: Hash,
+) -> VersionedTransaction {
+    Transaction::new_signed_with_payer(ixs, Some(&signers[0].pubkey()), signers, blockhash).into()
+}
+
+async fn send_with_retries(
+    client: &(impl Rpc + ?Sized),
+    tx: &VersionedTransaction,
+    ixs: &[Instruction],
+) -> Result<Signature> {
+    let mut delays = RETRY_DELAYS.iter();
+    loop {
+        match client.send_and_confirm_transaction_with_spinner(tx).await {
+            Ok(signature) => return Ok(signature),
+            Err(e) => match delays.next() {
+                Some(delay) => sleep(*delay).await,
+                None => return Err(map_client_error(e, ixs)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_client::{rpc_client::Mocks, rpc_request::RpcRequest};
+    use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction, signature::Keypair, system_instruction::transfer,
+    };
+
+    use crate::data::Priority;
+
+    fn fast_rebroadcast_config() -> RebroadcastConfig {
+        RebroadcastConfig {
+            rebroadcast_interval: Duration::ZERO,
+            poll_interval: Duration::ZERO,
+            ..RebroadcastConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_blockhash_fixed() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let hash = Hash::new_unique();
+        let ix = transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+
+        let (ixs, blockhash) = resolve_blockhash(
+            &client,
+            std::slice::from_ref(&ix),
+            &BlockhashSource::Fixed(hash),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(ixs, vec![ix]);
+        assert_eq!(blockhash, hash);
+    }
+
+    #[tokio::test]
+    async fn test_add_compute_budget_with_fixed_units() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let payer = Keypair::new();
+        let ix = transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        let budget = ComputeBudget {
+            priority: Priority::Custom(10_000_000),
+            compute_units: Some(100_000),
+            max_fee_lamports: 1_000,
+            ..ComputeBudget::default()
+        };
+
+        let ixs = add_compute_budget(&client, &[&payer], std::slice::from_ref(&ix), &budget)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ixs,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(100_000),
+                ComputeBudgetInstruction::set_compute_unit_price(10_000),
+                ix,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_and_confirm_tx() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let payer = Keypair::new();
+        let ix = transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        assert!(send_and_confirm_tx(&client, &[&payer], &[ix]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rebroadcast_outcomes() {
+        let payer = Keypair::new();
+        let ix = transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let config = fast_rebroadcast_config();
+
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let outcome = send_and_confirm_tx_with_rebroadcast(
+            &client,
+            &[&payer],
+            std::slice::from_ref(&ix),
+            &config,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(outcome, TxOutcome::Landed { .. }));
+
+        // The mock's blockhash is valid through block height 1234.
+        let mut mocks = Mocks::default();
+        mocks.insert(RpcRequest::GetBlockHeight, serde_json::json!(1235));
+        let client = RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks);
+        let outcome = send_and_confirm_tx_with_rebroadcast(&client, &[&payer], &[ix], &config)
+            .await
+            .unwrap();
+        assert!(matches!(outcome, TxOutcome::Expired { .. }));
+    }
+}
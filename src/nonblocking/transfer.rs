@@ -0,0 +1,49 @@
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signature;
+
+use super::{
+    decode::decode_metadata, rpc::Rpc, transaction::send_and_confirm_tx_with_compute_budget,
+};
+use crate::{
+    data::Asset,
+    decode::ToPubkey,
+    errors::Result,
+    transfer::{build_transfer_asset_v1_ix, TransferAssetArgs},
+};
+
+pub async fn transfer_asset<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: TransferAssetArgs<'_, P>,
+) -> Result<Signature> {
+    let TransferAssetArgs::V1 {
+        payer,
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
+
+    let transfer_ix = transfer_asset_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(
+        client,
+        &[payer, authority],
+        &[transfer_ix],
+        &compute_budget,
+    )
+    .await
+}
+
+pub async fn transfer_asset_ix<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: TransferAssetArgs<'_, P>,
+) -> Result<Instruction> {
+    let args = args.resolve()?;
+    let TransferAssetArgs::V1 { mint, .. } = args;
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    Ok(build_transfer_asset_v1_ix(args, md))
+}
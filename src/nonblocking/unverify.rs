@@ -0,0 +1,87 @@
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signature;
+
+use super::{
+    decode::decode_metadata, rpc::Rpc, transaction::send_and_confirm_tx_with_compute_budget,
+};
+use crate::{
+    data::Asset,
+    decode::ToPubkey,
+    errors::Result,
+    unverify::{
+        build_unverify_collection_v1_ix, build_unverify_creator_v1_ix, UnverifyCollectionArgs,
+        UnverifyCreatorArgs,
+    },
+};
+
+pub async fn unverify_collection<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCollectionArgs<'_, P1, P2>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let UnverifyCollectionArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
+
+    let unverify_ix = unverify_collection_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[unverify_ix], &compute_budget)
+        .await
+}
+
+pub async fn unverify_collection_ix<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCollectionArgs<'_, P1, P2>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let args = args.resolve()?;
+    let UnverifyCollectionArgs::V1 { mint, .. } = args;
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    Ok(build_unverify_collection_v1_ix(args, md))
+}
+
+pub async fn unverify_creator<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<'_, P1>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+{
+    let UnverifyCreatorArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
+
+    let unverify_ix = unverify_creator_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[unverify_ix], &compute_budget)
+        .await
+}
+
+pub async fn unverify_creator_ix<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<'_, P1>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+{
+    let args = args.resolve()?;
+    let UnverifyCreatorArgs::V1 { mint, .. } = args;
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    build_unverify_creator_v1_ix(args, md)
+}
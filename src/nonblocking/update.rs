@@ -0,0 +1,73 @@
+use mpl_token_metadata::types::TokenStandard;
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signature;
+
+use super::{
+    decode::decode_metadata, nft::get_nft_token_account, rpc::Rpc,
+    transaction::send_and_confirm_tx_with_compute_budget,
+};
+use crate::{
+    data::{Asset, UPDATE_COMPUTE_UNITS},
+    decode::ToPubkey,
+    errors::Result,
+    update::{build_update_asset_v1_ix, UpdateAssetArgs},
+};
+
+pub async fn update_asset<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: UpdateAssetArgs<'_, P1, P2, P3>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let UpdateAssetArgs::V1 {
+        payer,
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let mut compute_budget = compute_budget.clone();
+
+    let update_ix = update_asset_ix(client, args).await?;
+
+    compute_budget
+        .fallback_compute_units
+        .get_or_insert(UPDATE_COMPUTE_UNITS);
+
+    send_and_confirm_tx_with_compute_budget(
+        client,
+        &[payer, authority],
+        &[update_ix],
+        &compute_budget,
+    )
+    .await
+}
+
+pub async fn update_asset_ix<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: UpdateAssetArgs<'_, P1, P2, P3>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let args = args.resolve()?;
+    let UpdateAssetArgs::V1 { mint, token, .. } = args;
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    // We need the token account passed in for pNFT updates.
+    let token =
+        if md.token_standard == Some(TokenStandard::ProgrammableNonFungible) && token.is_none() {
+            Some(get_nft_token_account(client, &mint.to_string()).await?)
+        } else {
+            None
+        };
+
+    Ok(build_update_asset_v1_ix(args, token, md))
+}
@@ -0,0 +1,87 @@
+use solana_program::instruction::Instruction;
+use solana_sdk::signature::Signature;
+
+use super::{
+    decode::decode_metadata, rpc::Rpc, transaction::send_and_confirm_tx_with_compute_budget,
+};
+use crate::{
+    data::Asset,
+    decode::ToPubkey,
+    errors::Result,
+    verify::{
+        build_verify_collection_v1_ix, build_verify_creator_v1_ix, VerifyCollectionArgs,
+        VerifyCreatorArgs,
+    },
+};
+
+pub async fn verify_collection<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCollectionArgs<'_, P1, P2>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let VerifyCollectionArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
+
+    let verify_ix = verify_collection_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[verify_ix], &compute_budget)
+        .await
+}
+
+pub async fn verify_collection_ix<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCollectionArgs<'_, P1, P2>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let args = args.resolve()?;
+    let VerifyCollectionArgs::V1 { mint, .. } = args;
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    Ok(build_verify_collection_v1_ix(args, md))
+}
+
+pub async fn verify_creator<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<'_, P1>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+{
+    let VerifyCreatorArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
+
+    let verify_ix = verify_creator_ix(client, args).await?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[verify_ix], &compute_budget)
+        .await
+}
+
+pub async fn verify_creator_ix<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<'_, P1>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+{
+    let args = args.resolve()?;
+    let VerifyCreatorArgs::V1 { mint, .. } = args;
+
+    let md = decode_metadata(client, &Asset::new(mint).metadata).await?;
+
+    build_verify_creator_v1_ix(args, md)
+}
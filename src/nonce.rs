@@ -0,0 +1,263 @@
+use solana_client::nonce_utils;
+use solana_program::instruction::Instruction;
+use solana_sdk::{
+    hash::Hash,
+    nonce::state::{Data, State},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::Signer,
+    system_instruction::{
+        advance_nonce_account, create_nonce_account as create_nonce_account_ixs,
+        withdraw_nonce_account,
+    },
+};
+
+use crate::{
+    decode::ToPubkey,
+    errors::Result,
+    rpc::Rpc,
+    transaction::{send_and_confirm_tx, BlockhashSource},
+};
+
+/// Create and initialize a durable nonce account controlled by `authority`.
+///
+/// When `lamports` is `None` the account is funded with the rent-exempt minimum.
+pub fn create_nonce_account(
+    client: &(impl Rpc + ?Sized),
+    payer: &dyn Signer,
+    nonce_account: &dyn Signer,
+    authority: &Pubkey,
+    lamports: Option<u64>,
+) -> Result<Signature> {
+    let lamports = match lamports {
+        Some(lamports) => lamports,
+        None => client.get_minimum_balance_for_rent_exemption(State::size())?,
+    };
+
+    let ixs = create_nonce_account_ixs(
+        &payer.pubkey(),
+        &nonce_account.pubkey(),
+        authority,
+        lamports,
+    );
+
+    send_and_confirm_tx(client, &[payer, nonce_account], &ixs)
+}
+
+/// Fetch the authority and current durable nonce stored in a nonce account.
+pub fn get_nonce_data<P: ToPubkey>(client: &(impl Rpc + ?Sized), nonce_account: P) -> Result<Data> {
+    let nonce_account = nonce_account.to_pubkey()?;
+
+    let account = client.get_account(&nonce_account)?;
+    let data = nonce_utils::data_from_account(&account)?;
+
+    Ok(data)
+}
+
+/// The blockhash value transactions using this nonce account must be signed against.
+pub fn get_nonce_blockhash<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    nonce_account: P,
+) -> Result<Hash> {
+    Ok(get_nonce_data(client, nonce_account)?.blockhash())
+}
+
+/// Build a [`BlockhashSource`] that uses the nonce account with its stored authority.
+pub fn nonce_blockhash_source<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    nonce_account: P,
+) -> Result<BlockhashSource> {
+    let nonce_account = nonce_account.to_pubkey()?;
+    let data = get_nonce_data(client, nonce_account)?;
+
+    Ok(BlockhashSource::DurableNonce {
+        nonce_account,
+        nonce_authority: data.authority,
+    })
+}
+
+/// Advance the nonce manually, invalidating any transaction signed against the
+/// current value.
+pub fn advance_nonce<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    nonce_account: P,
+) -> Result<Signature> {
+    let ix = advance_nonce_ix(&authority.pubkey(), nonce_account)?;
+
+    send_and_confirm_tx(client, &[authority], &[ix])
+}
+
+pub fn advance_nonce_ix<P: ToPubkey>(authority: &Pubkey, nonce_account: P) -> Result<Instruction> {
+    Ok(advance_nonce_account(
+        &nonce_account.to_pubkey()?,
+        authority,
+    ))
+}
+
+/// Withdraw lamports from a nonce account. Withdrawing the full balance closes it.
+pub fn withdraw_nonce<P1: ToPubkey, P2: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    authority: &dyn Signer,
+    nonce_account: P1,
+    recipient: P2,
+    lamports: u64,
+) -> Result<Signature> {
+    let ix = withdraw_nonce_ix(&authority.pubkey(), nonce_account, recipient, lamports)?;
+
+    send_and_confirm_tx(client, &[authority], &[ix])
+}
+
+pub fn withdraw_nonce_ix<P1: ToPubkey, P2: ToPubkey>(
+    authority: &Pubkey,
+    nonce_account: P1,
+    recipient: P2,
+    lamports: u64,
+) -> Result<Instruction> {
+    Ok(withdraw_nonce_account(
+        &nonce_account.to_pubkey()?,
+        authority,
+        &recipient.to_pubkey()?,
+        lamports,
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::constants::SYSTEM_PROGRAM_ID;
+    use solana_account_decoder::{encode_ui_account, UiAccountEncoding};
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+        rpc_response::{Response, RpcResponseContext},
+    };
+    use solana_program::{instruction::AccountMeta, sysvar};
+    use solana_sdk::{
+        account::Account,
+        nonce::state::{DurableNonce, Versions},
+        system_instruction::SystemInstruction,
+    };
+
+    /// A mock client serving one lookup of an initialized nonce account. It serves each
+    /// response once, so every lookup gets its own client.
+    pub(crate) fn nonce_client(
+        nonce_account: &Pubkey,
+        authority: &Pubkey,
+        blockhash: &Hash,
+    ) -> RpcClient {
+        let state = Versions::new(State::Initialized(Data::new(
+            *authority,
+            DurableNonce::from_blockhash(blockhash),
+            5000,
+        )));
+        let account = Account::new_data(1_000_000, &state, &SYSTEM_PROGRAM_ID).unwrap();
+
+        let mut mocks = Mocks::new();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            serde_json::json!(Response {
+                context: RpcResponseContext::new(1),
+                value: Some(encode_ui_account(
+                    nonce_account,
+                    &account,
+                    UiAccountEncoding::Base64,
+                    None,
+                    None,
+                )),
+            }),
+        );
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+    }
+
+    #[test]
+    fn test_nonce_blockhash_from_account() {
+        let nonce_account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let durable_nonce = DurableNonce::from_blockhash(&blockhash);
+
+        let client = nonce_client(&nonce_account, &authority, &blockhash);
+        assert_eq!(
+            get_nonce_blockhash(&client, nonce_account).unwrap(),
+            *durable_nonce.as_hash()
+        );
+
+        let client = nonce_client(&nonce_account, &authority, &blockhash);
+        assert_eq!(
+            nonce_blockhash_source(&client, nonce_account).unwrap(),
+            BlockhashSource::DurableNonce {
+                nonce_account,
+                nonce_authority: authority,
+            }
+        );
+    }
+
+    #[test]
+    fn test_nonce_blockhash_rejects_non_nonce_account() {
+        let nonce_account = Pubkey::new_unique();
+        let account = Account::new(1_000_000, 0, &SYSTEM_PROGRAM_ID);
+
+        let mut mocks = Mocks::new();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            serde_json::json!(Response {
+                context: RpcResponseContext::new(1),
+                value: Some(encode_ui_account(
+                    &nonce_account,
+                    &account,
+                    UiAccountEncoding::Base64,
+                    None,
+                    None,
+                )),
+            }),
+        );
+        let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        assert!(get_nonce_blockhash(&client, nonce_account).is_err());
+    }
+
+    #[test]
+    fn test_advance_nonce_ix() {
+        let nonce_account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let ix = advance_nonce_ix(&authority, nonce_account).unwrap();
+
+        assert_eq!(ix.program_id, SYSTEM_PROGRAM_ID);
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(nonce_account, false),
+                #[allow(deprecated)]
+                AccountMeta::new_readonly(sysvar::recent_blockhashes::ID, false),
+                AccountMeta::new_readonly(authority, true),
+            ]
+        );
+        assert_eq!(
+            bincode::deserialize::<SystemInstruction>(&ix.data).unwrap(),
+            SystemInstruction::AdvanceNonceAccount
+        );
+    }
+
+    #[test]
+    fn test_withdraw_nonce_ix() {
+        let nonce_account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+
+        let ix = withdraw_nonce_ix(&authority, nonce_account, recipient, 42).unwrap();
+
+        assert_eq!(ix.program_id, SYSTEM_PROGRAM_ID);
+        assert_eq!(ix.accounts[0], AccountMeta::new(nonce_account, false));
+        assert_eq!(ix.accounts[1], AccountMeta::new(recipient, false));
+        assert_eq!(
+            ix.accounts.last(),
+            Some(&AccountMeta::new_readonly(authority, true))
+        );
+        assert_eq!(
+            bincode::deserialize::<SystemInstruction>(&ix.data).unwrap(),
+            SystemInstruction::WithdrawNonceAccount(42)
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_program::instruction::Instruction;
+use solana_sdk::{
+    message::Message, pubkey::Pubkey, sanitize::Sanitize, signature::Signature, signer::Signer,
+    transaction::Transaction,
+};
+
+use crate::errors::{MetabossError, Result};
+use crate::rpc::Rpc;
+use crate::transaction::{resolve_blockhash, BlockhashSource};
+
+/// Text encoding used to move serialized transactions between signers.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum TxEncoding {
+    Base58,
+    #[default]
+    Base64,
+}
+
+impl FromStr for TxEncoding {
+    type Err = MetabossError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "base58" => Ok(Self::Base58),
+            "base64" => Ok(Self::Base64),
+            _ => Err(MetabossError::validation("Invalid transaction encoding")),
+        }
+    }
+}
+
+impl Display for TxEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Base58 => write!(f, "base58"),
+            Self::Base64 => write!(f, "base64"),
+        }
+    }
+}
+
+/// Build an unsigned transaction from instructions produced by the `*_ix` functions.
+///
+/// Authorities that are not available locally, such as multisig members, can be passed
+/// to the `*_ix` functions as a `NullSigner` since only their pubkeys are needed.
+pub fn build_unsigned_tx(
+    client: &(impl Rpc + ?Sized),
+    payer: &Pubkey,
+    ixs: &[Instruction],
+    blockhash: &BlockhashSource,
+) -> Result<Transaction> {
+    let (instructions, blockhash) = resolve_blockhash(client, ixs, blockhash)?;
+
+    let message = Message::new_with_blockhash(&instructions, Some(payer), &blockhash);
+
+    Ok(Transaction::new_unsigned(message))
+}
+
+pub fn encode_tx(tx: &Transaction, encoding: TxEncoding) -> Result<String> {
+    let bytes = bincode::serialize(tx)?;
+
+    let encoded = match encoding {
+        TxEncoding::Base58 => bs58::encode(bytes).into_string(),
+        TxEncoding::Base64 => STANDARD.encode(bytes),
+    };
+
+    Ok(encoded)
+}
+
+pub fn decode_tx(encoded: &str, encoding: TxEncoding) -> Result<Transaction> {
+    let bytes = match encoding {
+        TxEncoding::Base58 => bs58::decode(encoded)
+            .into_vec()
+            .map_err(|e| MetabossError::deserialize(None, e))?,
+        TxEncoding::Base64 => STANDARD
+            .decode(encoded)
+            .map_err(|e| MetabossError::deserialize(None, e))?,
+    };
+
+    let tx: Transaction = bincode::deserialize(&bytes)?;
+    tx.sanitize()?;
+
+    Ok(tx)
+}
+
+/// Sign with whichever of the required signers are available locally, leaving the
+/// other signature slots untouched.
+pub fn partial_sign_tx(tx: &mut Transaction, signers: &[&dyn Signer]) -> Result<()> {
+    let blockhash = tx.message.recent_blockhash;
+    tx.try_partial_sign(signers, blockhash)?;
+
+    Ok(())
+}
+
+/// Attach a signature that was produced elsewhere, e.g. by a hardware wallet or a
+/// multisig member signing the serialized message.
+pub fn add_signature(tx: &mut Transaction, signer: &Pubkey, signature: Signature) -> Result<()> {
+    let index = required_signers(tx)?
+        .iter()
+        .position(|key| key == signer)
+        .ok_or_else(|| {
+            MetabossError::Validation(format!(
+                "{signer} is not a required signer of this transaction"
+            ))
+        })?;
+
+    if !signature.verify(signer.as_ref(), &tx.message_data()) {
+        return Err(MetabossError::Validation(format!(
+            "Signature {signature} is not valid for signer {signer}"
+        )));
+    }
+
+    tx.signatures[index] = signature;
+
+    Ok(())
+}
+
+/// Required signers that have not signed the transaction yet.
+pub fn missing_signers(tx: &Transaction) -> Result<Vec<Pubkey>> {
+    let missing = required_signers(tx)?
+        .iter()
+        .zip(tx.signatures.iter())
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(key, _)| *key)
+        .collect();
+
+    Ok(missing)
+}
+
+/// Check that every required signer is present and every signature is valid.
+pub fn verify_tx_signatures(tx: &Transaction) -> Result<()> {
+    if let Some(missing) = missing_signers(tx)?.first() {
+        return Err(MetabossError::SignerMissing(*missing));
+    }
+
+    tx.verify()?;
+
+    Ok(())
+}
+
+/// Submit a fully signed transaction.
+pub fn send_signed_tx(client: &(impl Rpc + ?Sized), tx: &Transaction) -> Result<Signature> {
+    verify_tx_signatures(tx)?;
+
+    let signature = client.send_and_confirm_transaction(&tx.clone().into())?;
+
+    Ok(signature)
+}
+
+/// The signer keys of `tx`, after checking that its header agrees with its account keys
+/// and signatures.
+fn required_signers(tx: &Transaction) -> Result<&[Pubkey]> {
+    tx.sanitize()
+        .map_err(|e| MetabossError::Validation(format!("Malformed transaction: {e}")))?;
+
+    let num_signers = tx.message.header.num_required_signatures as usize;
+    Ok(&tx.message.account_keys[..num_signers])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, signature::Keypair, system_instruction::transfer};
+
+    fn unsigned_tx(payer: &Keypair, authority: &Keypair) -> Transaction {
+        let ixs = [
+            transfer(&payer.pubkey(), &Pubkey::new_unique(), 1),
+            transfer(&authority.pubkey(), &Pubkey::new_unique(), 1),
+        ];
+        let message = Message::new_with_blockhash(&ixs, Some(&payer.pubkey()), &Hash::default());
+        Transaction::new_unsigned(message)
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+        let tx = unsigned_tx(&payer, &authority);
+
+        for encoding in [TxEncoding::Base58, TxEncoding::Base64] {
+            let encoded = encode_tx(&tx, encoding).unwrap();
+            assert_eq!(decode_tx(&encoded, encoding).unwrap(), tx);
+        }
+    }
+
+    #[test]
+    fn test_partial_and_external_signing() {
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+        let mut tx = unsigned_tx(&payer, &authority);
+
+        assert_eq!(
+            missing_signers(&tx).unwrap(),
+            vec![payer.pubkey(), authority.pubkey()]
+        );
+
+        partial_sign_tx(&mut tx, &[&payer]).unwrap();
+        assert_eq!(missing_signers(&tx).unwrap(), vec![authority.pubkey()]);
+        assert!(verify_tx_signatures(&tx).is_err());
+
+        // Signature collected from the other party over the serialized message.
+        let signature = authority.sign_message(&tx.message_data());
+        add_signature(&mut tx, &authority.pubkey(), signature).unwrap();
+
+        assert!(missing_signers(&tx).unwrap().is_empty());
+        verify_tx_signatures(&tx).unwrap();
+    }
+
+    #[test]
+    fn test_add_signature_rejects_invalid() {
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+        let mut tx = unsigned_tx(&payer, &authority);
+
+        let bad_signature = authority.sign_message(b"something else");
+        assert!(add_signature(&mut tx, &authority.pubkey(), bad_signature).is_err());
+
+        let stranger = Keypair::new();
+        let signature = stranger.sign_message(&tx.message_data());
+        assert!(add_signature(&mut tx, &stranger.pubkey(), signature).is_err());
+    }
+
+    #[test]
+    fn test_malformed_header_is_rejected() {
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+        let mut tx = unsigned_tx(&payer, &authority);
+        tx.message.header.num_required_signatures = tx.message.account_keys.len() as u8 + 1;
+
+        assert!(matches!(
+            missing_signers(&tx),
+            Err(MetabossError::Validation(_))
+        ));
+        assert!(verify_tx_signatures(&tx).is_err());
+        assert!(add_signature(&mut tx, &payer.pubkey(), Signature::default()).is_err());
+    }
+
+    #[test]
+    fn test_tx_encoding_from_str() {
+        assert_eq!(TxEncoding::from_str("Base58").unwrap(), TxEncoding::Base58);
+        assert_eq!(TxEncoding::from_str("base64").unwrap(), TxEncoding::Base64);
+        assert!(TxEncoding::from_str("hex").is_err());
+    }
+}
@@ -0,0 +1,336 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    signature::Signature,
+    signer::Signer,
+};
+
+use crate::{
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+    transaction::{
+        add_compute_budget, send_and_confirm_tx, send_and_confirm_versioned_tx, ComputeBudget,
+    },
+};
+
+/// Maximum compute units a single transaction can request.
+pub const MAX_TX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Compute units assumed for an instruction that doesn't carry its own estimate.
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u32 = 50_000;
+
+const SIGNATURE_LENGTH: usize = 64;
+
+/// An instruction from one of the `*_ix` builders, tagged with its origin (e.g. the
+/// mint it targets) so packed transactions can be traced back to their inputs.
+#[derive(Debug, Clone)]
+pub struct PackItem<T> {
+    pub origin: T,
+    pub instruction: Instruction,
+    pub compute_units: Option<u32>,
+}
+
+impl<T> PackItem<T> {
+    pub fn new(origin: T, instruction: Instruction) -> Self {
+        Self {
+            origin,
+            instruction,
+            compute_units: None,
+        }
+    }
+
+    pub fn with_compute_units(mut self, units: u32) -> Self {
+        self.compute_units = Some(units);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PackerConfig {
+    /// Fee payer of every packed transaction.
+    pub payer: Pubkey,
+    /// Signers available to sign. When non-empty, instructions requiring any other
+    /// signer are rejected.
+    pub signers: Vec<Pubkey>,
+    pub max_tx_size: usize,
+    pub max_compute_units: u32,
+    pub default_compute_units: u32,
+    /// Reserve room for the compute unit limit and price instructions.
+    pub with_compute_budget: bool,
+    /// Lookup tables to compile v0 messages against. Legacy messages are used when empty.
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+}
+
+impl PackerConfig {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            signers: Vec::new(),
+            max_tx_size: PACKET_DATA_SIZE,
+            max_compute_units: MAX_TX_COMPUTE_UNITS,
+            default_compute_units: DEFAULT_INSTRUCTION_COMPUTE_UNITS,
+            with_compute_budget: true,
+            lookup_tables: Vec::new(),
+        }
+    }
+}
+
+/// A batch of instructions that fits in a single transaction.
+#[derive(Debug, Clone)]
+pub struct PackedTx<T> {
+    pub instructions: Vec<Instruction>,
+    /// Origins of `instructions`, in the same order.
+    pub origins: Vec<T>,
+    pub compute_units: u32,
+    /// Serialized size of the signed transaction, including compute budget instructions
+    /// when reserved.
+    pub size: usize,
+}
+
+/// Greedily pack instructions, in order, into as few transactions as possible while
+/// respecting the size limit, the compute limit and the available signers.
+pub fn pack_instructions<T, I>(items: I, config: &PackerConfig) -> Result<Vec<PackedTx<T>>>
+where
+    I: IntoIterator<Item = PackItem<T>>,
+{
+    let mut packed = Vec::new();
+    let mut current: Option<PackedTx<T>> = None;
+
+    for item in items {
+        check_signers(&item.instruction, config)?;
+
+        let units = item.compute_units.unwrap_or(config.default_compute_units);
+
+        if let Some(tx) = current.as_mut() {
+            let total_units = tx.compute_units.saturating_add(units);
+            if total_units <= config.max_compute_units {
+                tx.instructions.push(item.instruction.clone());
+                match tx_size(&tx.instructions, total_units, config) {
+                    Ok(size) if size <= config.max_tx_size => {
+                        tx.origins.push(item.origin);
+                        tx.compute_units = total_units;
+                        tx.size = size;
+                        continue;
+                    }
+                    _ => {
+                        tx.instructions.pop();
+                    }
+                }
+            }
+            packed.extend(current.take());
+        }
+
+        if units > config.max_compute_units {
+            return Err(MetabossError::Validation(format!(
+                "Instruction requires {} compute units, more than the {} allowed per transaction",
+                units, config.max_compute_units
+            )));
+        }
+
+        let instructions = vec![item.instruction];
+        let size = tx_size(&instructions, units, config)?;
+        if size > config.max_tx_size {
+            return Err(MetabossError::Validation(format!(
+                "Instruction alone produces a {} byte transaction, more than the {} allowed",
+                size, config.max_tx_size
+            )));
+        }
+
+        current = Some(PackedTx {
+            instructions,
+            origins: vec![item.origin],
+            compute_units: units,
+            size,
+        });
+    }
+
+    packed.extend(current);
+
+    Ok(packed)
+}
+
+/// Send a packed transaction with the `config` it was packed with, so it is compiled
+/// against the same lookup tables that were used to size it. When compute budget
+/// instructions were reserved, the compute unit limit is set to the packed estimate and
+/// `compute_budget` only supplies the priority fee settings.
+pub fn send_packed_tx<T>(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    packed: &PackedTx<T>,
+    config: &PackerConfig,
+    compute_budget: &ComputeBudget,
+) -> Result<Signature> {
+    let ixs = if config.with_compute_budget {
+        let budget = compute_budget
+            .clone()
+            .with_compute_units(packed.compute_units);
+        add_compute_budget(client, signers, &packed.instructions, &budget)?
+    } else {
+        packed.instructions.clone()
+    };
+
+    if config.lookup_tables.is_empty() {
+        send_and_confirm_tx(client, signers, &ixs)
+    } else {
+        send_and_confirm_versioned_tx(client, signers, &ixs, &config.lookup_tables)
+    }
+}
+
+fn check_signers(ix: &Instruction, config: &PackerConfig) -> Result<()> {
+    if config.signers.is_empty() {
+        return Ok(());
+    }
+
+    for meta in ix.accounts.iter().filter(|meta| meta.is_signer) {
+        if meta.pubkey != config.payer && !config.signers.contains(&meta.pubkey) {
+            return Err(MetabossError::Validation(format!(
+                "Instruction requires signer {} which is not available",
+                meta.pubkey
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn tx_size(ixs: &[Instruction], compute_units: u32, config: &PackerConfig) -> Result<usize> {
+    let mut instructions = Vec::with_capacity(ixs.len() + 2);
+    if config.with_compute_budget {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+            compute_units,
+        ));
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(u64::MAX));
+    }
+    instructions.extend_from_slice(ixs);
+
+    let message = if config.lookup_tables.is_empty() {
+        VersionedMessage::Legacy(Message::new(&instructions, Some(&config.payer)))
+    } else {
+        VersionedMessage::V0(v0::Message::try_compile(
+            &config.payer,
+            &instructions,
+            &config.lookup_tables,
+            Hash::default(),
+        )?)
+    };
+
+    let num_signatures = message.header().num_required_signatures as usize;
+    let message_size = bincode::serialized_size(&message)? as usize;
+
+    Ok(short_vec_len(num_signatures) + num_signatures * SIGNATURE_LENGTH + message_size)
+}
+
+// Length of the compact-u16 prefix used by the wire format.
+fn short_vec_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::instruction::AccountMeta;
+    use solana_sdk::{signature::Keypair, transaction::Transaction};
+
+    fn verify_like_ix(authority: &Pubkey, collection: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            mpl_token_metadata::ID,
+            &[52, 1],
+            vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(*collection, false),
+                AccountMeta::new(crate::derive::derive_metadata_pda(collection), false),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_pack_instructions_respects_size_and_tracks_origins() {
+        let payer = Keypair::new();
+        let collection = Pubkey::new_unique();
+        let config = PackerConfig::new(payer.pubkey());
+
+        let items: Vec<PackItem<usize>> = (0..100)
+            .map(|i| PackItem::new(i, verify_like_ix(&payer.pubkey(), &collection)))
+            .collect();
+
+        let packed = pack_instructions(items, &config).unwrap();
+
+        assert!(packed.len() > 1);
+        assert!(packed.len() < 100);
+
+        let origins: Vec<usize> = packed.iter().flat_map(|tx| tx.origins.clone()).collect();
+        assert_eq!(origins, (0..100).collect::<Vec<_>>());
+
+        for tx in &packed {
+            assert!(tx.size <= PACKET_DATA_SIZE);
+            assert_eq!(tx.instructions.len(), tx.origins.len());
+
+            // The estimate matches the real serialized transaction.
+            let mut ixs = vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(tx.compute_units),
+                ComputeBudgetInstruction::set_compute_unit_price(u64::MAX),
+            ];
+            ixs.extend(tx.instructions.clone());
+            let signed = Transaction::new_signed_with_payer(
+                &ixs,
+                Some(&payer.pubkey()),
+                &[&payer],
+                Hash::default(),
+            );
+            assert_eq!(bincode::serialized_size(&signed).unwrap() as usize, tx.size);
+        }
+    }
+
+    #[test]
+    fn test_pack_instructions_respects_compute_limit() {
+        let payer = Pubkey::new_unique();
+        let mut config = PackerConfig::new(payer);
+        config.max_compute_units = 100_000;
+
+        let items: Vec<PackItem<u8>> = (0..4)
+            .map(|i| {
+                PackItem::new(i, verify_like_ix(&payer, &Pubkey::new_unique()))
+                    .with_compute_units(40_000)
+            })
+            .collect();
+
+        let packed = pack_instructions(items, &config).unwrap();
+
+        assert_eq!(packed.len(), 2);
+        assert_eq!(packed[0].origins, vec![0, 1]);
+        assert_eq!(packed[0].compute_units, 80_000);
+        assert_eq!(packed[1].origins, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_pack_instructions_rejects_unavailable_signer() {
+        let payer = Pubkey::new_unique();
+        let mut config = PackerConfig::new(payer);
+        config.signers = vec![Pubkey::new_unique()];
+
+        let items = vec![PackItem::new(
+            (),
+            verify_like_ix(&Pubkey::new_unique(), &Pubkey::new_unique()),
+        )];
+
+        assert!(pack_instructions(items, &config).is_err());
+    }
+
+    #[test]
+    fn test_pack_instructions_rejects_oversized_instruction() {
+        let payer = Pubkey::new_unique();
+        let config = PackerConfig::new(payer);
+
+        let ix = Instruction::new_with_bytes(mpl_token_metadata::ID, &[0; 1300], vec![]);
+
+        assert!(pack_instructions(vec![PackItem::new((), ix)], &config).is_err());
+    }
+}
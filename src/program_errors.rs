@@ -0,0 +1,317 @@
+use mpl_token_metadata::errors::MplTokenMetadataError;
+use num_traits::FromPrimitive;
+use solana_client::client_error::ClientError;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{
+    instruction::InstructionError, signature::Signature, transaction::TransactionError,
+};
+use spl_associated_token_account::error::AssociatedTokenAccountError;
+use spl_token::error::TokenError;
+use thiserror::Error;
+
+use crate::{constants::AUTH_RULES_PROGRAM_ID, errors::MetabossError};
+
+/// A failed instruction, with its custom error code decoded when the program is known.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+#[error("instruction {instruction_index} failed: {error}")]
+pub struct ProgramFailure {
+    pub instruction_index: u8,
+    /// Program the failing instruction was sent to, if it could be identified.
+    pub program_id: Option<Pubkey>,
+    pub error: ProgramErrorKind,
+    /// Transaction the instruction failed in, when it landed on-chain rather than failing
+    /// preflight.
+    pub signature: Option<Signature>,
+}
+
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum ProgramErrorKind {
+    #[error("Token Metadata error: {0:?}")]
+    TokenMetadata(MplTokenMetadataError),
+
+    #[error("SPL Token error: {0}")]
+    Token(TokenError),
+
+    #[error("Associated Token Account error: {0}")]
+    AssociatedToken(AssociatedTokenAccountError),
+
+    #[error("Token Auth Rules error: {0}")]
+    AuthRules(AuthRulesError),
+
+    /// A custom code from a program without a known error table.
+    #[error("custom program error: {0:#x}")]
+    Custom(u32),
+
+    /// A builtin error that isn't a custom program code.
+    #[error("{0}")]
+    Instruction(InstructionError),
+}
+
+/// Errors of the Token Auth Rules program, which isn't a dependency of this crate.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+#[repr(u32)]
+pub enum AuthRulesError {
+    #[error("Numerical overflow")]
+    NumericalOverflow = 0,
+    #[error("Data type mismatch")]
+    DataTypeMismatch,
+    #[error("Data slice unexpected index error")]
+    DataSliceUnexpectedIndexError,
+    #[error("Incorrect account owner")]
+    IncorrectOwner,
+    #[error("Could not index into PayloadVec")]
+    PayloadVecIndexError,
+    #[error("Derived key invalid")]
+    DerivedKeyInvalid,
+    #[error("Payer is not a signer")]
+    PayerIsNotSigner,
+    #[error("Not implemented")]
+    NotImplemented,
+    #[error("Borsh serialization error")]
+    BorshSerializationError,
+    #[error("Borsh deserialization error")]
+    BorshDeserializationError,
+    #[error("Value in Payload or RuleSet is occupied")]
+    ValueOccupied,
+    #[error("Account data is empty")]
+    DataIsEmpty,
+    #[error("MessagePack serialization error")]
+    MessagePackSerializationError,
+    #[error("MessagePack deserialization error")]
+    MessagePackDeserializationError,
+    #[error("Missing account")]
+    MissingAccount,
+    #[error("Missing Payload value")]
+    MissingPayloadValue,
+    #[error("RuleSet owner must be payer")]
+    RuleSetOwnerMismatch,
+    #[error("Name too long")]
+    NameTooLong,
+    #[error("The operation retrieved is not in the selected RuleSet")]
+    OperationNotFound,
+    #[error("Rule authority is not signer")]
+    RuleAuthorityIsNotSigner,
+    #[error("Unsupported RuleSet revision map version")]
+    UnsupportedRuleSetRevMapVersion,
+    #[error("Unsupported RuleSet version")]
+    UnsupportedRuleSetVersion,
+    #[error("Unexpected RuleSet failure")]
+    UnexpectedRuleSetFailure,
+    #[error("RuleSet revision not available")]
+    RuleSetRevisionNotAvailable,
+    #[error("Additional Signers check failed")]
+    AdditionalSignerCheckFailed,
+    #[error("Pubkey Match check failed")]
+    PubkeyMatchCheckFailed,
+    #[error("Derived Key Match check failed")]
+    DerivedKeyMatchCheckFailed,
+    #[error("Program Owned check failed")]
+    ProgramOwnedCheckFailed,
+    #[error("Amount checked failed")]
+    AmountCheckFailed,
+    #[error("Frequency check failed")]
+    FrequencyCheckFailed,
+    #[error("Pubkey Tree Match check failed")]
+    PubkeyTreeMatchCheckFailed,
+}
+
+impl AuthRulesError {
+    const ALL: [AuthRulesError; 31] = [
+        AuthRulesError::NumericalOverflow,
+        AuthRulesError::DataTypeMismatch,
+        AuthRulesError::DataSliceUnexpectedIndexError,
+        AuthRulesError::IncorrectOwner,
+        AuthRulesError::PayloadVecIndexError,
+        AuthRulesError::DerivedKeyInvalid,
+        AuthRulesError::PayerIsNotSigner,
+        AuthRulesError::NotImplemented,
+        AuthRulesError::BorshSerializationError,
+        AuthRulesError::BorshDeserializationError,
+        AuthRulesError::ValueOccupied,
+        AuthRulesError::DataIsEmpty,
+        AuthRulesError::MessagePackSerializationError,
+        AuthRulesError::MessagePackDeserializationError,
+        AuthRulesError::MissingAccount,
+        AuthRulesError::MissingPayloadValue,
+        AuthRulesError::RuleSetOwnerMismatch,
+        AuthRulesError::NameTooLong,
+        AuthRulesError::OperationNotFound,
+        AuthRulesError::RuleAuthorityIsNotSigner,
+        AuthRulesError::UnsupportedRuleSetRevMapVersion,
+        AuthRulesError::UnsupportedRuleSetVersion,
+        AuthRulesError::UnexpectedRuleSetFailure,
+        AuthRulesError::RuleSetRevisionNotAvailable,
+        AuthRulesError::AdditionalSignerCheckFailed,
+        AuthRulesError::PubkeyMatchCheckFailed,
+        AuthRulesError::DerivedKeyMatchCheckFailed,
+        AuthRulesError::ProgramOwnedCheckFailed,
+        AuthRulesError::AmountCheckFailed,
+        AuthRulesError::FrequencyCheckFailed,
+        AuthRulesError::PubkeyTreeMatchCheckFailed,
+    ];
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.get(code as usize).copied()
+    }
+}
+
+impl ProgramErrorKind {
+    /// Decode a custom error `code` returned by `program_id`.
+    pub fn from_custom_code(program_id: Option<&Pubkey>, code: u32) -> Self {
+        let known = match program_id {
+            Some(id) if *id == mpl_token_metadata::ID => {
+                MplTokenMetadataError::from_u32(code).map(ProgramErrorKind::TokenMetadata)
+            }
+            Some(id) if *id == spl_token::ID => {
+                TokenError::from_u32(code).map(ProgramErrorKind::Token)
+            }
+            Some(id) if *id == spl_associated_token_account::ID => {
+                AssociatedTokenAccountError::from_u32(code).map(ProgramErrorKind::AssociatedToken)
+            }
+            Some(id) if *id == AUTH_RULES_PROGRAM_ID => {
+                AuthRulesError::from_code(code).map(ProgramErrorKind::AuthRules)
+            }
+            _ => None,
+        };
+
+        known.unwrap_or(ProgramErrorKind::Custom(code))
+    }
+}
+
+/// Map a transaction error to the failing instruction's program error. `ixs` are the
+/// instructions in the order they appear in the transaction message.
+///
+/// Returns `None` for errors that aren't tied to an instruction, such as an expired
+/// blockhash or insufficient funds for fees.
+pub fn map_transaction_error(
+    err: &TransactionError,
+    ixs: &[Instruction],
+) -> Option<ProgramFailure> {
+    let TransactionError::InstructionError(index, ix_error) = err else {
+        return None;
+    };
+
+    let program_id = ixs.get(*index as usize).map(|ix| ix.program_id);
+
+    let error = match ix_error {
+        InstructionError::Custom(code) => {
+            ProgramErrorKind::from_custom_code(program_id.as_ref(), *code)
+        }
+        other => ProgramErrorKind::Instruction(other.clone()),
+    };
+
+    Some(ProgramFailure {
+        instruction_index: *index,
+        program_id,
+        error,
+        signature: None,
+    })
+}
+
+/// Convert a client error into a [`ProgramFailure`] when it carries an instruction
+/// error, either from preflight simulation or from execution, and into a plain error
+/// otherwise.
+pub(crate) fn map_client_error(err: ClientError, ixs: &[Instruction]) -> MetabossError {
+    match err
+        .get_transaction_error()
+        .and_then(|tx_err| map_transaction_error(&tx_err, ixs))
+    {
+        Some(failure) => failure.into(),
+        None => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::instruction::AccountMeta;
+
+    fn ix(program_id: Pubkey) -> Instruction {
+        Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(program_id, false)])
+    }
+
+    #[test]
+    fn test_map_token_metadata_error() {
+        let ixs = [ix(Pubkey::new_unique()), ix(mpl_token_metadata::ID)];
+        // 0x3b is DataIsImmutable.
+        let err = TransactionError::InstructionError(1, InstructionError::Custom(0x3b));
+
+        let failure = map_transaction_error(&err, &ixs).unwrap();
+
+        assert_eq!(failure.instruction_index, 1);
+        assert_eq!(failure.program_id, Some(mpl_token_metadata::ID));
+        assert_eq!(
+            failure.error,
+            ProgramErrorKind::TokenMetadata(MplTokenMetadataError::DataIsImmutable)
+        );
+    }
+
+    #[test]
+    fn test_map_token_and_ata_errors() {
+        let ixs = [ix(spl_token::ID), ix(spl_associated_token_account::ID)];
+
+        let token = map_transaction_error(
+            &TransactionError::InstructionError(0, InstructionError::Custom(4)),
+            &ixs,
+        )
+        .unwrap();
+        assert_eq!(
+            token.error,
+            ProgramErrorKind::Token(TokenError::OwnerMismatch)
+        );
+
+        let ata = map_transaction_error(
+            &TransactionError::InstructionError(1, InstructionError::Custom(0)),
+            &ixs,
+        )
+        .unwrap();
+        assert_eq!(
+            ata.error,
+            ProgramErrorKind::AssociatedToken(AssociatedTokenAccountError::InvalidOwner)
+        );
+    }
+
+    #[test]
+    fn test_map_auth_rules_error() {
+        let ixs = [ix(AUTH_RULES_PROGRAM_ID)];
+
+        let failure = map_transaction_error(
+            &TransactionError::InstructionError(0, InstructionError::Custom(23)),
+            &ixs,
+        )
+        .unwrap();
+
+        assert_eq!(
+            failure.error,
+            ProgramErrorKind::AuthRules(AuthRulesError::RuleSetRevisionNotAvailable)
+        );
+    }
+
+    #[test]
+    fn test_map_unknown_and_builtin_errors() {
+        let program = Pubkey::new_unique();
+        let ixs = [ix(program)];
+
+        let unknown = map_transaction_error(
+            &TransactionError::InstructionError(0, InstructionError::Custom(7)),
+            &ixs,
+        )
+        .unwrap();
+        assert_eq!(unknown.error, ProgramErrorKind::Custom(7));
+
+        let builtin = map_transaction_error(
+            &TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature),
+            &ixs,
+        )
+        .unwrap();
+        assert_eq!(
+            builtin.error,
+            ProgramErrorKind::Instruction(InstructionError::MissingRequiredSignature)
+        );
+
+        assert_eq!(
+            map_transaction_error(&TransactionError::BlockhashNotFound, &ixs),
+            None
+        );
+    }
+}
@@ -1,45 +1,74 @@
-use anyhow::Result;
 use borsh::BorshSerialize;
 use mpl_token_metadata::{
-    accounts::{MetadataDelegateRecord, TokenRecord},
+    accounts::{Metadata, MetadataDelegateRecord, TokenRecord},
     hooked::MetadataDelegateRoleSeed,
     types::{MetadataDelegateRole, ProgrammableConfig, RevokeArgs, TokenStandard},
     ID,
 };
-use solana_client::rpc_client::RpcClient;
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     sysvar,
 };
-use solana_sdk::{
-    signature::{Keypair, Signature},
-    signer::Signer,
-};
+use solana_sdk::{signature::Signature, signer::Signer};
 
 use crate::{
     constants::{AUTH_RULES_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID},
     data::Asset,
     decode::ToPubkey,
+    errors::Result,
     nft::get_nft_token_account,
-    transaction::send_and_confirm_tx,
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::{send_and_confirm_tx_with_compute_budget, ComputeBudget},
 };
 
 const REVOKE_IX: u8 = 45;
 
 pub enum RevokeAssetArgs<'a, P1, P2, P3: ToPubkey> {
     V1 {
-        payer: Option<&'a Keypair>,
-        authority: &'a Keypair,
+        payer: Option<&'a dyn Signer>,
+        authority: &'a dyn Signer,
         mint: P1,
         token: Option<P2>,
         delegate: P3,
         revoke_args: RevokeArgs,
+        compute_budget: ComputeBudget,
     },
 }
 
+impl<'a, P1, P2, P3> RevokeAssetArgs<'a, P1, P2, P3>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<RevokeAssetArgs<'a, Pubkey, Pubkey, Pubkey>> {
+        let RevokeAssetArgs::V1 {
+            payer,
+            authority,
+            mint,
+            token,
+            delegate,
+            revoke_args,
+            compute_budget,
+        } = self;
+
+        Ok(RevokeAssetArgs::V1 {
+            payer,
+            authority,
+            mint: mint.to_pubkey()?,
+            token: token.map(|t| t.to_pubkey()).transpose()?,
+            delegate: delegate.to_pubkey()?,
+            revoke_args,
+            compute_budget,
+        })
+    }
+}
+
 pub fn revoke_asset<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: RevokeAssetArgs<P1, P2, P3>,
 ) -> Result<Signature>
 where
@@ -53,7 +82,7 @@ where
 }
 
 pub fn revoke_asset_ix<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: RevokeAssetArgs<P1, P2, P3>,
 ) -> Result<Instruction>
 where
@@ -66,8 +95,22 @@ where
     }
 }
 
+pub fn revoke_asset_simulate<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: RevokeAssetArgs<P1, P2, P3>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    match args {
+        RevokeAssetArgs::V1 { .. } => revoke_asset_v1_simulate(client, args),
+    }
+}
+
 fn revoke_asset_v1<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: RevokeAssetArgs<P1, P2, P3>,
 ) -> Result<Signature>
 where
@@ -76,18 +119,47 @@ where
     P3: ToPubkey,
 {
     let RevokeAssetArgs::V1 {
-        payer, authority, ..
+        payer,
+        authority,
+        ref compute_budget,
+        ..
     } = args;
 
     let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
 
     let revoke_ix = revoke_asset_v1_ix(client, args)?;
 
-    send_and_confirm_tx(client, &[payer, authority], &[revoke_ix])
+    send_and_confirm_tx_with_compute_budget(
+        client,
+        &[payer, authority],
+        &[revoke_ix],
+        &compute_budget,
+    )
+}
+
+fn revoke_asset_v1_simulate<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: RevokeAssetArgs<P1, P2, P3>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let RevokeAssetArgs::V1 {
+        payer, authority, ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+
+    let ix = revoke_asset_v1_ix(client, args)?;
+
+    simulate_tx(client, &[payer, authority], &[ix])
 }
 
 fn revoke_asset_v1_ix<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: RevokeAssetArgs<P1, P2, P3>,
 ) -> Result<Instruction>
 where
@@ -95,30 +167,39 @@ where
     P2: ToPubkey,
     P3: ToPubkey,
 {
+    let args = args.resolve()?;
+    let RevokeAssetArgs::V1 { mint, token, .. } = args;
+
+    let token = match token {
+        Some(token) => token,
+        None => get_nft_token_account(client, &mint.to_string())?,
+    };
+
+    let md = Asset::new(mint).get_metadata(client)?;
+
+    Ok(build_revoke_asset_v1_ix(args, token, md))
+}
+
+/// Build the revoke instruction from resolved arguments, the asset's token account and
+/// its metadata.
+pub(crate) fn build_revoke_asset_v1_ix(
+    args: RevokeAssetArgs<Pubkey, Pubkey, Pubkey>,
+    token: Pubkey,
+    md: Metadata,
+) -> Instruction {
     let RevokeAssetArgs::V1 {
         payer,
         authority,
         mint,
-        token,
         delegate,
         revoke_args,
+        ..
     } = args;
 
     let payer = payer.unwrap_or(authority);
 
-    let mint = mint.to_pubkey()?;
     let mut asset = Asset::new(mint);
 
-    let token = if let Some(t) = token {
-        t.to_pubkey()?
-    } else {
-        get_nft_token_account(client, &mint.to_string())?
-    };
-
-    let md = asset.get_metadata(client)?;
-
-    let delegate = delegate.to_pubkey()?;
-
     let (auth_rules, auth_rules_program) =
         if let Some(ProgrammableConfig::V1 { rule_set: rules }) = md.programmable_config {
             (rules, Some(AUTH_RULES_PROGRAM_ID))
@@ -233,9 +314,7 @@ where
         revoke_accounts.master_edition = asset.edition;
     }
 
-    let revoke_ix: Instruction = revoke_ix(revoke_accounts, revoke_args);
-
-    Ok(revoke_ix)
+    revoke_ix(revoke_accounts, revoke_args)
 }
 
 fn revoke_ix(accounts: RevokeAccounts, args: RevokeArgs) -> Instruction {
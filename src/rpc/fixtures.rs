@@ -0,0 +1,1108 @@
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr, sync::Mutex};
+
+#[cfg(feature = "async")]
+use async_trait::async_trait;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_account_decoder::{encode_ui_account, UiAccount, UiAccountEncoding};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_config::{
+        RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig,
+    },
+    rpc_response::{
+        Response, RpcBlockhash, RpcKeyedAccount, RpcPrioritizationFee, RpcResult,
+        RpcSimulateTransactionResult, RpcTokenAccountBalance,
+    },
+};
+use solana_sdk::{
+    account::Account, clock::Slot, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+    signature::Signature, transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::TransactionStatus;
+
+use super::Rpc;
+use crate::errors::Result;
+#[cfg(feature = "async")]
+use crate::nonblocking::rpc::Rpc as AsyncRpc;
+
+/// RPC responses captured by a [`RecordingRpc`], keyed by method and parameters.
+///
+/// Saved as pretty-printed JSON with sorted keys, so recording the same calls twice
+/// produces the same file. Account data is stored base64 encoded, the same way the
+/// node returns it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fixtures {
+    /// Commitment of the recorded client, reported back by [`ReplayRpc`].
+    pub commitment: CommitmentConfig,
+    pub entries: BTreeMap<String, Recorded>,
+}
+
+/// A single recorded response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recorded {
+    Ok(Value),
+    /// The error message; replayed as `ClientErrorKind::Custom`.
+    Err(String),
+}
+
+impl Fixtures {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&file)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    fn insert<T, S: Serialize>(
+        &mut self,
+        key: String,
+        result: &ClientResult<T>,
+        stored: impl FnOnce(&T) -> S,
+    ) {
+        let recorded = match result {
+            Ok(value) => match serde_json::to_value(stored(value)) {
+                Ok(value) => Recorded::Ok(value),
+                Err(err) => Recorded::Err(err.to_string()),
+            },
+            Err(err) => Recorded::Err(err.to_string()),
+        };
+
+        self.entries.insert(key, recorded);
+    }
+
+    fn get<T, S: DeserializeOwned>(
+        &self,
+        key: String,
+        restore: impl FnOnce(S) -> ClientResult<T>,
+    ) -> ClientResult<T> {
+        match self.entries.get(&key) {
+            Some(Recorded::Ok(value)) => {
+                let stored = serde_json::from_value(value.clone())
+                    .map_err(|err| custom(format!("malformed fixture for {key}: {err}")))?;
+
+                restore(stored)
+            }
+            Some(Recorded::Err(err)) => Err(custom(err.clone())),
+            None => Err(custom(format!("no fixture recorded for {key}"))),
+        }
+    }
+}
+
+/// Wraps another [`Rpc`] and records every response it returns, errors included.
+///
+/// Run the code under test against a live node once, save the fixtures and check them
+/// in; [`ReplayRpc`] then serves the same responses without a network.
+pub struct RecordingRpc<R> {
+    inner: R,
+    fixtures: Mutex<Fixtures>,
+}
+
+impl<R: Rpc> RecordingRpc<R> {
+    pub fn new(inner: R) -> Self {
+        let commitment = inner.commitment();
+
+        Self::with_commitment(inner, commitment)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRpc> RecordingRpc<R> {
+    /// Record the responses of an async client, for use with the `nonblocking` API.
+    pub fn new_async(inner: R) -> Self {
+        let commitment = inner.commitment();
+
+        Self::with_commitment(inner, commitment)
+    }
+}
+
+impl<R> RecordingRpc<R> {
+    fn with_commitment(inner: R, commitment: CommitmentConfig) -> Self {
+        let fixtures = Fixtures {
+            commitment,
+            ..Fixtures::default()
+        };
+
+        Self {
+            inner,
+            fixtures: Mutex::new(fixtures),
+        }
+    }
+
+    /// A copy of everything recorded so far.
+    pub fn fixtures(&self) -> Fixtures {
+        self.lock().clone()
+    }
+
+    pub fn into_fixtures(self) -> Fixtures {
+        self.fixtures
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.lock().save(path)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Fixtures> {
+        self.fixtures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn record<T, S: Serialize>(
+        &self,
+        key: String,
+        result: ClientResult<T>,
+        stored: impl FnOnce(&T) -> S,
+    ) -> ClientResult<T> {
+        self.lock().insert(key, &result, stored);
+
+        result
+    }
+}
+
+impl<R: Rpc> Rpc for RecordingRpc<R> {
+    fn commitment(&self) -> CommitmentConfig {
+        self.inner.commitment()
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.record(
+            account_key(pubkey),
+            self.inner.get_account(pubkey),
+            |account| ui_account(pubkey, account),
+        )
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+        self.record(
+            multiple_accounts_key(pubkeys),
+            self.inner.get_multiple_accounts(pubkeys),
+            |accounts| {
+                pubkeys
+                    .iter()
+                    .zip(accounts)
+                    .map(|(pubkey, account)| {
+                        account.as_ref().map(|account| ui_account(pubkey, account))
+                    })
+                    .collect::<Vec<_>>()
+            },
+        )
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.record(
+            program_accounts_key(program_id, &config),
+            self.inner
+                .get_program_accounts_with_config(program_id, config),
+            |accounts| {
+                accounts
+                    .iter()
+                    .map(|(pubkey, account)| RpcKeyedAccount {
+                        pubkey: pubkey.to_string(),
+                        account: ui_account(pubkey, account),
+                    })
+                    .collect::<Vec<_>>()
+            },
+        )
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.record(
+            rent_exemption_key(data_len),
+            self.inner.get_minimum_balance_for_rent_exemption(data_len),
+            |lamports| *lamports,
+        )
+    }
+
+    fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>> {
+        self.record(
+            token_largest_accounts_key(mint),
+            self.inner.get_token_largest_accounts(mint),
+            Clone::clone,
+        )
+    }
+
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        self.record(
+            latest_blockhash_key(commitment),
+            self.inner.get_latest_blockhash_with_commitment(commitment),
+            |(blockhash, last_valid_block_height)| RpcBlockhash {
+                blockhash: blockhash.to_string(),
+                last_valid_block_height: *last_valid_block_height,
+            },
+        )
+    }
+
+    fn get_block_height_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.record(
+            block_height_key(commitment),
+            self.inner.get_block_height_with_commitment(commitment),
+            |height| *height,
+        )
+    }
+
+    fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        self.record(
+            slot_key(commitment),
+            self.inner.get_slot_with_commitment(commitment),
+            |slot| *slot,
+        )
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.record(
+            signature_statuses_key(signatures),
+            self.inner.get_signature_statuses(signatures),
+            Clone::clone,
+        )
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.record(
+            signature_statuses_with_history_key(signatures),
+            self.inner.get_signature_statuses_with_history(signatures),
+            Clone::clone,
+        )
+    }
+
+    fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.record(
+            prioritization_fees_key(addresses),
+            self.inner.get_recent_prioritization_fees(addresses),
+            Clone::clone,
+        )
+    }
+
+    fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.record(
+            simulate_key(transaction, &config),
+            self.inner
+                .simulate_transaction_with_config(transaction, config),
+            Clone::clone,
+        )
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.record(
+            send_key(transaction, &config),
+            self.inner.send_transaction_with_config(transaction, config),
+            ToString::to_string,
+        )
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.record(
+            send_and_confirm_key(transaction),
+            self.inner.send_and_confirm_transaction(transaction),
+            ToString::to_string,
+        )
+    }
+}
+
+/// Serves responses recorded by a [`RecordingRpc`].
+///
+/// A call fails with `ClientErrorKind::Custom` if it wasn't recorded with exactly the
+/// same parameters. Transactions are matched on their message, so they have to be built
+/// from the same keys and blockhash as when they were recorded.
+#[derive(Debug, Clone)]
+pub struct ReplayRpc {
+    fixtures: Fixtures,
+}
+
+impl ReplayRpc {
+    pub fn new(fixtures: Fixtures) -> Self {
+        Self { fixtures }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(Fixtures::load(path)?))
+    }
+
+    pub fn fixtures(&self) -> &Fixtures {
+        &self.fixtures
+    }
+}
+
+impl Rpc for ReplayRpc {
+    fn commitment(&self) -> CommitmentConfig {
+        self.fixtures.commitment
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.fixtures.get(account_key(pubkey), from_ui_account)
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+        self.fixtures.get(
+            multiple_accounts_key(pubkeys),
+            |accounts: Vec<Option<UiAccount>>| {
+                accounts
+                    .into_iter()
+                    .map(|account| account.map(from_ui_account).transpose())
+                    .collect()
+            },
+        )
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.fixtures.get(
+            program_accounts_key(program_id, &config),
+            |accounts: Vec<RpcKeyedAccount>| {
+                accounts
+                    .into_iter()
+                    .map(|keyed| {
+                        Ok((
+                            parse_pubkey(&keyed.pubkey)?,
+                            from_ui_account(keyed.account)?,
+                        ))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.fixtures.get(rent_exemption_key(data_len), Ok)
+    }
+
+    fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>> {
+        self.fixtures.get(token_largest_accounts_key(mint), Ok)
+    }
+
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        self.fixtures.get(
+            latest_blockhash_key(commitment),
+            |recorded: RpcBlockhash| {
+                let blockhash = Hash::from_str(&recorded.blockhash)
+                    .map_err(|err| custom(format!("malformed blockhash fixture: {err}")))?;
+
+                Ok((blockhash, recorded.last_valid_block_height))
+            },
+        )
+    }
+
+    fn get_block_height_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.fixtures.get(block_height_key(commitment), Ok)
+    }
+
+    fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        self.fixtures.get(slot_key(commitment), Ok)
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.fixtures.get(signature_statuses_key(signatures), Ok)
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.fixtures
+            .get(signature_statuses_with_history_key(signatures), Ok)
+    }
+
+    fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.fixtures.get(prioritization_fees_key(addresses), Ok)
+    }
+
+    fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.fixtures.get(
+            simulate_key(transaction, &config),
+            |response: Response<_>| Ok(response),
+        )
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.fixtures
+            .get(send_key(transaction, &config), |sig: String| {
+                parse_signature(&sig)
+            })
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.fixtures
+            .get(send_and_confirm_key(transaction), |sig: String| {
+                parse_signature(&sig)
+            })
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl<R: AsyncRpc> AsyncRpc for RecordingRpc<R> {
+    fn commitment(&self) -> CommitmentConfig {
+        self.inner.commitment()
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.record(
+            account_key(pubkey),
+            self.inner.get_account(pubkey).await,
+            |account| ui_account(pubkey, account),
+        )
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.record(
+            multiple_accounts_key(pubkeys),
+            self.inner.get_multiple_accounts(pubkeys).await,
+            |accounts| {
+                pubkeys
+                    .iter()
+                    .zip(accounts)
+                    .map(|(pubkey, account)| {
+                        account.as_ref().map(|account| ui_account(pubkey, account))
+                    })
+                    .collect::<Vec<_>>()
+            },
+        )
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.record(
+            program_accounts_key(program_id, &config),
+            self.inner
+                .get_program_accounts_with_config(program_id, config)
+                .await,
+            |accounts| {
+                accounts
+                    .iter()
+                    .map(|(pubkey, account)| RpcKeyedAccount {
+                        pubkey: pubkey.to_string(),
+                        account: ui_account(pubkey, account),
+                    })
+                    .collect::<Vec<_>>()
+            },
+        )
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.record(
+            rent_exemption_key(data_len),
+            self.inner
+                .get_minimum_balance_for_rent_exemption(data_len)
+                .await,
+            |lamports| *lamports,
+        )
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>> {
+        self.record(
+            token_largest_accounts_key(mint),
+            self.inner.get_token_largest_accounts(mint).await,
+            Clone::clone,
+        )
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        self.record(
+            latest_blockhash_key(commitment),
+            self.inner
+                .get_latest_blockhash_with_commitment(commitment)
+                .await,
+            |(blockhash, last_valid_block_height)| RpcBlockhash {
+                blockhash: blockhash.to_string(),
+                last_valid_block_height: *last_valid_block_height,
+            },
+        )
+    }
+
+    async fn get_block_height_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        self.record(
+            block_height_key(commitment),
+            self.inner
+                .get_block_height_with_commitment(commitment)
+                .await,
+            |height| *height,
+        )
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        self.record(
+            slot_key(commitment),
+            self.inner.get_slot_with_commitment(commitment).await,
+            |slot| *slot,
+        )
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.record(
+            signature_statuses_key(signatures),
+            self.inner.get_signature_statuses(signatures).await,
+            Clone::clone,
+        )
+    }
+
+    async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.record(
+            signature_statuses_with_history_key(signatures),
+            self.inner
+                .get_signature_statuses_with_history(signatures)
+                .await,
+            Clone::clone,
+        )
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.record(
+            prioritization_fees_key(addresses),
+            self.inner.get_recent_prioritization_fees(addresses).await,
+            Clone::clone,
+        )
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.record(
+            simulate_key(transaction, &config),
+            self.inner
+                .simulate_transaction_with_config(transaction, config)
+                .await,
+            Clone::clone,
+        )
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.record(
+            send_key(transaction, &config),
+            self.inner
+                .send_transaction_with_config(transaction, config)
+                .await,
+            ToString::to_string,
+        )
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.record(
+            send_and_confirm_key(transaction),
+            self.inner.send_and_confirm_transaction(transaction).await,
+            ToString::to_string,
+        )
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl AsyncRpc for ReplayRpc {
+    fn commitment(&self) -> CommitmentConfig {
+        self.fixtures.commitment
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.fixtures.get(account_key(pubkey), from_ui_account)
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        self.fixtures.get(
+            multiple_accounts_key(pubkeys),
+            |accounts: Vec<Option<UiAccount>>| {
+                accounts
+                    .into_iter()
+                    .map(|account| account.map(from_ui_account).transpose())
+                    .collect()
+            },
+        )
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.fixtures.get(
+            program_accounts_key(program_id, &config),
+            |accounts: Vec<RpcKeyedAccount>| {
+                accounts
+                    .into_iter()
+                    .map(|keyed| {
+                        Ok((
+                            parse_pubkey(&keyed.pubkey)?,
+                            from_ui_account(keyed.account)?,
+                        ))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.fixtures.get(rent_exemption_key(data_len), Ok)
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>> {
+        self.fixtures.get(token_largest_accounts_key(mint), Ok)
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        self.fixtures.get(
+            latest_blockhash_key(commitment),
+            |recorded: RpcBlockhash| {
+                let blockhash = Hash::from_str(&recorded.blockhash)
+                    .map_err(|err| custom(format!("malformed blockhash fixture: {err}")))?;
+
+                Ok((blockhash, recorded.last_valid_block_height))
+            },
+        )
+    }
+
+    async fn get_block_height_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        self.fixtures.get(block_height_key(commitment), Ok)
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        self.fixtures.get(slot_key(commitment), Ok)
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.fixtures.get(signature_statuses_key(signatures), Ok)
+    }
+
+    async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.fixtures
+            .get(signature_statuses_with_history_key(signatures), Ok)
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.fixtures.get(prioritization_fees_key(addresses), Ok)
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.fixtures.get(
+            simulate_key(transaction, &config),
+            |response: Response<_>| Ok(response),
+        )
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.fixtures
+            .get(send_key(transaction, &config), |sig: String| {
+                parse_signature(&sig)
+            })
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.fixtures
+            .get(send_and_confirm_key(transaction), |sig: String| {
+                parse_signature(&sig)
+            })
+    }
+}
+
+// Keys are the RPC method name followed by its parameters as JSON.
+fn key(method: &str, params: Value) -> String {
+    format!("{method} {params}")
+}
+
+fn account_key(pubkey: &Pubkey) -> String {
+    key("getAccountInfo", json!([pubkey.to_string()]))
+}
+
+fn multiple_accounts_key(pubkeys: &[Pubkey]) -> String {
+    key("getMultipleAccounts", json!([strings(pubkeys)]))
+}
+
+fn program_accounts_key(program_id: &Pubkey, config: &RpcProgramAccountsConfig) -> String {
+    key(
+        "getProgramAccounts",
+        json!([program_id.to_string(), config]),
+    )
+}
+
+fn rent_exemption_key(data_len: usize) -> String {
+    key("getMinimumBalanceForRentExemption", json!([data_len]))
+}
+
+fn token_largest_accounts_key(mint: &Pubkey) -> String {
+    key("getTokenLargestAccounts", json!([mint.to_string()]))
+}
+
+fn latest_blockhash_key(commitment: CommitmentConfig) -> String {
+    key("getLatestBlockhash", json!([commitment]))
+}
+
+fn block_height_key(commitment: CommitmentConfig) -> String {
+    key("getBlockHeight", json!([commitment]))
+}
+
+fn slot_key(commitment: CommitmentConfig) -> String {
+    key("getSlot", json!([commitment]))
+}
+
+fn signature_statuses_key(signatures: &[Signature]) -> String {
+    key("getSignatureStatuses", json!([strings(signatures)]))
+}
+
+fn signature_statuses_with_history_key(signatures: &[Signature]) -> String {
+    key(
+        "getSignatureStatuses",
+        json!([strings(signatures), { "searchTransactionHistory": true }]),
+    )
+}
+
+fn prioritization_fees_key(addresses: &[Pubkey]) -> String {
+    key("getRecentPrioritizationFees", json!([strings(addresses)]))
+}
+
+fn simulate_key(
+    transaction: &VersionedTransaction,
+    config: &RpcSimulateTransactionConfig,
+) -> String {
+    key(
+        "simulateTransaction",
+        json!([encode_message(transaction), config]),
+    )
+}
+
+fn send_key(transaction: &VersionedTransaction, config: &RpcSendTransactionConfig) -> String {
+    key(
+        "sendTransaction",
+        json!([encode_message(transaction), config]),
+    )
+}
+
+fn send_and_confirm_key(transaction: &VersionedTransaction) -> String {
+    key(
+        "sendAndConfirmTransaction",
+        json!([encode_message(transaction)]),
+    )
+}
+
+fn strings<T: ToString>(items: &[T]) -> Vec<String> {
+    items.iter().map(ToString::to_string).collect()
+}
+
+/// Signatures change every time a transaction is signed with a fresh keypair, so only
+/// the message is part of the key.
+fn encode_message(transaction: &VersionedTransaction) -> String {
+    BASE64_STANDARD.encode(transaction.message.serialize())
+}
+
+fn ui_account(pubkey: &Pubkey, account: &Account) -> UiAccount {
+    encode_ui_account(pubkey, account, UiAccountEncoding::Base64, None, None)
+}
+
+fn from_ui_account(account: UiAccount) -> ClientResult<Account> {
+    account
+        .decode()
+        .ok_or_else(|| custom("malformed account fixture".to_string()))
+}
+
+fn parse_pubkey(pubkey: &str) -> ClientResult<Pubkey> {
+    Pubkey::from_str(pubkey).map_err(|err| custom(format!("malformed pubkey fixture: {err}")))
+}
+
+fn parse_signature(signature: &str) -> ClientResult<Signature> {
+    Signature::from_str(signature)
+        .map_err(|err| custom(format!("malformed signature fixture: {err}")))
+}
+
+fn custom(message: String) -> ClientError {
+    ClientError::from(ClientErrorKind::Custom(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::{
+        accounts::Metadata,
+        types::{Creator, Key},
+    };
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+        rpc_response::RpcResponseContext,
+    };
+    use solana_sdk::signature::Keypair;
+
+    use crate::{
+        decode::decode_metadata_from_mint,
+        derive::derive_metadata_pda,
+        snapshot::get_metadata_accounts_by_creator,
+        transaction::ComputeBudget,
+        update::{update_asset_ix, UpdateAssetArgs, V1UpdateArgs},
+    };
+
+    fn metadata_account(mint: Pubkey, creator: Pubkey) -> Account {
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: creator,
+            mint,
+            name: "Fixture".to_string(),
+            symbol: "FIX".to_string(),
+            uri: "https://example.com/fixture.json".to_string(),
+            seller_fee_basis_points: 500,
+            creators: Some(vec![Creator {
+                address: creator,
+                verified: true,
+                share: 100,
+            }]),
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+
+        let data = metadata.try_to_vec().unwrap();
+
+        Account {
+            lamports: 5_616_720,
+            data,
+            owner: mpl_token_metadata::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let metadata_pda = derive_metadata_pda(&mint);
+        let account = metadata_account(mint, creator);
+
+        // The mock client serves each mocked response only once, so give each call
+        // that reads the metadata account its own client.
+        let mock = |request: RpcRequest, value: Value| {
+            let mut mocks = Mocks::default();
+            mocks.insert(request, value);
+            RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+        };
+        let account_info = || {
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: Some(ui_account(&metadata_pda, &account)),
+            })
+            .unwrap()
+        };
+        let program_accounts = serde_json::to_value(vec![RpcKeyedAccount {
+            pubkey: metadata_pda.to_string(),
+            account: ui_account(&metadata_pda, &account),
+        }])
+        .unwrap();
+
+        let authority = Keypair::new();
+        let update_args = || UpdateAssetArgs::V1 {
+            payer: None,
+            authority: &authority,
+            mint,
+            token: None::<Pubkey>,
+            delegate_record: None::<Pubkey>,
+            update_args: V1UpdateArgs {
+                is_mutable: Some(false),
+                ..V1UpdateArgs::default()
+            },
+            compute_budget: ComputeBudget::default(),
+        };
+
+        let recorder = RecordingRpc::new(mock(RpcRequest::GetAccountInfo, account_info()));
+        let metadata = decode_metadata_from_mint(&recorder, mint).unwrap();
+        let mut fixtures = recorder.into_fixtures();
+
+        let recorder = RecordingRpc::new(mock(RpcRequest::GetProgramAccounts, program_accounts));
+        let by_creator =
+            get_metadata_accounts_by_creator(&recorder, &creator.to_string(), 0).unwrap();
+        fixtures
+            .entries
+            .append(&mut recorder.into_fixtures().entries);
+
+        let recorder = RecordingRpc::new(mock(RpcRequest::GetAccountInfo, account_info()));
+        let ix = update_asset_ix(&recorder, update_args()).unwrap();
+        fixtures
+            .entries
+            .append(&mut recorder.into_fixtures().entries);
+
+        let path = std::env::temp_dir().join(format!("metaboss-fixtures-{mint}.json"));
+        fixtures.save(&path).unwrap();
+        let replay = ReplayRpc::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.fixtures(), &fixtures);
+        assert_eq!(decode_metadata_from_mint(&replay, mint).unwrap(), metadata);
+        assert_eq!(
+            get_metadata_accounts_by_creator(&replay, &creator.to_string(), 0).unwrap(),
+            by_creator
+        );
+        assert_eq!(by_creator, vec![(metadata_pda, account)]);
+        assert_eq!(update_asset_ix(&replay, update_args()).unwrap(), ix);
+
+        // Anything that wasn't recorded fails instead of reaching the network.
+        assert!(decode_metadata_from_mint(&replay, Pubkey::new_unique()).is_err());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_record_and_replay_async() {
+        use solana_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+
+        use crate::nonblocking::decode::decode_metadata_from_mint as decode_metadata_async;
+
+        let mint = Pubkey::new_unique();
+        let metadata_pda = derive_metadata_pda(&mint);
+        let account = metadata_account(mint, Pubkey::new_unique());
+
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: Some(ui_account(&metadata_pda, &account)),
+            })
+            .unwrap(),
+        );
+        let recorder = RecordingRpc::new_async(AsyncRpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            mocks,
+        ));
+        let metadata = decode_metadata_async(&recorder, mint).await.unwrap();
+
+        let replay = ReplayRpc::new(recorder.into_fixtures());
+
+        assert_eq!(
+            decode_metadata_async(&replay, mint).await.unwrap(),
+            metadata
+        );
+        // Fixtures recorded through either trait replay through both.
+        assert_eq!(decode_metadata_from_mint(&replay, mint).unwrap(), metadata);
+        assert!(decode_metadata_async(&replay, Pubkey::new_unique())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,431 @@
+//! The RPC calls the library makes, abstracted so operations can run against something
+//! other than a live `RpcClient`, such as an in-memory stand-in for tests, a caching
+//! layer or a client that fails over between nodes.
+//!
+//! Every blocking function takes `&(impl Rpc + ?Sized)`, so both `&RpcClient` and
+//! `&dyn Rpc` can be passed. The async API in `nonblocking` takes its async counterpart,
+//! `nonblocking::rpc::Rpc`.
+//!
+//! [`RecordingRpc`] and [`ReplayRpc`] capture responses from a live node into fixture
+//! files and serve them back offline, through either trait.
+
+// The methods mirror `RpcClient`, whose error type is large.
+#![allow(clippy::result_large_err)]
+
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::RpcClient,
+    rpc_config::{
+        RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig,
+    },
+    rpc_response::{
+        Response, RpcPrioritizationFee, RpcResult, RpcSimulateTransactionResult,
+        RpcTokenAccountBalance,
+    },
+};
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{self, VersionedTransaction},
+};
+use solana_transaction_status_client_types::TransactionStatus;
+
+use crate::nft::nft_token_account_request;
+
+mod fixtures;
+
+pub use fixtures::*;
+
+/// RPC methods used by the library.
+///
+/// Legacy transactions are passed as the equivalent `VersionedTransaction`, which
+/// serializes to the same bytes. Errors use the RPC client's error type; implementations
+/// backed by something other than a node can report failures with
+/// `ClientErrorKind::Custom`.
+pub trait Rpc {
+    /// Commitment used by the methods that don't take one explicitly.
+    fn commitment(&self) -> CommitmentConfig {
+        CommitmentConfig::confirmed()
+    }
+
+    /// Fetch an account, failing if it doesn't exist.
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account>;
+
+    fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        self.get_account(pubkey).map(|account| account.data)
+    }
+
+    /// Fetch several accounts at once, with `None` for the ones that don't exist.
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>>;
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>>;
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64>;
+
+    /// Largest token accounts of `mint`, as returned by `getTokenLargestAccounts`.
+    fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>>;
+
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)>;
+
+    fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        self.get_latest_blockhash_with_commitment(self.commitment())
+            .map(|(blockhash, _)| blockhash)
+    }
+
+    fn get_block_height_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64>;
+
+    fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot>;
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>>;
+
+    /// Like [`Rpc::get_signature_statuses`] but also searches the ledger history, not just
+    /// the recent status cache.
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>>;
+
+    fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        let statuses = self.get_signature_statuses(std::slice::from_ref(signature))?;
+
+        Ok(statuses
+            .value
+            .into_iter()
+            .next()
+            .flatten()
+            .map(|status| status.status))
+    }
+
+    fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>>;
+
+    fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult>;
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> ClientResult<Signature> {
+        self.send_transaction_with_config(transaction, RpcSendTransactionConfig::default())
+    }
+
+    /// Send a transaction and wait until it reaches the client's commitment.
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature>;
+
+    /// Like [`Rpc::send_and_confirm_transaction`], showing progress where the
+    /// implementation supports it.
+    fn send_and_confirm_transaction_with_spinner(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.send_and_confirm_transaction(transaction)
+    }
+}
+
+impl Rpc for RpcClient {
+    fn commitment(&self) -> CommitmentConfig {
+        RpcClient::commitment(self)
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        RpcClient::get_account(self, pubkey)
+    }
+
+    fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        RpcClient::get_account_data(self, pubkey)
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+        RpcClient::get_multiple_accounts(self, pubkeys)
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        RpcClient::get_program_accounts_with_config(self, program_id, config)
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        RpcClient::get_minimum_balance_for_rent_exemption(self, data_len)
+    }
+
+    fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> ClientResult<Vec<RpcTokenAccountBalance>> {
+        // Sent as a custom request so the commitment is always confirmed, regardless
+        // of the client's.
+        let (request, params) = nft_token_account_request(&mint.to_string());
+
+        let response: Response<Vec<RpcTokenAccountBalance>> = self.send(request, params)?;
+
+        Ok(response.value)
+    }
+
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        RpcClient::get_latest_blockhash_with_commitment(self, commitment)
+    }
+
+    fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        RpcClient::get_latest_blockhash(self)
+    }
+
+    fn get_block_height_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        RpcClient::get_block_height_with_commitment(self, commitment)
+    }
+
+    fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        RpcClient::get_slot_with_commitment(self, commitment)
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        RpcClient::get_signature_statuses(self, signatures)
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        RpcClient::get_signature_statuses_with_history(self, signatures)
+    }
+
+    fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        RpcClient::get_signature_status(self, signature)
+    }
+
+    fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        RpcClient::get_recent_prioritization_fees(self, addresses)
+    }
+
+    fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        RpcClient::simulate_transaction_with_config(self, transaction, config)
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_transaction_with_config(self, transaction, config)
+    }
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> ClientResult<Signature> {
+        RpcClient::send_transaction(self, transaction)
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_and_confirm_transaction(self, transaction)
+    }
+
+    fn send_and_confirm_transaction_with_spinner(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_and_confirm_transaction_with_spinner(self, transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::client_error::{ClientError, ClientErrorKind};
+    use solana_program::program_pack::Pack;
+    use spl_token::state::Mint;
+    use std::collections::HashMap;
+
+    use crate::decode::decode_mint;
+
+    /// Serves accounts from memory and fails everything else.
+    struct InMemoryRpc {
+        accounts: HashMap<Pubkey, Account>,
+    }
+
+    fn unsupported<T>() -> ClientResult<T> {
+        Err(ClientError::from(ClientErrorKind::Custom(
+            "not supported".to_string(),
+        )))
+    }
+
+    impl Rpc for InMemoryRpc {
+        fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+            self.accounts.get(pubkey).cloned().ok_or_else(|| {
+                ClientError::from(ClientErrorKind::Custom(format!("{pubkey} not found")))
+            })
+        }
+
+        fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+            Ok(pubkeys
+                .iter()
+                .map(|pubkey| self.accounts.get(pubkey).cloned())
+                .collect())
+        }
+
+        fn get_program_accounts_with_config(
+            &self,
+            program_id: &Pubkey,
+            _config: RpcProgramAccountsConfig,
+        ) -> ClientResult<Vec<(Pubkey, Account)>> {
+            Ok(self
+                .accounts
+                .iter()
+                .filter(|(_, account)| account.owner == *program_id)
+                .map(|(pubkey, account)| (*pubkey, account.clone()))
+                .collect())
+        }
+
+        fn get_minimum_balance_for_rent_exemption(&self, _data_len: usize) -> ClientResult<u64> {
+            unsupported()
+        }
+
+        fn get_token_largest_accounts(
+            &self,
+            _mint: &Pubkey,
+        ) -> ClientResult<Vec<RpcTokenAccountBalance>> {
+            unsupported()
+        }
+
+        fn get_latest_blockhash_with_commitment(
+            &self,
+            _commitment: CommitmentConfig,
+        ) -> ClientResult<(Hash, u64)> {
+            unsupported()
+        }
+
+        fn get_block_height_with_commitment(
+            &self,
+            _commitment: CommitmentConfig,
+        ) -> ClientResult<u64> {
+            unsupported()
+        }
+
+        fn get_slot_with_commitment(&self, _commitment: CommitmentConfig) -> ClientResult<Slot> {
+            unsupported()
+        }
+
+        fn get_signature_statuses(
+            &self,
+            _signatures: &[Signature],
+        ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+            unsupported()
+        }
+
+        fn get_signature_statuses_with_history(
+            &self,
+            _signatures: &[Signature],
+        ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+            unsupported()
+        }
+
+        fn get_recent_prioritization_fees(
+            &self,
+            _addresses: &[Pubkey],
+        ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+            unsupported()
+        }
+
+        fn simulate_transaction_with_config(
+            &self,
+            _transaction: &VersionedTransaction,
+            _config: RpcSimulateTransactionConfig,
+        ) -> RpcResult<RpcSimulateTransactionResult> {
+            unsupported()
+        }
+
+        fn send_transaction_with_config(
+            &self,
+            _transaction: &VersionedTransaction,
+            _config: RpcSendTransactionConfig,
+        ) -> ClientResult<Signature> {
+            unsupported()
+        }
+
+        fn send_and_confirm_transaction(
+            &self,
+            _transaction: &VersionedTransaction,
+        ) -> ClientResult<Signature> {
+            unsupported()
+        }
+    }
+
+    #[test]
+    fn test_decode_through_dyn_rpc() {
+        let mint_address = Pubkey::new_unique();
+        let mint = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+
+        let rpc = InMemoryRpc {
+            accounts: HashMap::from([(
+                mint_address,
+                Account {
+                    lamports: 1,
+                    data,
+                    owner: spl_token::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )]),
+        };
+        let client: &dyn Rpc = &rpc;
+
+        assert_eq!(decode_mint(client, mint_address).unwrap(), mint);
+        assert!(decode_mint(client, Pubkey::new_unique()).is_err());
+        assert!(client.get_latest_blockhash().is_err());
+    }
+}
@@ -0,0 +1,355 @@
+//! Token Auth Rules `RuleSet` accounts.
+//!
+//! `mpl-token-auth-rules` is pinned to an old Solana SDK, so the account layout and the
+//! V1 rule types are defined here. A RuleSet account is laid out as
+//!
+//! ```text
+//! | header | revision 0 | revision 1 | ... | revision map |
+//! ```
+//!
+//! The borsh header holds the account key and the location of the revision map. Each
+//! revision is a library version byte followed by the MessagePack-encoded [`RuleSetV1`],
+//! and the revision map is a version byte followed by the borsh-encoded start of every
+//! revision.
+//!
+//! RuleSets are built from [`RuleV1`] trees and published with [`publish_rule_set`].
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::types::ProgrammableConfig;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    constants::AUTH_RULES_PROGRAM_ID,
+    decode::{decode_metadata_from_mint, errors::DecodeError, ToPubkey},
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+};
+
+mod publish;
+
+pub use publish::*;
+
+/// Library version byte of MessagePack-encoded `RuleSetV1` revisions.
+pub const RULE_SET_LIB_VERSION: u8 = 1;
+/// Version byte of the revision map.
+pub const RULE_SET_REV_MAP_VERSION: u8 = 1;
+/// Length of the borsh [`RuleSetHeader`].
+pub const RULE_SET_HEADER_LEN: usize = 1 + 8;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSetKey {
+    Uninitialized,
+    RuleSet,
+    Frequency,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuleSetHeader {
+    pub key: RuleSetKey,
+    pub rev_map_version_location: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSetRevisionMapV1 {
+    /// Start of each revision's library version byte, oldest first.
+    pub rule_set_revisions: Vec<u64>,
+}
+
+/// Which revision of a RuleSet to decode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RuleSetRevision {
+    #[default]
+    Latest,
+    /// Zero-based revision number, as stored in a token record's `rule_set_revision`.
+    Number(usize),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuleSetV1 {
+    pub lib_version: u8,
+    pub owner: Pubkey,
+    pub rule_set_name: String,
+    /// Rules keyed by operation, e.g. `Transfer:Owner` or `Delegate:Sale`.
+    pub operations: BTreeMap<String, RuleV1>,
+}
+
+/// A rule of a V1 RuleSet. Fields name the payload entries the rule reads.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RuleV1 {
+    All {
+        rules: Vec<RuleV1>,
+    },
+    Any {
+        rules: Vec<RuleV1>,
+    },
+    Not {
+        rule: Box<RuleV1>,
+    },
+    AdditionalSigner {
+        account: Pubkey,
+    },
+    PubkeyMatch {
+        pubkey: Pubkey,
+        field: String,
+    },
+    PubkeyListMatch {
+        pubkeys: Vec<Pubkey>,
+        field: String,
+    },
+    PubkeyTreeMatch {
+        root: [u8; 32],
+        pubkey_field: String,
+        proof_field: String,
+    },
+    PDAMatch {
+        program: Option<Pubkey>,
+        pda_field: String,
+        seeds_field: String,
+    },
+    ProgramOwned {
+        program: Pubkey,
+        field: String,
+    },
+    ProgramOwnedList {
+        programs: Vec<Pubkey>,
+        field: String,
+    },
+    ProgramOwnedTree {
+        root: [u8; 32],
+        pubkey_field: String,
+        proof_field: String,
+    },
+    Amount {
+        amount: u64,
+        operator: CompareOp,
+        field: String,
+    },
+    Frequency {
+        authority: Pubkey,
+    },
+    IsWallet {
+        field: String,
+    },
+    Pass,
+    Namespace,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    LtEq,
+    Eq,
+    GtEq,
+    Gt,
+}
+
+pub fn decode_rule_set<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    rule_set: P,
+    revision: RuleSetRevision,
+) -> Result<RuleSetV1> {
+    let pubkey = rule_set.to_pubkey()?;
+    let account = client.get_account(&pubkey)?;
+
+    decode_rule_set_from_bytes(&account.owner, &account.data, revision)
+        .map_err(|e| MetabossError::from(e).for_account(&pubkey))
+}
+
+/// Decode the RuleSet attached to a programmable NFT through its `ProgrammableConfig`.
+/// Returns `None` if the NFT has no RuleSet.
+pub fn decode_rule_set_from_mint<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    mint: P,
+    revision: RuleSetRevision,
+) -> Result<Option<RuleSetV1>> {
+    let metadata = decode_metadata_from_mint(client, mint)?;
+
+    match metadata.programmable_config {
+        Some(ProgrammableConfig::V1 {
+            rule_set: Some(rule_set),
+        }) => decode_rule_set(client, rule_set, revision).map(Some),
+        _ => Ok(None),
+    }
+}
+
+pub fn decode_rule_set_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+    revision: RuleSetRevision,
+) -> Result<RuleSetV1, DecodeError> {
+    let (header, revision_map) = decode_rule_set_revision_map_from_bytes(owner, data)?;
+    let revisions = &revision_map.rule_set_revisions;
+
+    let number = match revision {
+        RuleSetRevision::Latest => revisions
+            .len()
+            .checked_sub(1)
+            .ok_or(DecodeError::RuleSetRevisionNotAvailable)?,
+        RuleSetRevision::Number(number) => number,
+    };
+
+    let start = *revisions
+        .get(number)
+        .ok_or(DecodeError::RuleSetRevisionNotAvailable)?;
+    // A revision ends where the next one, or the revision map, starts.
+    let end = revisions
+        .get(number + 1)
+        .copied()
+        .unwrap_or(header.rev_map_version_location);
+
+    let revision_data = usize::try_from(start)
+        .ok()
+        .zip(usize::try_from(end).ok())
+        .and_then(|(start, end)| data.get(start..end))
+        .ok_or_else(|| {
+            DecodeError::DeserializationFailed(format!(
+                "revision {number} at {start}..{end} is outside the account"
+            ))
+        })?;
+
+    match revision_data.split_first() {
+        Some((&RULE_SET_LIB_VERSION, rule_set)) => rmp_serde::from_slice(rule_set)
+            .map_err(|e| DecodeError::DeserializationFailed(e.to_string())),
+        Some((version, _)) => Err(DecodeError::DeserializationFailed(format!(
+            "unsupported RuleSet library version {version}"
+        ))),
+        None => Err(DecodeError::DeserializationFailed(format!(
+            "revision {number} is empty"
+        ))),
+    }
+}
+
+/// Decode the header and revision map of a RuleSet account, e.g. to count its revisions.
+pub fn decode_rule_set_revision_map_from_bytes(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<(RuleSetHeader, RuleSetRevisionMapV1), DecodeError> {
+    if *owner != AUTH_RULES_PROGRAM_ID {
+        return Err(DecodeError::DeserializationFailed(format!(
+            "account is owned by {owner}, expected {AUTH_RULES_PROGRAM_ID}"
+        )));
+    }
+
+    let header = RuleSetHeader::deserialize(&mut &data[..])
+        .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))?;
+    if header.key != RuleSetKey::RuleSet {
+        return Err(DecodeError::DeserializationFailed(format!(
+            "expected a RuleSet account, found {:?}",
+            header.key
+        )));
+    }
+
+    let rev_map = usize::try_from(header.rev_map_version_location)
+        .ok()
+        .and_then(|location| data.get(location..))
+        .ok_or_else(|| {
+            DecodeError::DeserializationFailed("revision map is outside the account".to_string())
+        })?;
+
+    match rev_map.split_first() {
+        Some((&RULE_SET_REV_MAP_VERSION, mut rev_map)) => {
+            let revision_map = RuleSetRevisionMapV1::deserialize(&mut rev_map)
+                .map_err(|e| DecodeError::DeserializationFailed(e.to_string()))?;
+
+            Ok((header, revision_map))
+        }
+        Some((version, _)) => Err(DecodeError::DeserializationFailed(format!(
+            "unsupported RuleSet revision map version {version}"
+        ))),
+        None => Err(DecodeError::DeserializationFailed(
+            "revision map is empty".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_set(owner: Pubkey, transfer: RuleV1) -> RuleSetV1 {
+        RuleSetV1 {
+            lib_version: RULE_SET_LIB_VERSION,
+            owner,
+            rule_set_name: "royalties".to_string(),
+            operations: BTreeMap::from([
+                ("Transfer:Owner".to_string(), transfer),
+                (
+                    "Delegate:Sale".to_string(),
+                    RuleV1::Not {
+                        rule: Box::new(RuleV1::Pass),
+                    },
+                ),
+            ]),
+        }
+    }
+
+    fn rule_set_account(revisions: &[RuleSetV1]) -> Vec<u8> {
+        let mut data = vec![0; RULE_SET_HEADER_LEN];
+        let mut revision_map = RuleSetRevisionMapV1::default();
+
+        for revision in revisions {
+            revision_map.rule_set_revisions.push(data.len() as u64);
+            data.push(RULE_SET_LIB_VERSION);
+            data.extend(rmp_serde::to_vec(revision).unwrap());
+        }
+
+        let header = RuleSetHeader {
+            key: RuleSetKey::RuleSet,
+            rev_map_version_location: data.len() as u64,
+        };
+        data[..RULE_SET_HEADER_LEN].copy_from_slice(&header.try_to_vec().unwrap());
+        data.push(RULE_SET_REV_MAP_VERSION);
+        data.extend(revision_map.try_to_vec().unwrap());
+
+        data
+    }
+
+    #[test]
+    fn test_decode_rule_set_from_bytes() {
+        let owner = Pubkey::new_unique();
+        let first = rule_set(owner, RuleV1::Pass);
+        let second = rule_set(
+            owner,
+            RuleV1::Any {
+                rules: vec![
+                    RuleV1::ProgramOwnedList {
+                        programs: vec![Pubkey::new_unique()],
+                        field: "Destination".to_string(),
+                    },
+                    RuleV1::Amount {
+                        amount: 1,
+                        operator: CompareOp::LtEq,
+                        field: "Amount".to_string(),
+                    },
+                ],
+            },
+        );
+        let data = rule_set_account(&[first.clone(), second.clone()]);
+
+        assert_eq!(
+            decode_rule_set_from_bytes(&AUTH_RULES_PROGRAM_ID, &data, RuleSetRevision::Latest)
+                .unwrap(),
+            second
+        );
+        assert_eq!(
+            decode_rule_set_from_bytes(&AUTH_RULES_PROGRAM_ID, &data, RuleSetRevision::Number(0))
+                .unwrap(),
+            first
+        );
+        assert!(matches!(
+            decode_rule_set_from_bytes(&AUTH_RULES_PROGRAM_ID, &data, RuleSetRevision::Number(2)),
+            Err(DecodeError::RuleSetRevisionNotAvailable)
+        ));
+        assert!(matches!(
+            decode_rule_set_from_bytes(&Pubkey::new_unique(), &data, RuleSetRevision::Latest),
+            Err(DecodeError::DeserializationFailed(_))
+        ));
+
+        let (_, revision_map) =
+            decode_rule_set_revision_map_from_bytes(&AUTH_RULES_PROGRAM_ID, &data).unwrap();
+        assert_eq!(revision_map.rule_set_revisions.len(), 2);
+    }
+}
@@ -0,0 +1,270 @@
+//! Create and update RuleSets.
+//!
+//! A RuleSet small enough to fit in one transaction is sent in the `CreateOrUpdate`
+//! instruction itself. Larger ones are first written to the owner's buffer account in
+//! chunks with `WriteToBuffer`, and `CreateOrUpdate` then copies the buffer into the
+//! RuleSet account.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_sdk::{signature::Signature, signer::Signer};
+
+use super::{RuleSetV1, RuleV1, RULE_SET_LIB_VERSION};
+use crate::{
+    constants::{AUTH_RULES_PROGRAM_ID, SYSTEM_PROGRAM_ID},
+    derive::{derive_rule_set_buffer_pda, derive_rule_set_pda},
+    errors::{MetabossError, Result},
+    rpc::Rpc,
+    transaction::{send_and_confirm_tx_with_compute_budget, ComputeBudget},
+};
+
+/// Largest serialized RuleSet sent directly in `CreateOrUpdate`, and the size of each
+/// `WriteToBuffer` chunk for larger ones.
+pub const MAX_RULE_SET_CHUNK_LEN: usize = 800;
+
+const CREATE_OR_UPDATE_IX: u8 = 0;
+const WRITE_TO_BUFFER_IX: u8 = 2;
+
+#[derive(BorshSerialize)]
+enum CreateOrUpdateArgs {
+    V1 { serialized_rule_set: Vec<u8> },
+}
+
+#[derive(BorshSerialize)]
+enum WriteToBufferArgs {
+    V1 {
+        serialized_rule_set: Vec<u8>,
+        overwrite: bool,
+    },
+}
+
+pub struct PublishRuleSetResult {
+    /// The RuleSet account, which can be assigned to programmable NFTs with
+    /// `V1UpdateArgs { rule_set: RuleSetToggle::Set(rule_set), .. }`.
+    pub rule_set: Pubkey,
+    pub signatures: Vec<Signature>,
+}
+
+impl RuleSetV1 {
+    pub fn new<S: Into<String>>(owner: Pubkey, rule_set_name: S) -> Self {
+        Self {
+            lib_version: RULE_SET_LIB_VERSION,
+            owner,
+            rule_set_name: rule_set_name.into(),
+            operations: Default::default(),
+        }
+    }
+
+    /// Add or replace the rule for `operation`, e.g. `Transfer:Owner`.
+    pub fn with_operation<S: Into<String>>(mut self, operation: S, rule: RuleV1) -> Self {
+        self.operations.insert(operation.into(), rule);
+        self
+    }
+
+    /// The account this RuleSet is stored in.
+    pub fn address(&self) -> Pubkey {
+        derive_rule_set_pda(&self.owner, &self.rule_set_name)
+    }
+
+    /// MessagePack encoding of the RuleSet, as the program stores it.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec(self)
+            .map_err(|e| MetabossError::validation(format!("failed to serialize RuleSet: {e}")))
+    }
+}
+
+pub fn create_or_update_rule_set_ix(
+    payer: &Pubkey,
+    rule_set: &Pubkey,
+    serialized_rule_set: Vec<u8>,
+    buffer: Option<Pubkey>,
+) -> Instruction {
+    let mut data = vec![CREATE_OR_UPDATE_IX];
+    data.extend(
+        CreateOrUpdateArgs::V1 {
+            serialized_rule_set,
+        }
+        .try_to_vec()
+        .expect("serializing into a Vec cannot fail"),
+    );
+
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*rule_set, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+    if let Some(buffer) = buffer {
+        accounts.push(AccountMeta::new_readonly(buffer, false));
+    }
+
+    Instruction {
+        program_id: AUTH_RULES_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Write a chunk of a serialized RuleSet to `payer`'s buffer, replacing its contents if
+/// `overwrite` is set and appending to them otherwise.
+pub fn write_to_rule_set_buffer_ix(payer: &Pubkey, chunk: Vec<u8>, overwrite: bool) -> Instruction {
+    let mut data = vec![WRITE_TO_BUFFER_IX];
+    data.extend(
+        WriteToBufferArgs::V1 {
+            serialized_rule_set: chunk,
+            overwrite,
+        }
+        .try_to_vec()
+        .expect("serializing into a Vec cannot fail"),
+    );
+
+    Instruction {
+        program_id: AUTH_RULES_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(derive_rule_set_buffer_pda(payer), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// Instructions that publish `rule_set`, one per transaction and in order. The RuleSet
+/// owner pays for and signs all of them.
+pub fn publish_rule_set_ixs(rule_set: &RuleSetV1) -> Result<Vec<Instruction>> {
+    let owner = rule_set.owner;
+    let serialized = rule_set.to_msgpack()?;
+
+    if serialized.len() <= MAX_RULE_SET_CHUNK_LEN {
+        return Ok(vec![create_or_update_rule_set_ix(
+            &owner,
+            &rule_set.address(),
+            serialized,
+            None,
+        )]);
+    }
+
+    let mut ixs: Vec<Instruction> = serialized
+        .chunks(MAX_RULE_SET_CHUNK_LEN)
+        .enumerate()
+        .map(|(i, chunk)| write_to_rule_set_buffer_ix(&owner, chunk.to_vec(), i == 0))
+        .collect();
+    ixs.push(create_or_update_rule_set_ix(
+        &owner,
+        &rule_set.address(),
+        Vec::new(),
+        Some(derive_rule_set_buffer_pda(&owner)),
+    ));
+
+    Ok(ixs)
+}
+
+/// Create `rule_set`, or add it as a new revision if it already exists.
+///
+/// The program only accepts RuleSets from their owner, so `owner` must match
+/// `rule_set.owner`.
+pub fn publish_rule_set(
+    client: &(impl Rpc + ?Sized),
+    owner: &dyn Signer,
+    rule_set: &RuleSetV1,
+    compute_budget: &ComputeBudget,
+) -> Result<PublishRuleSetResult> {
+    if owner.pubkey() != rule_set.owner {
+        return Err(MetabossError::validation(format!(
+            "RuleSet is owned by {}, but {} is signing",
+            rule_set.owner,
+            owner.pubkey()
+        )));
+    }
+
+    let signatures = publish_rule_set_ixs(rule_set)?
+        .into_iter()
+        .map(|ix| send_and_confirm_tx_with_compute_budget(client, &[owner], &[ix], compute_budget))
+        .collect::<Result<_>>()?;
+
+    Ok(PublishRuleSetResult {
+        rule_set: rule_set.address(),
+        signatures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Decode the serialized RuleSet from CreateOrUpdate or WriteToBuffer data.
+    fn serialized_rule_set(data: &[u8]) -> &[u8] {
+        let len = u32::from_le_bytes(data[2..6].try_into().unwrap()) as usize;
+        &data[6..6 + len]
+    }
+
+    #[test]
+    fn test_publish_small_rule_set() {
+        let owner = Pubkey::new_unique();
+        let rule_set = RuleSetV1::new(owner, "royalties").with_operation(
+            "Transfer:Owner",
+            RuleV1::ProgramOwnedList {
+                programs: vec![Pubkey::new_unique()],
+                field: "Destination".to_string(),
+            },
+        );
+
+        let ixs = publish_rule_set_ixs(&rule_set).unwrap();
+
+        assert_eq!(ixs.len(), 1);
+        assert_eq!(ixs[0].program_id, AUTH_RULES_PROGRAM_ID);
+        assert_eq!(ixs[0].data[..2], [CREATE_OR_UPDATE_IX, 0]);
+        assert_eq!(ixs[0].accounts.len(), 3);
+        assert_eq!(
+            ixs[0].accounts[1].pubkey,
+            derive_rule_set_pda(&owner, "royalties")
+        );
+        assert_eq!(
+            rmp_serde::from_slice::<RuleSetV1>(serialized_rule_set(&ixs[0].data)).unwrap(),
+            rule_set
+        );
+        assert_eq!(rule_set.lib_version, RULE_SET_LIB_VERSION);
+    }
+
+    #[test]
+    fn test_publish_large_rule_set() {
+        let owner = Pubkey::new_unique();
+        let rule_set = RuleSetV1::new(owner, "allow-list").with_operation(
+            "Transfer:Owner",
+            RuleV1::PubkeyListMatch {
+                pubkeys: (0..40).map(|_| Pubkey::new_unique()).collect(),
+                field: "Destination".to_string(),
+            },
+        );
+        let serialized = rule_set.to_msgpack().unwrap();
+        assert!(serialized.len() > MAX_RULE_SET_CHUNK_LEN);
+
+        let ixs = publish_rule_set_ixs(&rule_set).unwrap();
+        let (create, writes) = ixs.split_last().unwrap();
+
+        assert_eq!(
+            writes.len(),
+            serialized.len().div_ceil(MAX_RULE_SET_CHUNK_LEN)
+        );
+        let written: Vec<u8> = writes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, ix)| {
+                assert_eq!(ix.data[0], WRITE_TO_BUFFER_IX);
+                // Only the first chunk overwrites the buffer.
+                assert_eq!(*ix.data.last().unwrap(), (i == 0) as u8);
+                serialized_rule_set(&ix.data).to_vec()
+            })
+            .collect();
+        assert_eq!(written, serialized);
+
+        assert_eq!(create.data[0], CREATE_OR_UPDATE_IX);
+        assert!(serialized_rule_set(&create.data).is_empty());
+        assert_eq!(
+            create.accounts[3].pubkey,
+            derive_rule_set_buffer_pda(&owner)
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use mpl_token_metadata::{
+    accounts::{Metadata, TokenRecord},
+    types::Key,
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_program::instruction::Instruction;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
+    signer::Signer,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+
+use crate::errors::Result;
+use crate::rpc::Rpc;
+
+/// Outcome of simulating an operation without sending it.
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    pub logs: Vec<String>,
+    pub compute_units: Option<u64>,
+    /// Set when the transaction would fail.
+    pub error: Option<TransactionError>,
+    /// Before and after state of every writable account in the transaction.
+    pub accounts: Vec<AccountDiff>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountDiff {
+    pub address: Pubkey,
+    /// Current on-chain state; `None` if the account doesn't exist yet.
+    pub before: Option<Account>,
+    /// State after the simulated transaction; `None` if it was closed or the simulation
+    /// failed.
+    pub after: Option<Account>,
+    pub decoded_before: Option<TokenMetadataAccount>,
+    pub decoded_after: Option<TokenMetadataAccount>,
+}
+
+/// Token Metadata accounts that an operation commonly modifies.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TokenMetadataAccount {
+    Metadata(Box<Metadata>),
+    TokenRecord(TokenRecord),
+}
+
+/// Simulate `ixs` signed by `signers`, with the first signer paying, and collect the
+/// logs, compute units, error and writable account changes.
+pub fn simulate_tx(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+) -> Result<SimulationResult> {
+    let payer = signers[0].pubkey();
+    let message = Message::new(ixs, Some(&payer));
+
+    let writable: Vec<Pubkey> = message
+        .account_keys
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| message.is_maybe_writable(*i, None))
+        .map(|(_, key)| *key)
+        .collect();
+
+    let before = client.get_multiple_accounts(&writable)?;
+
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(CommitmentConfig::confirmed()),
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: writable.iter().map(|key| key.to_string()).collect(),
+        }),
+        ..Default::default()
+    };
+
+    let tx: VersionedTransaction = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&payer),
+        signers,
+        Hash::new_from_array(Pubkey::default().to_bytes()), // dummy value
+    )
+    .into();
+
+    let sim = client.simulate_transaction_with_config(&tx, config)?.value;
+
+    let after: Vec<Option<Account>> = match sim.accounts {
+        Some(accounts) => accounts
+            .into_iter()
+            .map(|account| account.and_then(|a| a.decode::<Account>()))
+            .collect(),
+        None => vec![None; writable.len()],
+    };
+
+    let accounts = writable
+        .into_iter()
+        .zip(before)
+        .zip(after)
+        .map(|((address, before), after)| AccountDiff {
+            address,
+            decoded_before: before.as_ref().and_then(decode_token_metadata_account),
+            decoded_after: after.as_ref().and_then(decode_token_metadata_account),
+            before,
+            after,
+        })
+        .collect();
+
+    Ok(SimulationResult {
+        logs: sim.logs.unwrap_or_default(),
+        compute_units: sim.units_consumed,
+        error: sim.err,
+        accounts,
+    })
+}
+
+/// Decode a Metadata or TokenRecord account, returning `None` for anything else.
+pub fn decode_token_metadata_account(account: &Account) -> Option<TokenMetadataAccount> {
+    if account.owner != mpl_token_metadata::ID {
+        return None;
+    }
+
+    match *account.data.first()? {
+        k if k == Key::MetadataV1 as u8 => Metadata::safe_deserialize(&account.data)
+            .ok()
+            .map(|md| TokenMetadataAccount::Metadata(Box::new(md))),
+        k if k == Key::TokenRecord as u8 => TokenRecord::from_bytes(&account.data)
+            .ok()
+            .map(TokenMetadataAccount::TokenRecord),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::types::TokenState;
+    use solana_account_decoder::encode_ui_account;
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+        rpc_response::{Response, RpcResponseContext, RpcSimulateTransactionResult},
+    };
+    use solana_sdk::{signature::Keypair, system_instruction::transfer};
+
+    fn token_record_account(state: TokenState) -> Account {
+        let record = TokenRecord {
+            key: Key::TokenRecord,
+            bump: 255,
+            state,
+            rule_set_revision: None,
+            delegate: None,
+            delegate_role: None,
+            locked_transfer: None,
+        };
+
+        let mut data = record.try_to_vec().unwrap();
+        data.resize(TokenRecord::LEN, 0);
+
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: mpl_token_metadata::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_decode_token_metadata_account() {
+        let account = token_record_account(TokenState::Locked);
+
+        match decode_token_metadata_account(&account) {
+            Some(TokenMetadataAccount::TokenRecord(record)) => {
+                assert_eq!(record.state, TokenState::Locked)
+            }
+            other => panic!("unexpected decode result: {other:?}"),
+        }
+
+        let foreign = Account {
+            owner: Pubkey::new_unique(),
+            ..account
+        };
+        assert_eq!(decode_token_metadata_account(&foreign), None);
+    }
+
+    #[test]
+    fn test_simulate_tx_collects_account_diffs() {
+        let payer = Keypair::new();
+        let record = Pubkey::new_unique();
+        // The record isn't really touched by a transfer; it only needs to be writable.
+        let mut ix = transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        ix.accounts
+            .push(solana_program::instruction::AccountMeta::new(record, false));
+
+        let before = token_record_account(TokenState::Unlocked);
+        let after = token_record_account(TokenState::Locked);
+
+        let ui = |account: &Account| {
+            serde_json::to_value(encode_ui_account(
+                &record,
+                account,
+                UiAccountEncoding::Base64,
+                None,
+                None,
+            ))
+            .unwrap()
+        };
+
+        // Writable keys come first in the compiled message, so this is also the
+        // record's position among the simulated accounts.
+        let index = Message::new(std::slice::from_ref(&ix), Some(&payer.pubkey()))
+            .account_keys
+            .iter()
+            .position(|key| *key == record)
+            .unwrap();
+
+        let mut before_accounts = vec![None, None, None];
+        before_accounts[index] = Some(ui(&before));
+        let mut after_accounts = vec![None, None, None];
+        after_accounts[index] = Some(serde_json::from_value(ui(&after)).unwrap());
+
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: before_accounts,
+            })
+            .unwrap(),
+        );
+        mocks.insert(
+            RpcRequest::SimulateTransaction,
+            serde_json::to_value(Response {
+                context: RpcResponseContext::new(1),
+                value: RpcSimulateTransactionResult {
+                    err: None,
+                    logs: Some(vec!["Program log: ok".to_string()]),
+                    accounts: Some(after_accounts),
+                    units_consumed: Some(150),
+                    loaded_accounts_data_size: None,
+                    return_data: None,
+                    inner_instructions: None,
+                    replacement_blockhash: None,
+                },
+            })
+            .unwrap(),
+        );
+        let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let result = simulate_tx(&client, &[&payer], &[ix]).unwrap();
+
+        assert_eq!(result.logs, vec!["Program log: ok".to_string()]);
+        assert_eq!(result.compute_units, Some(150));
+        assert_eq!(result.error, None);
+        assert_eq!(result.accounts.len(), 3);
+
+        let diff = &result.accounts[index];
+        assert_eq!(diff.address, record);
+        assert!(matches!(
+            diff.decoded_before,
+            Some(TokenMetadataAccount::TokenRecord(TokenRecord {
+                state: TokenState::Unlocked,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            diff.decoded_after,
+            Some(TokenMetadataAccount::TokenRecord(TokenRecord {
+                state: TokenState::Locked,
+                ..
+            }))
+        ));
+    }
+}
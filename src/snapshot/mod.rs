@@ -1,7 +1,6 @@
 use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
 use solana_account_decoder::UiAccountEncoding;
 use solana_client::{
-    rpc_client::RpcClient,
     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
 };
@@ -13,108 +12,88 @@ use solana_sdk::{
 
 pub mod errors;
 
-use crate::constants::*;
-use errors::SnapshotError;
+use crate::{constants::*, errors::Result, rpc::Rpc};
 
 pub fn get_metadata_accounts_by_update_authority(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     update_authority: &str,
-) -> Result<Vec<(Pubkey, Account)>, SnapshotError> {
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = metadata_by_update_authority_config(update_authority);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config)
+}
+
+pub fn get_metadata_accounts_by_creator(
+    client: &(impl Rpc + ?Sized),
+    creator_id: &str,
+    creator_position: usize,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = metadata_by_creator_config(creator_id, creator_position);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config)
+}
+
+pub fn get_holder_token_accounts(
+    client: &(impl Rpc + ?Sized),
+    mint_account: String,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = holder_token_accounts_config(mint_account);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config)
+}
+
+pub fn get_edition_accounts_by_master(
+    client: &(impl Rpc + ?Sized),
+    parent_pubkey: &str,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = edition_accounts_by_master_config(parent_pubkey);
+
+    get_program_accounts(client, &TOKEN_METADATA_PROGRAM_ID, config)
+}
+
+fn get_program_accounts(
+    client: &(impl Rpc + ?Sized),
+    program_id: &Pubkey,
+    config: RpcProgramAccountsConfig,
+) -> Result<Vec<(Pubkey, Account)>> {
+    client
+        .get_program_accounts_with_config(program_id, config)
+        .map_err(Into::into)
+}
+
+// Query configs shared by the blocking and async snapshot functions.
+
+pub(crate) fn metadata_by_update_authority_config(
+    update_authority: &str,
+) -> RpcProgramAccountsConfig {
     let filter = RpcFilterType::Memcmp(Memcmp::new(
         1, // key
         MemcmpEncodedBytes::Base58(update_authority.to_string()),
     ));
-    let config = RpcProgramAccountsConfig {
-        filters: Some(vec![filter]),
-        account_config: RpcAccountInfoConfig {
-            encoding: Some(UiAccountEncoding::Base64),
-            data_slice: None,
-            commitment: Some(CommitmentConfig {
-                commitment: CommitmentLevel::Confirmed,
-            }),
-            min_context_slot: None,
-        },
-        with_context: None,
-        sort_results: None,
-    };
 
-    let accounts = match client.get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)
-    {
-        Ok(accounts) => accounts,
-        Err(err) => return Err(SnapshotError::ClientError(Box::new(err.kind))),
-    };
-
-    Ok(accounts)
+    program_accounts_config(vec![filter])
 }
 
-pub fn get_metadata_accounts_by_creator(
-    client: &RpcClient,
+pub(crate) fn metadata_by_creator_config(
     creator_id: &str,
     creator_position: usize,
-) -> Result<Vec<(Pubkey, Account)>, SnapshotError> {
+) -> RpcProgramAccountsConfig {
     let filter = RpcFilterType::Memcmp(Memcmp::new(
         OFFSET_TO_CREATORS + creator_position * PUBKEY_LENGTH,
         MemcmpEncodedBytes::Base58(creator_id.to_string()),
     ));
 
-    let config = RpcProgramAccountsConfig {
-        filters: Some(vec![filter]),
-        account_config: RpcAccountInfoConfig {
-            encoding: Some(UiAccountEncoding::Base64),
-            data_slice: None,
-            commitment: Some(CommitmentConfig {
-                commitment: CommitmentLevel::Confirmed,
-            }),
-            min_context_slot: None,
-        },
-        with_context: None,
-        sort_results: None,
-    };
-
-    let accounts = match client.get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)
-    {
-        Ok(accounts) => accounts,
-        Err(err) => return Err(SnapshotError::ClientError(Box::new(err.kind))),
-    };
-
-    Ok(accounts)
+    program_accounts_config(vec![filter])
 }
 
-pub fn get_holder_token_accounts(
-    client: &RpcClient,
-    mint_account: String,
-) -> Result<Vec<(Pubkey, Account)>, SnapshotError> {
+pub(crate) fn holder_token_accounts_config(mint_account: String) -> RpcProgramAccountsConfig {
     let filter1 = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Base58(mint_account)));
     let filter2 = RpcFilterType::DataSize(165);
-    let account_config = RpcAccountInfoConfig {
-        encoding: Some(UiAccountEncoding::Base64),
-        data_slice: None,
-        commitment: Some(CommitmentConfig {
-            commitment: CommitmentLevel::Confirmed,
-        }),
-        min_context_slot: None,
-    };
-
-    let config = RpcProgramAccountsConfig {
-        filters: Some(vec![filter1, filter2]),
-        account_config,
-        with_context: None,
-        sort_results: None,
-    };
 
-    let holders = match client.get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)
-    {
-        Ok(accounts) => accounts,
-        Err(err) => return Err(SnapshotError::ClientError(Box::new(err.kind))),
-    };
-
-    Ok(holders)
+    program_accounts_config(vec![filter1, filter2])
 }
 
-pub fn get_edition_accounts_by_master(
-    client: &RpcClient,
-    parent_pubkey: &str,
-) -> Result<Vec<(Pubkey, Account)>, SnapshotError> {
+pub(crate) fn edition_accounts_by_master_config(parent_pubkey: &str) -> RpcProgramAccountsConfig {
     let key_filter = RpcFilterType::Memcmp(Memcmp::new(
         0,
         MemcmpEncodedBytes::Base58(EDITION_V1_BS58.to_string()),
@@ -123,9 +102,12 @@ pub fn get_edition_accounts_by_master(
         1,
         MemcmpEncodedBytes::Base58(parent_pubkey.to_string()),
     ));
-    let filters = vec![key_filter, parent_filter];
 
-    let config = RpcProgramAccountsConfig {
+    program_accounts_config(vec![key_filter, parent_filter])
+}
+
+fn program_accounts_config(filters: Vec<RpcFilterType>) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
         filters: Some(filters),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
@@ -137,13 +119,5 @@ pub fn get_edition_accounts_by_master(
         },
         with_context: None,
         sort_results: None,
-    };
-
-    let accounts = match client.get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)
-    {
-        Ok(accounts) => accounts,
-        Err(err) => return Err(SnapshotError::ClientError(Box::new(err.kind))),
-    };
-
-    Ok(accounts)
+    }
 }
@@ -1,89 +1,1084 @@
-use anyhow::Result;
-use retry::{delay::Exponential, retry};
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use retry::retry;
+use solana_client::{
+    client_error::ClientError,
+    nonce_utils,
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_response::RpcSimulateTransactionResult,
+};
 use solana_program::instruction::Instruction;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
     hash::Hash,
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
+    signature::Signature,
     signer::Signer,
-    transaction::Transaction,
+    system_instruction::advance_nonce_account,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+use solana_transaction_status_client_types::TransactionStatus;
+
+use crate::{
+    data::Priority,
+    errors::{MetabossError, Result},
+    fees::{get_priority_fee, DEFAULT_MAX_PRIORITY_FEE_LAMPORTS},
+    program_errors::{map_client_error, map_transaction_error, ProgramFailure},
+    rpc::Rpc,
 };
 
 macro_rules! transaction {
-    ($signers:expr, $instructions:expr, $client:expr) => {
-        Transaction::new_signed_with_payer(
+    ($signers:expr, $instructions:expr, $blockhash:expr) => {
+        VersionedTransaction::from(Transaction::new_signed_with_payer(
             $instructions,
             Some(&$signers[0].pubkey()),
             $signers,
-            $client.get_latest_blockhash()?,
-        )
+            $blockhash,
+        ))
     };
 }
 
+macro_rules! versioned_transaction {
+    ($signers:expr, $instructions:expr, $lookup_tables:expr, $blockhash:expr) => {{
+        let signers = unique_signers($signers);
+        let message = v0::Message::try_compile(
+            &signers[0].pubkey(),
+            $instructions,
+            $lookup_tables,
+            $blockhash,
+        )?;
+        VersionedTransaction::try_new(VersionedMessage::V0(message), &signers)?
+    }};
+}
+
+/// Where a transaction gets the blockhash it is signed against.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub enum BlockhashSource {
+    /// Fetch the latest blockhash from the cluster.
+    #[default]
+    Latest,
+    /// Use a caller-chosen blockhash.
+    Fixed(Hash),
+    /// Use the value stored in a durable nonce account. The nonce advance instruction is
+    /// prepended and `nonce_authority` must sign the transaction.
+    DurableNonce {
+        nonce_account: Pubkey,
+        nonce_authority: Pubkey,
+    },
+}
+
+/// Resolve the blockhash for `source`, returning it together with the instructions to
+/// send, which are prefixed with the nonce advance instruction when a durable nonce is used.
+pub fn resolve_blockhash(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    source: &BlockhashSource,
+) -> Result<(Vec<Instruction>, Hash)> {
+    match source {
+        BlockhashSource::Latest => Ok((ixs.to_vec(), client.get_latest_blockhash()?)),
+        BlockhashSource::Fixed(hash) => Ok((ixs.to_vec(), *hash)),
+        BlockhashSource::DurableNonce {
+            nonce_account,
+            nonce_authority,
+        } => {
+            let account = client.get_account(nonce_account)?;
+            let data = nonce_utils::data_from_account(&account)?;
+
+            let mut instructions = Vec::with_capacity(ixs.len() + 1);
+            instructions.push(advance_nonce_account(nonce_account, nonce_authority));
+            instructions.extend_from_slice(ixs);
+
+            Ok((instructions, data.blockhash()))
+        }
+    }
+}
+
 pub fn send_and_confirm_tx(
-    client: &RpcClient,
-    signers: &[&Keypair],
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+) -> Result<Signature> {
+    send_and_confirm_tx_with_blockhash(client, signers, ixs, &BlockhashSource::Latest)
+}
+
+/// Like [`send_and_confirm_tx`] but signs against the given [`BlockhashSource`], e.g. a
+/// durable nonce. The nonce authority must be one of the `signers`.
+pub fn send_and_confirm_tx_with_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
     ixs: &[Instruction],
+    blockhash: &BlockhashSource,
 ) -> Result<Signature> {
-    let tx = transaction!(signers, ixs, client);
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash)?;
+    let tx = transaction!(signers, &ixs, blockhash);
 
-    let signature = client.send_and_confirm_transaction(&tx)?;
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .map_err(|e| map_client_error(e, &ixs))?;
 
     Ok(signature)
 }
 
 pub fn send_and_confirm_tx_with_retries(
-    client: &RpcClient,
-    signers: &[&Keypair],
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
     ixs: &[Instruction],
 ) -> Result<Signature> {
-    let tx = transaction!(signers, ixs, client);
+    send_and_confirm_tx_with_retries_and_blockhash(client, signers, ixs, &BlockhashSource::Latest)
+}
 
-    // Send tx with retries.
-    #[allow(clippy::result_large_err)]
-    let res = retry(
-        Exponential::from_millis_with_factor(250, 2.0).take(3),
-        || client.send_and_confirm_transaction_with_spinner(&tx),
-    )?;
+pub fn send_and_confirm_tx_with_retries_and_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    blockhash: &BlockhashSource,
+) -> Result<Signature> {
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash)?;
+    let tx = transaction!(signers, &ixs, blockhash);
+
+    send_with_retries(client, &tx, &ixs)
+}
+
+/// Final state of a transaction sent with [`send_and_confirm_tx_with_rebroadcast`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TxOutcome {
+    /// The transaction executed successfully at the requested commitment.
+    Landed { signature: Signature },
+    /// The transaction was included in a block but its execution failed.
+    FailedOnChain {
+        signature: Signature,
+        error: TransactionError,
+        /// The failing instruction's decoded program error, when `error` is tied to an
+        /// instruction.
+        failure: Option<ProgramFailure>,
+    },
+    /// The blockhash expired without the transaction landing, so it can no longer be
+    /// processed.
+    Expired { signature: Signature },
+    /// The status couldn't be determined, e.g. because the RPC node kept erroring.
+    Unknown {
+        signature: Signature,
+        reason: String,
+    },
+}
+
+impl TxOutcome {
+    /// Signature of the last transaction that was broadcast.
+    pub fn signature(&self) -> Signature {
+        match self {
+            TxOutcome::Landed { signature }
+            | TxOutcome::FailedOnChain { signature, .. }
+            | TxOutcome::Expired { signature }
+            | TxOutcome::Unknown { signature, .. } => *signature,
+        }
+    }
+
+    /// Convert to a `Result`, treating anything other than [`TxOutcome::Landed`] as an error.
+    pub fn into_result(self) -> Result<Signature> {
+        match self {
+            TxOutcome::Landed { signature } => Ok(signature),
+            TxOutcome::FailedOnChain {
+                signature,
+                failure: Some(failure),
+                ..
+            } => Err(ProgramFailure {
+                signature: Some(signature),
+                ..failure
+            }
+            .into()),
+            TxOutcome::FailedOnChain {
+                signature,
+                error,
+                failure: None,
+            } => Err(MetabossError::FailedOnChain { signature, error }),
+            TxOutcome::Expired { signature } => Err(MetabossError::Expired(signature)),
+            TxOutcome::Unknown { signature, reason } => {
+                Err(MetabossError::UnknownStatus { signature, reason })
+            }
+        }
+    }
+}
+
+/// Delays between attempts of the `*_with_retries` functions.
+pub(crate) const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_millis(1000),
+];
+
+/// Upper bound on how long a single blockhash is waited on. Blockhashes normally expire
+/// after about 150 blocks, well within this.
+pub const MAX_CONFIRMATION_WAIT: Duration = Duration::from_secs(180);
+
+#[derive(Debug, Clone)]
+pub struct RebroadcastConfig {
+    /// How often the same signed transaction is sent again while waiting.
+    pub rebroadcast_interval: Duration,
+    /// How often the signature status and block height are polled.
+    pub poll_interval: Duration,
+    pub commitment: CommitmentConfig,
+    /// Re-sign with a fresh blockhash once the previous one expires. Only enable this
+    /// for idempotent operations; an expired transaction can never land, but a
+    /// non-idempotent one that partially succeeded elsewhere may be applied twice.
+    pub resign_on_expiry: bool,
+    /// Maximum number of blockhashes the transaction is signed against.
+    pub max_attempts: u32,
+}
+
+impl RebroadcastConfig {
+    /// Number of blockhashes the transaction is signed against.
+    pub(crate) fn attempts(&self) -> u32 {
+        if self.resign_on_expiry {
+            self.max_attempts.max(1)
+        } else {
+            1
+        }
+    }
+}
+
+impl Default for RebroadcastConfig {
+    fn default() -> Self {
+        Self {
+            rebroadcast_interval: Duration::from_secs(2),
+            poll_interval: Duration::from_millis(500),
+            commitment: CommitmentConfig::confirmed(),
+            resign_on_expiry: false,
+            max_attempts: 3,
+        }
+    }
+}
+
+/// Send `ixs` and keep rebroadcasting the signed transaction until it lands or its
+/// blockhash passes `last_valid_block_height`.
+///
+/// Unlike [`send_and_confirm_tx_with_retries`], which retries the same bytes after the
+/// blockhash may have expired, this polls the signature status between broadcasts and
+/// reports which of the [`TxOutcome`]s occurred. A transaction rejected on its first
+/// send, e.g. by preflight, is returned as an error. RPC failures after that are reported
+/// as [`TxOutcome::Unknown`] rather than as errors.
+pub fn send_and_confirm_tx_with_rebroadcast(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    config: &RebroadcastConfig,
+) -> Result<TxOutcome> {
+    rebroadcast_until_expiry(client, ixs, config, |blockhash| {
+        let tx =
+            Transaction::new_signed_with_payer(ixs, Some(&signers[0].pubkey()), signers, blockhash);
+        Ok(tx.into())
+    })
+}
+
+/// Versioned counterpart of [`send_and_confirm_tx_with_rebroadcast`].
+pub fn send_and_confirm_versioned_tx_with_rebroadcast(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    config: &RebroadcastConfig,
+) -> Result<TxOutcome> {
+    rebroadcast_until_expiry(client, ixs, config, |blockhash| {
+        compile_versioned_tx(signers, ixs, lookup_tables, blockhash)
+    })
+}
+
+fn rebroadcast_until_expiry<F>(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    config: &RebroadcastConfig,
+    build_tx: F,
+) -> Result<TxOutcome>
+where
+    F: Fn(Hash) -> Result<VersionedTransaction>,
+{
+    let mut outcome = None;
+    for _ in 0..config.attempts() {
+        let (blockhash, last_valid_block_height) =
+            client.get_latest_blockhash_with_commitment(config.commitment)?;
+        let tx = build_tx(blockhash)?;
+
+        let attempt = broadcast_and_poll(client, ixs, config, &tx, last_valid_block_height)?;
+        if !matches!(attempt, TxOutcome::Expired { .. }) {
+            return Ok(attempt);
+        }
+        outcome = Some(attempt);
+    }
+
+    Ok(outcome.expect("at least one attempt is made"))
+}
+
+fn broadcast_and_poll(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    config: &RebroadcastConfig,
+    tx: &VersionedTransaction,
+    last_valid_block_height: u64,
+) -> Result<TxOutcome> {
+    let signature = tx.signatures[0];
+
+    // A rejected first send never reached the cluster, so it is a definite failure.
+    client
+        .send_transaction(tx)
+        .map_err(|e| map_client_error(e, ixs))?;
+
+    let mut poll = RebroadcastPoll::new(signature, ixs, config, last_valid_block_height);
+    let mut action = PollAction::PollStatus;
+    loop {
+        action = match action {
+            PollAction::PollStatus => {
+                let status = client
+                    .get_signature_statuses(&[signature])
+                    .map(|response| response.value.into_iter().next().flatten())
+                    .map_err(|e| e.to_string());
+                poll.on_status(status)
+            }
+            PollAction::CheckBlockHeight => {
+                let height = client
+                    .get_block_height_with_commitment(config.commitment)
+                    .map_err(|e| e.to_string());
+                poll.on_block_height(height)
+            }
+            PollAction::Wait { rebroadcast } => {
+                if rebroadcast {
+                    // Errors are ignored here; the next status poll decides what happened.
+                    let _ = client.send_transaction_with_config(tx, REBROADCAST_SEND_CONFIG);
+                }
+                thread::sleep(config.poll_interval);
+                PollAction::PollStatus
+            }
+            PollAction::Finish(outcome) => return Ok(*outcome),
+        };
+    }
+}
+
+/// Rebroadcasts skip preflight so that a transaction already being processed isn't
+/// rejected as a duplicate.
+pub(crate) const REBROADCAST_SEND_CONFIG: RpcSendTransactionConfig = RpcSendTransactionConfig {
+    skip_preflight: true,
+    preflight_commitment: None,
+    encoding: None,
+    max_retries: Some(0),
+    min_context_slot: None,
+};
+
+/// Next RPC call [`RebroadcastPoll`] needs the result of, or the final outcome.
+pub(crate) enum PollAction {
+    /// Look up the signature status and pass it to [`RebroadcastPoll::on_status`].
+    PollStatus,
+    /// Look up the block height and pass it to [`RebroadcastPoll::on_block_height`].
+    CheckBlockHeight,
+    /// Sleep for the poll interval, rebroadcasting the transaction first if asked to.
+    Wait {
+        rebroadcast: bool,
+    },
+    Finish(Box<TxOutcome>),
+}
 
-    Ok(res)
+/// Status and expiry decisions of a rebroadcast loop. The blocking and async clients
+/// only perform the RPC calls each [`PollAction`] asks for and feed the results back.
+pub(crate) struct RebroadcastPoll<'a> {
+    signature: Signature,
+    ixs: &'a [Instruction],
+    commitment: CommitmentConfig,
+    rebroadcast_interval: Duration,
+    last_valid_block_height: u64,
+    started: Instant,
+    last_broadcast: Instant,
+    last_error: Option<String>,
+    seen: bool,
+    expired: bool,
+}
+
+impl<'a> RebroadcastPoll<'a> {
+    /// Start polling right after the transaction's first send.
+    pub(crate) fn new(
+        signature: Signature,
+        ixs: &'a [Instruction],
+        config: &RebroadcastConfig,
+        last_valid_block_height: u64,
+    ) -> Self {
+        let started = Instant::now();
+
+        Self {
+            signature,
+            ixs,
+            commitment: config.commitment,
+            rebroadcast_interval: config.rebroadcast_interval,
+            last_valid_block_height,
+            started,
+            last_broadcast: started,
+            last_error: None,
+            seen: false,
+            expired: false,
+        }
+    }
+
+    pub(crate) fn on_status(
+        &mut self,
+        status: std::result::Result<Option<TransactionStatus>, String>,
+    ) -> PollAction {
+        let signature = self.signature;
+
+        self.seen = false;
+        let status_error = match status {
+            Ok(Some(status)) if status.satisfies_commitment(self.commitment) => {
+                return PollAction::Finish(Box::new(match status.err {
+                    Some(error) => TxOutcome::FailedOnChain {
+                        signature,
+                        failure: map_transaction_error(&error, self.ixs),
+                        error,
+                    },
+                    None => TxOutcome::Landed { signature },
+                }));
+            }
+            // Seen by the cluster but not yet at the requested commitment, so it can't
+            // expire anymore; keep polling without rebroadcasting.
+            Ok(Some(_)) => {
+                self.seen = true;
+                return self.wait();
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.last_error = Some(e.clone());
+                Some(e)
+            }
+        };
+
+        if self.expired {
+            // A failed status lookup right after expiry leaves us unable to tell whether
+            // the transaction landed.
+            return PollAction::Finish(Box::new(match status_error {
+                Some(reason) => TxOutcome::Unknown { signature, reason },
+                None => TxOutcome::Expired { signature },
+            }));
+        }
+
+        PollAction::CheckBlockHeight
+    }
+
+    pub(crate) fn on_block_height(
+        &mut self,
+        height: std::result::Result<u64, String>,
+    ) -> PollAction {
+        match height {
+            // The transaction may have landed between the status lookup and expiry, so
+            // the status is looked up once more before it is reported as expired.
+            Ok(height) if height > self.last_valid_block_height => {
+                self.expired = true;
+                return PollAction::PollStatus;
+            }
+            Ok(_) => {}
+            Err(e) => self.last_error = Some(e),
+        }
+
+        self.wait()
+    }
+
+    fn wait(&mut self) -> PollAction {
+        // Guards against an RPC node that never answers the block height query.
+        if self.started.elapsed() >= MAX_CONFIRMATION_WAIT {
+            return PollAction::Finish(Box::new(TxOutcome::Unknown {
+                signature: self.signature,
+                reason: self
+                    .last_error
+                    .take()
+                    .unwrap_or_else(|| "timed out waiting for confirmation".to_string()),
+            }));
+        }
+
+        let rebroadcast = !self.seen && self.last_broadcast.elapsed() >= self.rebroadcast_interval;
+        if rebroadcast {
+            self.last_broadcast = Instant::now();
+        }
+
+        PollAction::Wait { rebroadcast }
+    }
 }
 
 pub fn get_compute_units(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     ixs: &[Instruction],
-    signers: &[&Keypair],
+    signers: &[&dyn Signer],
 ) -> Result<Option<u64>> {
-    let config = RpcSimulateTransactionConfig {
+    let config = compute_units_simulation_config();
+
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&signers[0].pubkey()),
+        signers,
+        Hash::new_from_array(Pubkey::default().to_bytes()), // dummy value
+    );
+
+    // This doesn't return an error if the simulation fails
+    let sim_result = client.simulate_transaction_with_config(&tx.into(), config)?;
+
+    units_from_simulation(sim_result.value, ixs)
+}
+
+pub(crate) fn compute_units_simulation_config() -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
         sig_verify: false,
         replace_recent_blockhash: true,
         commitment: Some(CommitmentConfig::confirmed()),
         ..Default::default()
+    }
+}
+
+/// Compute unit limit for a simulated transaction, with headroom over the units it
+/// consumed.
+pub(crate) fn units_from_simulation(
+    result: RpcSimulateTransactionResult,
+    ixs: &[Instruction],
+) -> Result<Option<u64>> {
+    // A failed simulation is reported through the error Option on the result rather
+    // than as a client error, so we check here and return the error manually.
+    if let Some(err) = result.err {
+        return Err(match map_transaction_error(&err, ixs) {
+            Some(failure) => failure.into(),
+            None => err.into(),
+        });
+    }
+
+    Ok(result
+        .units_consumed
+        .map(|units| (units as f64 * 1.20) as u64))
+}
+
+/// Compute unit limit and price settings applied to an operation's transaction.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ComputeBudget {
+    pub priority: Priority,
+    /// Fixed compute unit limit. When `None` the limit is found by simulating the
+    /// transaction.
+    pub compute_units: Option<u32>,
+    /// Limit used when the simulation doesn't report the units consumed. When `None`
+    /// the operation's own default is used, which is [`DEFAULT_COMPUTE_UNITS`] for most.
+    pub fallback_compute_units: Option<u32>,
+    /// Ceiling on the total priority fee paid by the transaction.
+    pub max_fee_lamports: u64,
+}
+
+impl Default for ComputeBudget {
+    fn default() -> Self {
+        Self {
+            priority: Priority::None,
+            compute_units: None,
+            fallback_compute_units: None,
+            max_fee_lamports: DEFAULT_MAX_PRIORITY_FEE_LAMPORTS,
+        }
+    }
+}
+
+impl From<Priority> for ComputeBudget {
+    fn from(priority: Priority) -> Self {
+        Self {
+            priority,
+            ..Self::default()
+        }
+    }
+}
+
+impl ComputeBudget {
+    /// Use a fixed compute unit limit instead of simulating the transaction.
+    pub fn with_compute_units(mut self, units: u32) -> Self {
+        self.compute_units = Some(units);
+        self
+    }
+
+    pub fn with_fallback_compute_units(mut self, units: u32) -> Self {
+        self.fallback_compute_units = Some(units);
+        self
+    }
+
+    pub fn with_max_fee_lamports(mut self, lamports: u64) -> Self {
+        self.max_fee_lamports = lamports;
+        self
+    }
+
+    pub(crate) fn fallback_units(&self) -> u64 {
+        self.fallback_compute_units.unwrap_or(DEFAULT_COMPUTE_UNITS) as u64
+    }
+}
+
+/// Default compute unit limit for a transaction, used when simulation gives no answer.
+pub const DEFAULT_COMPUTE_UNITS: u32 = 200_000;
+
+/// Prefix `ixs` with the compute unit limit and price instructions described by `budget`.
+pub fn add_compute_budget(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    budget: &ComputeBudget,
+) -> Result<Vec<Instruction>> {
+    let units = match budget.compute_units {
+        Some(units) => units as u64,
+        None => get_compute_units(client, ixs, signers)?.unwrap_or(budget.fallback_units()),
     };
 
-    let tx = Transaction::new_signed_with_payer(
+    let micro_lamports = get_priority_fee(
+        client,
+        &budget.priority,
         ixs,
-        Some(&signers[0].pubkey()),
+        units,
+        budget.max_fee_lamports,
+    )?;
+
+    Ok(prepend_compute_budget(units, micro_lamports, ixs))
+}
+
+pub(crate) fn prepend_compute_budget(
+    units: u64,
+    micro_lamports: u64,
+    ixs: &[Instruction],
+) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(ixs.len() + 2);
+    instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+        units as u32,
+    ));
+    instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+        micro_lamports,
+    ));
+    instructions.extend_from_slice(ixs);
+
+    instructions
+}
+
+/// Send `ixs` with compute unit limit and priority fee instructions added. This is the
+/// path every operation module sends through.
+pub fn send_and_confirm_tx_with_compute_budget(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    budget: &ComputeBudget,
+) -> Result<Signature> {
+    let instructions = add_compute_budget(client, signers, ixs, budget)?;
+
+    send_and_confirm_tx(client, signers, &instructions)
+}
+
+/// Send a v0 transaction compiled against the provided address lookup tables.
+///
+/// The first signer is the fee payer. Accounts present in any of the lookup tables are
+/// loaded by index instead of being listed in the message, which allows more accounts
+/// per transaction than the legacy format.
+pub fn send_and_confirm_versioned_tx(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Signature> {
+    send_and_confirm_versioned_tx_with_blockhash(
+        client,
         signers,
-        Hash::new_from_array(Pubkey::default().to_bytes()), // dummy value
+        ixs,
+        lookup_tables,
+        &BlockhashSource::Latest,
+    )
+}
+
+pub fn send_and_confirm_versioned_tx_with_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: &BlockhashSource,
+) -> Result<Signature> {
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash)?;
+    let tx = versioned_transaction!(signers, &ixs, lookup_tables, blockhash);
+
+    let signature = client
+        .send_and_confirm_transaction(&tx)
+        .map_err(|e| map_client_error(e, &ixs))?;
+
+    Ok(signature)
+}
+
+pub fn send_and_confirm_versioned_tx_with_retries(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Signature> {
+    send_and_confirm_versioned_tx_with_retries_and_blockhash(
+        client,
+        signers,
+        ixs,
+        lookup_tables,
+        &BlockhashSource::Latest,
+    )
+}
+
+pub fn send_and_confirm_versioned_tx_with_retries_and_blockhash(
+    client: &(impl Rpc + ?Sized),
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: &BlockhashSource,
+) -> Result<Signature> {
+    let (ixs, blockhash) = resolve_blockhash(client, ixs, blockhash)?;
+    let tx = versioned_transaction!(signers, &ixs, lookup_tables, blockhash);
+
+    send_with_retries(client, &tx, &ixs)
+}
+
+/// Versioned counterpart of [`get_compute_units`] for transactions using lookup tables.
+pub fn get_compute_units_versioned(
+    client: &(impl Rpc + ?Sized),
+    ixs: &[Instruction],
+    signers: &[&dyn Signer],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Option<u64>> {
+    let config = compute_units_simulation_config();
+
+    let tx = versioned_transaction!(
+        signers,
+        ixs,
+        lookup_tables,
+        Hash::new_from_array(Pubkey::default().to_bytes()) // dummy value
     );
 
-    // This doesn't return an error if the simulation fails
     let sim_result = client.simulate_transaction_with_config(&tx, config)?;
 
-    // it sets the error Option on the value in the Ok variant, so we check here
-    // and return the error manually.
-    if let Some(err) = sim_result.value.err {
-        return Err(err.into());
+    units_from_simulation(sim_result.value, ixs)
+}
+
+/// Compile and sign a v0 transaction. The first signer is the fee payer.
+pub(crate) fn compile_versioned_tx(
+    signers: &[&dyn Signer],
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    Ok(versioned_transaction!(
+        signers,
+        ixs,
+        lookup_tables,
+        blockhash
+    ))
+}
+
+fn send_with_retries(
+    client: &(impl Rpc + ?Sized),
+    tx: &VersionedTransaction,
+    ixs: &[Instruction],
+) -> Result<Signature> {
+    #[allow(clippy::result_large_err)]
+    retry(RETRY_DELAYS, || {
+        client.send_and_confirm_transaction_with_spinner(tx)
+    })
+    .map_err(|e| map_retry_error(e, tx.signatures[0], ixs))
+}
+
+fn map_retry_error(
+    err: retry::Error<ClientError>,
+    signature: Signature,
+    ixs: &[Instruction],
+) -> MetabossError {
+    match err {
+        retry::Error::Operation { error, .. } => map_client_error(error, ixs),
+        // The retry loop itself failed, possibly after the transaction was sent.
+        retry::Error::Internal(reason) => MetabossError::UnknownStatus { signature, reason },
     }
+}
 
-    // Otherwise, we can get the compute units from the simulation result
-    let units = sim_result
-        .value
-        .units_consumed
-        .map(|units| (units as f64 * 1.20) as u64);
+// Versioned transactions require exactly one signature per required signer, so
+// duplicates such as a payer that is also the authority are dropped.
+fn unique_signers<'a>(signers: &[&'a dyn Signer]) -> Vec<&'a dyn Signer> {
+    let mut unique: Vec<&dyn Signer> = Vec::with_capacity(signers.len());
+    for signer in signers {
+        if !unique.iter().any(|s| s.pubkey() == signer.pubkey()) {
+            unique.push(*signer);
+        }
+    }
+    unique
+}
 
-    Ok(units)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonce::tests::nonce_client;
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+    };
+    use solana_sdk::{
+        nonce::state::DurableNonce, signature::Keypair, system_instruction::transfer,
+    };
+    use solana_transaction_status_client_types::TransactionConfirmationStatus;
+
+    #[test]
+    fn test_unique_signers_drops_duplicates() {
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+
+        let signers = unique_signers(&[&payer, &authority, &payer]);
+
+        assert_eq!(signers.len(), 2);
+        assert_eq!(signers[0].pubkey(), payer.pubkey());
+        assert_eq!(signers[1].pubkey(), authority.pubkey());
+    }
+
+    #[test]
+    fn test_versioned_transaction_uses_lookup_table() {
+        let payer = Keypair::new();
+        let destination = Pubkey::new_unique();
+        let ix = transfer(&payer.pubkey(), &destination, 1);
+
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![destination],
+        };
+
+        let tx = compile_versioned_tx(
+            &[&payer, &payer],
+            &[ix],
+            std::slice::from_ref(&lookup_table),
+            Hash::default(),
+        )
+        .unwrap();
+
+        assert_eq!(tx.signatures.len(), 1);
+        let VersionedMessage::V0(message) = tx.message else {
+            panic!("expected v0 message");
+        };
+        assert!(!message.account_keys.contains(&destination));
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(
+            message.address_table_lookups[0].account_key,
+            lookup_table.key
+        );
+        assert_eq!(message.address_table_lookups[0].writable_indexes, vec![0]);
+    }
+
+    #[test]
+    fn test_resolve_blockhash_fixed() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let hash = Hash::new_unique();
+        let ix = transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+
+        let (ixs, blockhash) = resolve_blockhash(
+            &client,
+            std::slice::from_ref(&ix),
+            &BlockhashSource::Fixed(hash),
+        )
+        .unwrap();
+
+        assert_eq!(ixs, vec![ix]);
+        assert_eq!(blockhash, hash);
+    }
+
+    #[test]
+    fn test_resolve_blockhash_durable_nonce() {
+        let nonce_account = Pubkey::new_unique();
+        let nonce_authority = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let durable_nonce = DurableNonce::from_blockhash(&blockhash);
+
+        let client = nonce_client(&nonce_account, &nonce_authority, &blockhash);
+
+        let ix = transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+        let source = BlockhashSource::DurableNonce {
+            nonce_account,
+            nonce_authority,
+        };
+
+        let (ixs, blockhash) =
+            resolve_blockhash(&client, std::slice::from_ref(&ix), &source).unwrap();
+
+        assert_eq!(blockhash, *durable_nonce.as_hash());
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(
+            ixs[0],
+            advance_nonce_account(&nonce_account, &nonce_authority)
+        );
+        assert_eq!(ixs[1], ix);
+    }
+
+    #[test]
+    fn test_add_compute_budget_with_fixed_units() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let payer = Keypair::new();
+        let ix = transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        let budget = ComputeBudget {
+            priority: Priority::Custom(10_000_000),
+            compute_units: Some(100_000),
+            max_fee_lamports: 1_000,
+            ..ComputeBudget::default()
+        };
+
+        let ixs =
+            add_compute_budget(&client, &[&payer], std::slice::from_ref(&ix), &budget).unwrap();
+
+        assert_eq!(
+            ixs,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(100_000),
+                // Capped so 100k units cost at most 1_000 lamports.
+                ComputeBudgetInstruction::set_compute_unit_price(10_000),
+                ix,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_budget_from_priority() {
+        let budget = ComputeBudget::from(Priority::High);
+
+        assert_eq!(budget.priority, Priority::High);
+        assert_eq!(budget.compute_units, None);
+        assert_eq!(budget.fallback_units(), DEFAULT_COMPUTE_UNITS as u64);
+        assert_eq!(budget.max_fee_lamports, DEFAULT_MAX_PRIORITY_FEE_LAMPORTS);
+
+        let budget = budget
+            .with_compute_units(80_000)
+            .with_fallback_compute_units(50_000)
+            .with_max_fee_lamports(5_000);
+
+        assert_eq!(budget.compute_units, Some(80_000));
+        assert_eq!(budget.fallback_units(), 50_000);
+        assert_eq!(budget.max_fee_lamports, 5_000);
+    }
+
+    fn rebroadcast_outcome(client: &(impl Rpc + ?Sized), config: &RebroadcastConfig) -> TxOutcome {
+        let payer = Keypair::new();
+        let ix = transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        send_and_confirm_tx_with_rebroadcast(client, &[&payer], &[ix], config).unwrap()
+    }
+
+    fn fast_rebroadcast_config() -> RebroadcastConfig {
+        RebroadcastConfig {
+            rebroadcast_interval: Duration::ZERO,
+            poll_interval: Duration::ZERO,
+            ..RebroadcastConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_poll_rechecks_status_before_expiring() {
+        let config = RebroadcastConfig::default();
+        let mut poll = RebroadcastPoll::new(Signature::new_unique(), &[], &config, 100);
+
+        assert!(matches!(
+            poll.on_status(Ok(None)),
+            PollAction::CheckBlockHeight
+        ));
+        assert!(matches!(
+            poll.on_block_height(Ok(100)),
+            PollAction::Wait { .. }
+        ));
+        assert!(matches!(
+            poll.on_status(Ok(None)),
+            PollAction::CheckBlockHeight
+        ));
+        assert!(matches!(
+            poll.on_block_height(Ok(101)),
+            PollAction::PollStatus
+        ));
+        assert!(matches!(
+            poll.on_status(Err("node is behind".to_string())),
+            PollAction::Finish(outcome) if matches!(*outcome, TxOutcome::Unknown { .. })
+        ));
+    }
+
+    #[test]
+    fn test_poll_waits_without_rebroadcast_once_seen() {
+        let config = RebroadcastConfig {
+            rebroadcast_interval: Duration::ZERO,
+            ..RebroadcastConfig::default()
+        };
+        let mut poll = RebroadcastPoll::new(Signature::new_unique(), &[], &config, 100);
+        let processed = TransactionStatus {
+            slot: 1,
+            confirmations: Some(0),
+            status: Ok(()),
+            err: None,
+            confirmation_status: Some(TransactionConfirmationStatus::Processed),
+        };
+
+        assert!(matches!(
+            poll.on_status(Ok(Some(processed.clone()))),
+            PollAction::Wait { rebroadcast: false }
+        ));
+        assert!(matches!(
+            poll.on_status(Ok(Some(TransactionStatus {
+                confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+                ..processed
+            }))),
+            PollAction::Finish(outcome) if matches!(*outcome, TxOutcome::Landed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rebroadcast_landed() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+
+        let outcome = rebroadcast_outcome(&client, &fast_rebroadcast_config());
+
+        assert!(matches!(outcome, TxOutcome::Landed { .. }));
+        assert!(outcome.into_result().is_ok());
+    }
+
+    #[test]
+    fn test_rebroadcast_failed_on_chain() {
+        let client = RpcClient::new_mock("instruction_error".to_string());
+
+        let outcome = rebroadcast_outcome(&client, &fast_rebroadcast_config());
+
+        assert!(matches!(
+            outcome,
+            TxOutcome::FailedOnChain {
+                error: TransactionError::InstructionError(0, _),
+                failure: Some(_),
+                ..
+            }
+        ));
+
+        let signature = outcome.signature();
+        assert!(matches!(
+            outcome.into_result(),
+            Err(MetabossError::Program(failure)) if failure.signature == Some(signature)
+        ));
+    }
+
+    #[test]
+    fn test_rebroadcast_expired_after_last_valid_block_height() {
+        // The mock's blockhash is valid through block height 1234.
+        let mut mocks = Mocks::default();
+        mocks.insert(RpcRequest::GetBlockHeight, serde_json::json!(1235));
+        let client = RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks);
+
+        let outcome = rebroadcast_outcome(&client, &fast_rebroadcast_config());
+
+        assert!(matches!(outcome, TxOutcome::Expired { .. }));
+        assert!(outcome.into_result().is_err());
+    }
+
+    #[test]
+    fn test_rebroadcast_rejected_first_send_is_an_error() {
+        // A signature other than the transaction's makes the client reject the send.
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::SendTransaction,
+            serde_json::json!(Signature::default().to_string()),
+        );
+        let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        let payer = Keypair::new();
+        let ix = transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        let result = send_and_confirm_tx_with_rebroadcast(
+            &client,
+            &[&payer],
+            &[ix],
+            &fast_rebroadcast_config(),
+        );
+
+        assert!(matches!(result, Err(MetabossError::Rpc(_))));
+    }
 }
@@ -1,20 +1,24 @@
-use anyhow::Result;
 use mpl_token_metadata::{
+    accounts::Metadata,
     instructions::TransferV1Builder,
     types::{AuthorizationData, ProgrammableConfig, TokenStandard},
 };
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{
-    signature::{Keypair, Signature},
-    signer::Signer,
-};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{signature::Signature, signer::Signer};
 
-use crate::{data::Asset, decode::ToPubkey, transaction::send_and_confirm_tx};
+use crate::{
+    data::Asset,
+    decode::ToPubkey,
+    errors::Result,
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::{send_and_confirm_tx_with_compute_budget, ComputeBudget},
+};
 
 pub enum TransferAssetArgs<'a, P: ToPubkey> {
     V1 {
-        payer: Option<&'a Keypair>,
-        authority: &'a Keypair,
+        payer: Option<&'a dyn Signer>,
+        authority: &'a dyn Signer,
         mint: P,
         source_owner: P,
         source_token: P,
@@ -22,11 +26,43 @@ pub enum TransferAssetArgs<'a, P: ToPubkey> {
         destination_token: P,
         amount: u64,
         authorization_data: Option<AuthorizationData>,
+        compute_budget: ComputeBudget,
     },
 }
 
+impl<'a, P: ToPubkey> TransferAssetArgs<'a, P> {
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<TransferAssetArgs<'a, Pubkey>> {
+        let TransferAssetArgs::V1 {
+            payer,
+            authority,
+            mint,
+            source_owner,
+            source_token,
+            destination_owner,
+            destination_token,
+            amount,
+            authorization_data,
+            compute_budget,
+        } = self;
+
+        Ok(TransferAssetArgs::V1 {
+            payer,
+            authority,
+            mint: mint.to_pubkey()?,
+            source_owner: source_owner.to_pubkey()?,
+            source_token: source_token.to_pubkey()?,
+            destination_owner: destination_owner.to_pubkey()?,
+            destination_token: destination_token.to_pubkey()?,
+            amount,
+            authorization_data,
+            compute_budget,
+        })
+    }
+}
+
 pub fn transfer_asset<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: TransferAssetArgs<P>,
 ) -> Result<Signature> {
     match args {
@@ -34,10 +70,80 @@ pub fn transfer_asset<P: ToPubkey>(
     }
 }
 
+pub fn transfer_asset_ix<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: TransferAssetArgs<P>,
+) -> Result<Instruction> {
+    match args {
+        TransferAssetArgs::V1 { .. } => transfer_asset_v1_ix(client, args),
+    }
+}
+
+pub fn transfer_asset_simulate<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: TransferAssetArgs<P>,
+) -> Result<SimulationResult> {
+    match args {
+        TransferAssetArgs::V1 { .. } => transfer_asset_v1_simulate(client, args),
+    }
+}
+
 fn transfer_asset_v1<P: ToPubkey>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: TransferAssetArgs<P>,
 ) -> Result<Signature> {
+    let TransferAssetArgs::V1 {
+        payer,
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let compute_budget = compute_budget.clone();
+
+    let transfer_ix = transfer_asset_v1_ix(client, args)?;
+
+    send_and_confirm_tx_with_compute_budget(
+        client,
+        &[payer, authority],
+        &[transfer_ix],
+        &compute_budget,
+    )
+}
+
+fn transfer_asset_v1_simulate<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: TransferAssetArgs<P>,
+) -> Result<SimulationResult> {
+    let TransferAssetArgs::V1 {
+        payer, authority, ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+
+    let ix = transfer_asset_v1_ix(client, args)?;
+
+    simulate_tx(client, &[payer, authority], &[ix])
+}
+
+fn transfer_asset_v1_ix<P: ToPubkey>(
+    client: &(impl Rpc + ?Sized),
+    args: TransferAssetArgs<P>,
+) -> Result<Instruction> {
+    let args = args.resolve()?;
+    let TransferAssetArgs::V1 { mint, .. } = args;
+
+    let md = Asset::new(mint).get_metadata(client)?;
+
+    Ok(build_transfer_asset_v1_ix(args, md))
+}
+
+/// Build the transfer instruction from resolved arguments and the asset's metadata.
+pub(crate) fn build_transfer_asset_v1_ix(
+    args: TransferAssetArgs<Pubkey>,
+    md: Metadata,
+) -> Instruction {
     let TransferAssetArgs::V1 {
         payer,
         authority,
@@ -48,14 +154,9 @@ fn transfer_asset_v1<P: ToPubkey>(
         destination_token,
         amount,
         authorization_data,
+        ..
     } = args;
 
-    let mint = mint.to_pubkey()?;
-    let source_owner = source_owner.to_pubkey()?;
-    let source_token = source_token.to_pubkey()?;
-    let destination_owner = destination_owner.to_pubkey()?;
-    let destination_token = destination_token.to_pubkey()?;
-
     let mut asset = Asset::new(mint);
     let payer = payer.unwrap_or(authority);
 
@@ -75,8 +176,6 @@ fn transfer_asset_v1<P: ToPubkey>(
         transfer_builder.authorization_data(data);
     }
 
-    let md = asset.get_metadata(client)?;
-
     if matches!(
         md.token_standard,
         Some(TokenStandard::ProgrammableNonFungible)
@@ -112,7 +211,5 @@ fn transfer_asset_v1<P: ToPubkey>(
         transfer_builder.edition(asset.edition);
     }
 
-    let transfer_ix = transfer_builder.instruction();
-
-    send_and_confirm_tx(client, &[payer, authority], &[transfer_ix])
+    transfer_builder.instruction()
 }
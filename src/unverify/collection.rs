@@ -1,24 +1,52 @@
 use mpl_token_metadata::{
-    accounts::MetadataDelegateRecord, hooked::MetadataDelegateRoleSeed,
-    instructions::UnverifyCollectionV1Builder, types::MetadataDelegateRole,
+    accounts::{Metadata, MetadataDelegateRecord},
+    hooked::MetadataDelegateRoleSeed,
+    instructions::UnverifyCollectionV1Builder,
+    types::MetadataDelegateRole,
 };
-use solana_program::instruction::Instruction;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
 
-use crate::transaction::send_and_confirm_tx;
+use crate::{
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::send_and_confirm_tx_with_compute_budget,
+};
 
 use super::*;
 
 pub enum UnverifyCollectionArgs<'a, P1: ToPubkey, P2: ToPubkey> {
     V1 {
-        authority: &'a Keypair,
+        authority: &'a dyn Signer,
         mint: P1,
         collection_mint: P2,
         is_delegate: bool,
+        compute_budget: ComputeBudget,
     },
 }
 
+impl<'a, P1: ToPubkey, P2: ToPubkey> UnverifyCollectionArgs<'a, P1, P2> {
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<UnverifyCollectionArgs<'a, Pubkey, Pubkey>> {
+        let UnverifyCollectionArgs::V1 {
+            authority,
+            mint,
+            collection_mint,
+            is_delegate,
+            compute_budget,
+        } = self;
+
+        Ok(UnverifyCollectionArgs::V1 {
+            authority,
+            mint: mint.to_pubkey()?,
+            collection_mint: collection_mint.to_pubkey()?,
+            is_delegate,
+            compute_budget,
+        })
+    }
+}
+
 pub fn unverify_collection<P1, P2>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: UnverifyCollectionArgs<P1, P2>,
 ) -> Result<Signature>
 where
@@ -31,7 +59,7 @@ where
 }
 
 pub fn unverify_collection_ix<P1, P2>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: UnverifyCollectionArgs<P1, P2>,
 ) -> Result<Instruction>
 where
@@ -43,44 +71,87 @@ where
     }
 }
 
+pub fn unverify_collection_simulate<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCollectionArgs<P1, P2>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    match args {
+        UnverifyCollectionArgs::V1 { .. } => unverify_collection_v1_simulate(client, args),
+    }
+}
+
 fn unverify_collection_v1<P1, P2>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: UnverifyCollectionArgs<P1, P2>,
 ) -> Result<Signature>
 where
     P1: ToPubkey,
     P2: ToPubkey,
 {
-    let UnverifyCollectionArgs::V1 { authority, .. } = args;
+    let UnverifyCollectionArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
 
     let unverify_ix = unverify_collection_v1_ix(client, args)?;
 
-    send_and_confirm_tx(client, &[authority], &[unverify_ix])
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[unverify_ix], &compute_budget)
+}
+
+fn unverify_collection_v1_simulate<P1, P2>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCollectionArgs<P1, P2>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+{
+    let UnverifyCollectionArgs::V1 { authority, .. } = args;
+
+    let ix = unverify_collection_v1_ix(client, args)?;
+
+    simulate_tx(client, &[authority], &[ix])
 }
 
 fn unverify_collection_v1_ix<P1, P2>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: UnverifyCollectionArgs<P1, P2>,
 ) -> Result<Instruction>
 where
     P1: ToPubkey,
     P2: ToPubkey,
 {
+    let args = args.resolve()?;
+    let UnverifyCollectionArgs::V1 { mint, .. } = args;
+
+    let md = Asset::new(mint).get_metadata(client)?;
+
+    Ok(build_unverify_collection_v1_ix(args, md))
+}
+
+/// Build the unverify collection instruction from resolved arguments and the asset's
+/// metadata.
+pub(crate) fn build_unverify_collection_v1_ix(
+    args: UnverifyCollectionArgs<Pubkey, Pubkey>,
+    md: Metadata,
+) -> Instruction {
     let UnverifyCollectionArgs::V1 {
         authority,
         mint,
         collection_mint,
         is_delegate,
+        ..
     } = args;
 
-    let mint = mint.to_pubkey()?;
-    let collection_mint = collection_mint.to_pubkey()?;
-
     let asset = Asset::new(mint);
     let mut collection_asset = Asset::new(collection_mint);
 
-    let md = asset.get_metadata(client)?;
-
     collection_asset.add_edition();
 
     let mut unverify_builder = UnverifyCollectionV1Builder::new();
@@ -100,7 +171,5 @@ where
         unverify_builder.delegate_record(Some(pda_key));
     }
 
-    let ix = unverify_builder.instruction();
-
-    Ok(ix)
+    unverify_builder.instruction()
 }
@@ -1,14 +1,45 @@
-use mpl_token_metadata::instructions::UnverifyCreatorV1Builder;
+use mpl_token_metadata::{accounts::Metadata, instructions::UnverifyCreatorV1Builder};
 
-use crate::transaction::send_and_confirm_tx;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    errors::MetabossError,
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::send_and_confirm_tx_with_compute_budget,
+};
 
 use super::*;
 
 pub enum UnverifyCreatorArgs<'a, P1: ToPubkey> {
-    V1 { authority: &'a Keypair, mint: P1 },
+    V1 {
+        authority: &'a dyn Signer,
+        mint: P1,
+        compute_budget: ComputeBudget,
+    },
+}
+
+impl<'a, P1: ToPubkey> UnverifyCreatorArgs<'a, P1> {
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<UnverifyCreatorArgs<'a, Pubkey>> {
+        let UnverifyCreatorArgs::V1 {
+            authority,
+            mint,
+            compute_budget,
+        } = self;
+
+        Ok(UnverifyCreatorArgs::V1 {
+            authority,
+            mint: mint.to_pubkey()?,
+            compute_budget,
+        })
+    }
 }
 
-pub fn unverify_creator<P1>(client: &RpcClient, args: UnverifyCreatorArgs<P1>) -> Result<Signature>
+pub fn unverify_creator<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<P1>,
+) -> Result<Signature>
 where
     P1: ToPubkey,
 {
@@ -17,16 +48,88 @@ where
     }
 }
 
-fn unverify_creator_v1<P1>(client: &RpcClient, args: UnverifyCreatorArgs<P1>) -> Result<Signature>
+pub fn unverify_creator_ix<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<P1>,
+) -> Result<Instruction>
 where
     P1: ToPubkey,
 {
-    let UnverifyCreatorArgs::V1 { authority, mint } = args;
+    match args {
+        UnverifyCreatorArgs::V1 { .. } => unverify_creator_v1_ix(client, args),
+    }
+}
 
-    let mint = mint.to_pubkey()?;
-    let asset = Asset::new(mint);
+pub fn unverify_creator_simulate<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<P1>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+{
+    match args {
+        UnverifyCreatorArgs::V1 { .. } => unverify_creator_v1_simulate(client, args),
+    }
+}
+
+fn unverify_creator_v1<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<P1>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+{
+    let UnverifyCreatorArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
 
-    let md = asset.get_metadata(client)?;
+    let unverify_ix = unverify_creator_v1_ix(client, args)?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[unverify_ix], &compute_budget)
+}
+
+fn unverify_creator_v1_simulate<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<P1>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+{
+    let UnverifyCreatorArgs::V1 { authority, .. } = args;
+
+    let ix = unverify_creator_v1_ix(client, args)?;
+
+    simulate_tx(client, &[authority], &[ix])
+}
+
+fn unverify_creator_v1_ix<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: UnverifyCreatorArgs<P1>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+{
+    let args = args.resolve()?;
+    let UnverifyCreatorArgs::V1 { mint, .. } = args;
+
+    let md = Asset::new(mint).get_metadata(client)?;
+
+    build_unverify_creator_v1_ix(args, md)
+}
+
+/// Build the unverify creator instruction from resolved arguments and the asset's metadata.
+pub(crate) fn build_unverify_creator_v1_ix(
+    args: UnverifyCreatorArgs<Pubkey>,
+    md: Metadata,
+) -> Result<Instruction> {
+    let UnverifyCreatorArgs::V1 {
+        authority, mint, ..
+    } = args;
+
+    let asset = Asset::new(mint);
 
     let mut unverify_builder = UnverifyCreatorV1Builder::new();
     unverify_builder
@@ -37,10 +140,12 @@ where
         md.token_standard,
         Some(TokenStandard::NonFungible | TokenStandard::ProgrammableNonFungible) | None
     ) {
-        bail!("Only NFTs or pNFTs can have creators be verified");
+        return Err(MetabossError::validation(
+            "Only NFTs or pNFTs can have creators be verified",
+        ));
     }
 
     let unverify_ix = unverify_builder.instruction();
 
-    send_and_confirm_tx(client, &[authority], &[unverify_ix])
+    Ok(unverify_ix)
 }
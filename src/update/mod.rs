@@ -1,24 +1,22 @@
-use anyhow::Result;
 use mpl_token_metadata::{
+    accounts::Metadata,
     instructions::{UpdateV1, UpdateV1InstructionArgs},
     types::{
         AuthorizationData, CollectionDetailsToggle, CollectionToggle, Data, ProgrammableConfig,
         RuleSetToggle, TokenStandard, UsesToggle,
     },
 };
-use solana_client::rpc_client::RpcClient;
 use solana_program::{instruction::Instruction, pubkey::Pubkey};
-use solana_sdk::{
-    compute_budget::ComputeBudgetInstruction,
-    signature::{Keypair, Signature},
-    signer::Signer,
-};
+use solana_sdk::{signature::Signature, signer::Signer};
 
 use crate::{
-    data::{Asset, Priority, UPDATE_COMPUTE_UNITS},
+    data::{Asset, UPDATE_COMPUTE_UNITS},
     decode::ToPubkey,
+    errors::Result,
     nft::get_nft_token_account,
-    transaction::{get_compute_units, send_and_confirm_tx},
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::{send_and_confirm_tx_with_compute_budget, ComputeBudget},
 };
 
 // Wrapper type for the UpdateV1InstructionArgs type from mpl-token-metadata since it doesn't have a `default` implementation.
@@ -81,18 +79,48 @@ impl From<V1UpdateArgs> for UpdateV1InstructionArgs {
 
 pub enum UpdateAssetArgs<'a, P1, P2, P3: ToPubkey> {
     V1 {
-        payer: Option<&'a Keypair>,
-        authority: &'a Keypair,
+        payer: Option<&'a dyn Signer>,
+        authority: &'a dyn Signer,
         mint: P1,
         token: Option<P2>,
         delegate_record: Option<P3>,
         update_args: V1UpdateArgs,
-        priority: Priority,
+        compute_budget: ComputeBudget,
     },
 }
 
+impl<'a, P1, P2, P3> UpdateAssetArgs<'a, P1, P2, P3>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<UpdateAssetArgs<'a, Pubkey, Pubkey, Pubkey>> {
+        let UpdateAssetArgs::V1 {
+            payer,
+            authority,
+            mint,
+            token,
+            delegate_record,
+            update_args,
+            compute_budget,
+        } = self;
+
+        Ok(UpdateAssetArgs::V1 {
+            payer,
+            authority,
+            mint: mint.to_pubkey()?,
+            token: token.map(|t| t.to_pubkey()).transpose()?,
+            delegate_record: delegate_record.map(|t| t.to_pubkey()).transpose()?,
+            update_args,
+            compute_budget,
+        })
+    }
+}
+
 pub fn update_asset<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: UpdateAssetArgs<P1, P2, P3>,
 ) -> Result<Signature>
 where
@@ -106,7 +134,7 @@ where
 }
 
 pub fn update_asset_ix<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: UpdateAssetArgs<P1, P2, P3>,
 ) -> Result<Instruction>
 where
@@ -119,8 +147,22 @@ where
     }
 }
 
+pub fn update_asset_simulate<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: UpdateAssetArgs<P1, P2, P3>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    match args {
+        UpdateAssetArgs::V1 { .. } => update_asset_v1_simulate(client, args),
+    }
+}
+
 fn update_asset_v1<P1, P2, P3>(
-    client: &RpcClient,
+    client: &(impl Rpc + ?Sized),
     args: UpdateAssetArgs<P1, P2, P3>,
 ) -> Result<Signature>
 where
@@ -131,63 +173,57 @@ where
     let UpdateAssetArgs::V1 {
         payer,
         authority,
-        ref priority,
+        ref compute_budget,
         ..
     } = args;
 
     let payer = payer.unwrap_or(authority);
+    let mut compute_budget = compute_budget.clone();
 
-    let micro_lamports = match priority {
-        Priority::None => 20,        // 1       lamports
-        Priority::Low => 20_000,     // 1_000   lamports  ~$1 for 10k updates
-        Priority::Medium => 200_000, // 10_000  lamports  ~$10 for 10k updates
-        Priority::High => 1_000_000, // 50_000  lamports  ~$0.01/update @ $150 SOL
-        Priority::Max => 2_000_000,  // 100_000 lamports  ~$0.02/update @ $150 SOL
-    };
-
-    let signers = vec![payer, authority];
+    let signers: Vec<&dyn Signer> = vec![payer, authority];
 
     let update_ix = update_asset_v1_ix(client, args)?;
-    let units = get_compute_units(client, std::slice::from_ref(&update_ix), &signers)?
-        .unwrap_or(UPDATE_COMPUTE_UNITS as u64);
 
-    let instructions = vec![
-        ComputeBudgetInstruction::set_compute_unit_limit(units as u32),
-        ComputeBudgetInstruction::set_compute_unit_price(micro_lamports),
-        update_ix,
-    ];
+    compute_budget
+        .fallback_compute_units
+        .get_or_insert(UPDATE_COMPUTE_UNITS);
 
-    send_and_confirm_tx(client, &signers, &instructions)
+    send_and_confirm_tx_with_compute_budget(client, &signers, &[update_ix], &compute_budget)
 }
 
-fn update_asset_v1_ix<P1, P2, P3>(
-    client: &RpcClient,
+fn update_asset_v1_simulate<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
     args: UpdateAssetArgs<P1, P2, P3>,
-) -> Result<Instruction>
+) -> Result<SimulationResult>
 where
     P1: ToPubkey,
     P2: ToPubkey,
     P3: ToPubkey,
 {
     let UpdateAssetArgs::V1 {
-        payer,
-        authority,
-        mint,
-        token,
-        delegate_record,
-        update_args,
-        ..
+        payer, authority, ..
     } = args;
 
     let payer = payer.unwrap_or(authority);
 
-    let mint = mint.to_pubkey()?;
-    let mut asset = Asset::new(mint);
+    let ix = update_asset_v1_ix(client, args)?;
 
-    let md = asset.get_metadata(client)?;
+    simulate_tx(client, &[payer, authority], &[ix])
+}
 
-    let token = token.map(|t| t.to_pubkey()).transpose()?;
-    let delegate_record = delegate_record.map(|t| t.to_pubkey()).transpose()?;
+fn update_asset_v1_ix<P1, P2, P3>(
+    client: &(impl Rpc + ?Sized),
+    args: UpdateAssetArgs<P1, P2, P3>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+    P2: ToPubkey,
+    P3: ToPubkey,
+{
+    let args = args.resolve()?;
+    let UpdateAssetArgs::V1 { mint, token, .. } = args;
+
+    let md = Asset::new(mint).get_metadata(client)?;
 
     // We need the token account passed in for pNFT updates.
     let token =
@@ -197,6 +233,29 @@ where
             None
         };
 
+    Ok(build_update_asset_v1_ix(args, token, md))
+}
+
+/// Build the update instruction from resolved arguments, the token account required for
+/// pNFTs and the asset's metadata.
+pub(crate) fn build_update_asset_v1_ix(
+    args: UpdateAssetArgs<Pubkey, Pubkey, Pubkey>,
+    token: Option<Pubkey>,
+    md: Metadata,
+) -> Instruction {
+    let UpdateAssetArgs::V1 {
+        payer,
+        authority,
+        mint,
+        delegate_record,
+        update_args,
+        ..
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+
+    let mut asset = Asset::new(mint);
+
     // Fungibles without a token standard will fail when an edition is passed in, but
     // assets in this call are much more likely to be NonFungible so we assume that and
     // let Token Metadata and God sort it out.
@@ -223,7 +282,7 @@ where
         (None, None)
     };
 
-    let update_ix = UpdateV1 {
+    UpdateV1 {
         payer: payer.pubkey(),
         authority: authority.pubkey(),
         mint: asset.mint,
@@ -236,9 +295,7 @@ where
         authorization_rules,
         authorization_rules_program,
     }
-    .instruction(update_args.into());
-
-    Ok(update_ix)
+    .instruction(update_args.into())
 }
 
 #[cfg(test)]
@@ -1,12 +1,44 @@
-use crate::transaction::send_and_confirm_tx;
+use mpl_token_metadata::accounts::Metadata;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    errors::MetabossError,
+    rpc::Rpc,
+    simulate::{simulate_tx, SimulationResult},
+    transaction::send_and_confirm_tx_with_compute_budget,
+};
 
 use super::*;
 
 pub enum VerifyCreatorArgs<'a, P1: ToPubkey> {
-    V1 { authority: &'a Keypair, mint: P1 },
+    V1 {
+        authority: &'a dyn Signer,
+        mint: P1,
+        compute_budget: ComputeBudget,
+    },
+}
+
+impl<'a, P1: ToPubkey> VerifyCreatorArgs<'a, P1> {
+    /// Convert the address arguments to pubkeys.
+    pub(crate) fn resolve(self) -> Result<VerifyCreatorArgs<'a, Pubkey>> {
+        let VerifyCreatorArgs::V1 {
+            authority,
+            mint,
+            compute_budget,
+        } = self;
+
+        Ok(VerifyCreatorArgs::V1 {
+            authority,
+            mint: mint.to_pubkey()?,
+            compute_budget,
+        })
+    }
 }
 
-pub fn verify_creator<P1>(client: &RpcClient, args: VerifyCreatorArgs<P1>) -> Result<Signature>
+pub fn verify_creator<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<P1>,
+) -> Result<Signature>
 where
     P1: ToPubkey,
 {
@@ -15,16 +47,88 @@ where
     }
 }
 
-fn verify_creator_v1<P1>(client: &RpcClient, args: VerifyCreatorArgs<P1>) -> Result<Signature>
+pub fn verify_creator_ix<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<P1>,
+) -> Result<Instruction>
 where
     P1: ToPubkey,
 {
-    let VerifyCreatorArgs::V1 { authority, mint } = args;
+    match args {
+        VerifyCreatorArgs::V1 { .. } => verify_creator_v1_ix(client, args),
+    }
+}
 
-    let mint = mint.to_pubkey()?;
-    let asset = Asset::new(mint);
+pub fn verify_creator_simulate<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<P1>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+{
+    match args {
+        VerifyCreatorArgs::V1 { .. } => verify_creator_v1_simulate(client, args),
+    }
+}
+
+fn verify_creator_v1<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<P1>,
+) -> Result<Signature>
+where
+    P1: ToPubkey,
+{
+    let VerifyCreatorArgs::V1 {
+        authority,
+        ref compute_budget,
+        ..
+    } = args;
+    let compute_budget = compute_budget.clone();
 
-    let md = asset.get_metadata(client)?;
+    let verify_ix = verify_creator_v1_ix(client, args)?;
+
+    send_and_confirm_tx_with_compute_budget(client, &[authority], &[verify_ix], &compute_budget)
+}
+
+fn verify_creator_v1_simulate<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<P1>,
+) -> Result<SimulationResult>
+where
+    P1: ToPubkey,
+{
+    let VerifyCreatorArgs::V1 { authority, .. } = args;
+
+    let ix = verify_creator_v1_ix(client, args)?;
+
+    simulate_tx(client, &[authority], &[ix])
+}
+
+fn verify_creator_v1_ix<P1>(
+    client: &(impl Rpc + ?Sized),
+    args: VerifyCreatorArgs<P1>,
+) -> Result<Instruction>
+where
+    P1: ToPubkey,
+{
+    let args = args.resolve()?;
+    let VerifyCreatorArgs::V1 { mint, .. } = args;
+
+    let md = Asset::new(mint).get_metadata(client)?;
+
+    build_verify_creator_v1_ix(args, md)
+}
+
+/// Build the verify creator instruction from resolved arguments and the asset's metadata.
+pub(crate) fn build_verify_creator_v1_ix(
+    args: VerifyCreatorArgs<Pubkey>,
+    md: Metadata,
+) -> Result<Instruction> {
+    let VerifyCreatorArgs::V1 {
+        authority, mint, ..
+    } = args;
+
+    let asset = Asset::new(mint);
 
     let mut verify_builder = VerifyCreatorV1Builder::new();
     verify_builder
@@ -35,10 +139,12 @@ where
         md.token_standard,
         Some(TokenStandard::NonFungible | TokenStandard::ProgrammableNonFungible) | None
     ) {
-        bail!("Only NFTs or pNFTs can have creators be verified");
+        return Err(MetabossError::validation(
+            "Only NFTs or pNFTs can have creators be verified",
+        ));
     }
 
     let verify_ix = verify_builder.instruction();
 
-    send_and_confirm_tx(client, &[authority], &[verify_ix])
+    Ok(verify_ix)
 }
@@ -1,15 +1,10 @@
-use anyhow::{bail, Result};
 use mpl_token_metadata::{
     instructions::{VerifyCollectionV1Builder, VerifyCreatorV1Builder},
     types::TokenStandard,
 };
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{
-    signature::{Keypair, Signature},
-    signer::Signer,
-};
+use solana_sdk::{signature::Signature, signer::Signer};
 
-use crate::{data::Asset, decode::ToPubkey};
+use crate::{data::Asset, decode::ToPubkey, errors::Result, transaction::ComputeBudget};
 
 mod collection;
 mod creator;